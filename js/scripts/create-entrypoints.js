@@ -17,6 +17,7 @@ const entrypoints = {
   vercel: "vercel",
   wrappers: "wrappers/index",
   anonymizer: "anonymizer/index",
+  exporters: "exporters/index",
   "wrappers/openai": "wrappers/openai",
   "wrappers/vercel": "wrappers/vercel",
   "singletons/traceable": "singletons/traceable",
@@ -62,6 +62,50 @@ import { raiseForStatus } from "./utils/error.js";
 import { _getFetchImplementation } from "./singletons/fetch.js";
 
 import { stringify as stringifyForTracing } from "./utils/fast-safe-stringify/index.js";
+import {
+  SamplingRule,
+  getSampleRate,
+  getSamplingRulesFromEnv,
+  validateSamplingRules,
+} from "./utils/sampling.js";
+import { WaitUntil, WaitUntilLike, toWaitUntil } from "./utils/wait_until.js";
+import {
+  PayloadLimit,
+  applyPayloadLimit,
+  getPayloadLimitFromEnv,
+  validatePayloadLimit,
+} from "./utils/payload_limits.js";
+import { extractInlineMedia } from "./utils/media.js";
+import {
+  CircuitBreaker,
+  getCircuitBreakerFromEnv,
+} from "./utils/circuit_breaker.js";
+import {
+  COMPRESSION_METHODS,
+  CompressionMethod,
+  compress,
+  negotiateCompression,
+} from "./utils/compression.js";
+
+import {
+  LangSmithExporter,
+  OfflineExporter,
+  TraceBatch,
+  TraceExporter,
+} from "./exporters/index.js";
+
+export type { SamplingRule } from "./utils/sampling.js";
+export type { CompressionMethod } from "./utils/compression.js";
+export type { WaitUntil, WaitUntilLike } from "./utils/wait_until.js";
+export type {
+  PayloadLimit,
+  PayloadLimitAction,
+} from "./utils/payload_limits.js";
+export {
+  CircuitBreaker,
+  type CircuitBreakerConfig,
+  type CircuitState,
+} from "./utils/circuit_breaker.js";
 
 export interface ClientConfig {
   apiUrl?: string;
@@ -77,6 +121,90 @@ export interface ClientConfig {
   blockOnRootRunFinalization?: boolean;
   traceBatchConcurrency?: number;
   fetchOptions?: RequestInit;
+  /**
+   * Ordered rules setting the sample rate of root runs by project, name,
+   * run type, tags or metadata keys. The first matching rule wins, and
+   * LANGSMITH_TRACING_SAMPLING_RATE applies when none match. Defaults to
+   * the LANGSMITH_TRACING_SAMPLING_RULES environment variable.
+   */
+  samplingRules?: SamplingRule[];
+  /**
+   * Where auto-batched traces are sent, e.g. a local file or the console.
+   * Every batch is handed to each exporter. Defaults to the LangSmith API;
+   * include a `LangSmithExporter` to keep sending traces there alongside
+   * other exporters. Requires `autoBatchTracing`. If the
+   * LANGSMITH_TRACING_OFFLINE_DIR environment variable is set, defaults to
+   * writing traces to files in that directory, to be uploaded later with
+   * `uploadTraces`.
+   */
+  exporters?: TraceExporter[];
+  /**
+   * Compress the bodies of batch and multipart ingestion requests. Only used
+   * if the API lists the method in its batch ingest config, falling back to
+   * gzip, and size limits then apply to the compressed body. zstd requires a
+   * runtime whose `CompressionStream` supports it. Defaults to the
+   * LANGSMITH_TRACING_COMPRESSION environment variable.
+   */
+  compression?: CompressionMethod;
+  /**
+   * The maximum number of queued and in-flight runs. Defaults to the
+   * LANGSMITH_TRACING_QUEUE_MAX_ITEMS environment variable, or no limit.
+   */
+  tracingQueueMaxItems?: number;
+  /**
+   * The maximum serialized size of queued and in-flight runs. Defaults to
+   * the LANGSMITH_TRACING_QUEUE_MAX_BYTES environment variable, or no limit.
+   */
+  tracingQueueMaxBytes?: number;
+  /**
+   * What to do with a run that doesn't fit in the tracing queue:
+   * "drop_newest" (the default), "drop_oldest", "block" until there is room
+   * (`createRun` and `updateRun` wait), or "spill" it to a file in
+   * `tracingQueueSpillDir`, to be uploaded later with `uploadTraces`.
+   * Defaults to the LANGSMITH_TRACING_QUEUE_OVERFLOW_POLICY environment
+   * variable.
+   */
+  tracingQueueOverflowPolicy?: TracingQueueOverflowPolicy;
+  /**
+   * The directory runs are spilled to. Defaults to the
+   * LANGSMITH_TRACING_QUEUE_SPILL_DIR environment variable.
+   */
+  tracingQueueSpillDir?: string;
+  /**
+   * A platform hook that keeps serverless invocations alive while traces
+   * send, such as `waitUntil` from `@vercel/functions`. Every queued run is
+   * registered with it, so ending a root run doesn't wait for its trace to
+   * send even if `blockOnRootRunFinalization` is set.
+   *
+   * The hook is bound once, for the lifetime of the client, so only pass one
+   * that works across requests. A Cloudflare Workers `ExecutionContext` is
+   * per request: pass it as the `waitUntil` of each request's root `RunTree`
+   * instead, or of a `traceable` wrapped in the request handler, e.g.
+   * `traceable(fn, { waitUntil: ctx })`, or create a client per request.
+   */
+  waitUntil?: WaitUntilLike;
+  /**
+   * Move input and output values over a size into attachments, or truncate
+   * or summarize them, so oversized runs can still be ingested. Applies to
+   * multipart ingestion. Defaults to the LANGSMITH_TRACING_PAYLOAD_MAX_BYTES
+   * and LANGSMITH_TRACING_PAYLOAD_LIMIT_ACTION environment variables.
+   */
+  payloadLimit?: PayloadLimit;
+  /**
+   * Move base64 images and audio inlined in run inputs and outputs, such as
+   * the data URLs of multimodal messages, into run attachments, and leave an
+   * `attachment://` reference in their place. Applies to multipart ingestion.
+   * Defaults to the LANGSMITH_TRACING_EXTRACT_MEDIA environment variable.
+   */
+  extractMedia?: boolean;
+  /**
+   * Stop sending batches after repeated failed requests, while the API is
+   * down, and probe it before resuming. Batches that aren't sent count as
+   * failed in the `flush()` report. Defaults to the
+   * LANGSMITH_TRACING_CIRCUIT_BREAKER_THRESHOLD and
+   * LANGSMITH_TRACING_CIRCUIT_BREAKER_RESET_TIMEOUT environment variables.
+   */
+  circuitBreaker?: CircuitBreaker;
 }
 
 /**
@@ -284,6 +412,31 @@ type MultipartPart = {
   payload: Blob;
 };
 
+/**
+ * Split multipart parts in two, keeping the parts of each run together.
+ * Returns undefined if the parts all belong to one run.
+ */
+function splitMultipartParts(
+  parts: MultipartPart[]
+): [MultipartPart[], MultipartPart[]] | undefined {
+  // Parts are named {event}.{id}[.{field}] or attachment.{id}.{name}
+  const ids = parts.map((part) => part.name.split(".")[1]);
+  let middle: number | undefined;
+  for (let i = 1; i < ids.length; i += 1) {
+    if (
+      ids[i] !== ids[i - 1] &&
+      (middle === undefined ||
+        Math.abs(2 * i - ids.length) < Math.abs(2 * middle - ids.length))
+    ) {
+      middle = i;
+    }
+  }
+  if (middle === undefined) {
+    return undefined;
+  }
+  return [parts.slice(0, middle), parts.slice(middle)];
+}
+
 export function mergeRuntimeEnvIntoRunCreate(run: RunCreate) {
   const runtimeEnv = getRuntimeEnvironment();
   const envVars = getLangChainEnvVarsMetadata();
@@ -363,6 +516,45 @@ const handle429 = async (response?: Response) => {
   return false;
 };
 
+export type TracingQueueOverflowPolicy =
+  | "drop_oldest"
+  | "drop_newest"
+  | "block"
+  | "spill";
+
+const TRACING_QUEUE_OVERFLOW_POLICIES: TracingQueueOverflowPolicy[] = [
+  "drop_oldest",
+  "drop_newest",
+  "block",
+  "spill",
+];
+
+/** What happened to queued runs while a client flushed or shut down. */
+export interface DeliveryReport {
+  /** Runs that were sent. */
+  delivered: number;
+  /** Runs in batches that failed to send. */
+  failed: number;
+  /** Runs still queued or being sent when the timeout expired. */
+  abandoned: number;
+}
+
+export interface AutoBatchQueueLimits {
+  /** The maximum number of queued and in-flight runs. */
+  maxItems?: number;
+  /** The maximum serialized size of queued and in-flight runs. */
+  maxBytes?: number;
+  /** What to do with a run that doesn't fit. Defaults to "drop_newest". */
+  overflowPolicy?: TracingQueueOverflowPolicy;
+  /** The directory the "spill" policy writes runs that don't fit to. */
+  spillDir?: string;
+}
+
+/**
+ * Runs waiting to be sent in a batch. Runs count against the limits until
+ * the batch they were popped in has been sent, so that an outage can't grow
+ * memory without bound.
+ */
 export class AutoBatchQueue {
   items: {
     action: "create" | "update";
@@ -374,18 +566,126 @@ export class AutoBatchQueue {
 
   sizeBytes = 0;
 
+  inFlightItems = 0;
+
+  inFlightBytes = 0;
+
+  droppedItems = 0;
+
+  droppedBytes = 0;
+
+  spilledItems = 0;
+
+  maxItems?: number;
+
+  maxBytes?: number;
+
+  overflowPolicy: TracingQueueOverflowPolicy;
+
+  /** Resolves once pending writes of spilled runs have finished. */
+  spillWrites: Promise<void> = Promise.resolve();
+
+  private spillExporter?: OfflineExporter;
+
+  private roomWaiters: (() => void)[] = [];
+
+  // Set while a caller woken by waitForRoom has yet to push its run
+  private wokenWaiter = false;
+
+  private warnedFull = false;
+
+  constructor(limits: AutoBatchQueueLimits = {}) {
+    this.maxItems = limits.maxItems;
+    this.maxBytes = limits.maxBytes;
+    this.overflowPolicy = limits.overflowPolicy ?? "drop_newest";
+    if (!TRACING_QUEUE_OVERFLOW_POLICIES.includes(this.overflowPolicy)) {
+      throw new Error(
+        `Unsupported tracing queue overflow policy "${this.overflowPolicy}".`
+      );
+    }
+    if (this.overflowPolicy === "spill") {
+      if (!limits.spillDir) {
+        throw new Error(
+          'The "spill" overflow policy requires a spill directory.'
+        );
+      }
+      this.spillExporter = new OfflineExporter(limits.spillDir);
+    }
+  }
+
   peek() {
     return this.items[0];
   }
 
+  /** Whether there is room for another run of the given size. */
+  hasRoom(size = 0): boolean {
+    const items = this.items.length + this.inFlightItems;
+    if (items === 0) {
+      // A run over the byte budget is only accepted into an empty queue
+      return true;
+    }
+    return (
+      (this.maxItems === undefined || items < this.maxItems) &&
+      (this.maxBytes === undefined ||
+        this.sizeBytes + this.inFlightBytes + size <= this.maxBytes)
+    );
+  }
+
+  /**
+   * With the "block" policy, resolves once the queue has room for another
+   * run. Resolves immediately otherwise.
+   *
+   * Blocked callers are let through in order, one at a time: the next one is
+   * only woken once the previous one pushed its run, if there is still room.
+   */
+  async waitForRoom(): Promise<void> {
+    if (
+      this.overflowPolicy !== "block" ||
+      (this.hasRoom() && !this.wokenWaiter && this.roomWaiters.length === 0)
+    ) {
+      return;
+    }
+    await new Promise<void>((resolve) => this.roomWaiters.push(resolve));
+  }
+
+  private wakeNextWaiter() {
+    if (this.wokenWaiter || !this.hasRoom()) {
+      return;
+    }
+    const resolve = this.roomWaiters.shift();
+    if (resolve !== undefined) {
+      this.wokenWaiter = true;
+      resolve();
+    }
+  }
+
   push(item: AutoBatchQueueItem): Promise<void> {
+    const size = stringifyForTracing(item.item).length;
+    if (!this.hasRoom(size)) {
+      if (this.overflowPolicy === "spill") {
+        return this.spill(item, size);
+      }
+      if (this.overflowPolicy === "drop_oldest") {
+        while (!this.hasRoom(size) && this.items.length > 0) {
+          // eslint-disable-next-line @typescript-eslint/no-non-null-assertion
+          const dropped = this.items.shift()!;
+          this.sizeBytes -= dropped.size;
+          this.recordDrop(dropped.size);
+          dropped.itemPromiseResolve();
+        }
+      }
+      // Blocked callers wait for room before pushing, so let them through
+      if (this.overflowPolicy !== "block" && !this.hasRoom(size)) {
+        this.recordDrop(size);
+        return Promise.resolve();
+      }
+    }
     let itemPromiseResolve;
     const itemPromise = new Promise<void>((resolve) => {
       // Setting itemPromiseResolve is synchronous with promise creation:
       // https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Global_Objects/Promise/Promise
       itemPromiseResolve = resolve;
     });
-    const size = stringifyForTracing(item.item).length;
     this.items.push({
       action: item.action,
       payload: item.item,
@@ -395,6 +695,10 @@ export class AutoBatchQueue {
       size,
     });
     this.sizeBytes += size;
+    if (this.overflowPolicy === "block") {
+      this.wokenWaiter = false;
+      this.wakeNextWaiter();
+    }
     return itemPromise;
   }
 
@@ -424,11 +728,48 @@ export class AutoBatchQueue {
       poppedSizeBytes += item.size;
       this.sizeBytes -= item.size;
     }
+    this.inFlightItems += popped.length;
+    this.inFlightBytes += poppedSizeBytes;
     return [
       popped.map((it) => ({ action: it.action, item: it.payload })),
-      () => popped.forEach((it) => it.itemPromiseResolve()),
+      () => {
+        popped.forEach((it) => it.itemPromiseResolve());
+        this.inFlightItems -= popped.length;
+        this.inFlightBytes -= poppedSizeBytes;
+        if (this.items.length + this.inFlightItems === 0) {
+          this.warnedFull = false;
+        }
+        this.wakeNextWaiter();
+      },
     ];
   }
+
+  private spill(item: AutoBatchQueueItem, size: number): Promise<void> {
+    this.spilledItems += 1;
+    const batch =
+      item.action === "create"
+        ? { runCreates: [item.item as RunCreate], runUpdates: [] }
+        : { runCreates: [], runUpdates: [item.item as RunUpdate] };
+    // eslint-disable-next-line @typescript-eslint/no-non-null-assertion
+    const write = this.spillExporter!.export(batch).catch((e) => {
+      this.spilledItems -= 1;
+      this.recordDrop(size);
+      console.warn(`Failed to spill run to disk: ${e}`);
+    });
+    this.spillWrites = this.spillWrites.then(() => write);
+    return write;
+  }
+
+  private recordDrop(size: number) {
+    this.droppedItems += 1;
+    this.droppedBytes += size;
+    if (!this.warnedFull) {
+      this.warnedFull = true;
+      console.warn(
+        `[WARNING]: LangSmith tracing queue is full. Dropping runs with the "${this.overflowPolicy}" policy until it drains.`
+      );
+    }
+  }
 }
 
 // 20 MB
@@ -457,20 +798,35 @@ export class Client {
 
   private tracingSampleRate?: number;
 
+  private samplingRules: SamplingRule[];
+
+  private exporters?: TraceExporter[];
+
   private filteredPostUuids = new Set();
 
   private autoBatchTracing = true;
 
-  private autoBatchQueue = new AutoBatchQueue();
+  private autoBatchQueue: AutoBatchQueue;
 
   private autoBatchTimeout: ReturnType<typeof setTimeout> | undefined;
 
+  private pendingBatches = new Set<Promise<void>>();
+
+  private deliveredRuns = 0;
+
+  private failedRuns = 0;
+
   private autoBatchInitialDelayMs = 250;
 
   private autoBatchAggregationDelayMs = 50;
 
   private batchSizeBytesLimit?: number;
 
+  private compression?: CompressionMethod;
+
+  // The compressed size of recent requests relative to their raw size
+  private compressionRatio = 1;
+
   private fetchOptions: RequestInit;
 
   private settings: Promise<LangSmithSettings> | null;
@@ -480,6 +836,14 @@ export class Client {
 
   private traceBatchConcurrency = 5;
 
+  private waitUntil?: WaitUntil;
+
+  private payloadLimit?: PayloadLimit;
+
+  private extractMedia: boolean;
+
+  private circuitBreaker?: CircuitBreaker;
+
   private _serverInfo: RecordStringAny | undefined;
 
   // eslint-disable-next-line @typescript-eslint/no-explicit-any
@@ -489,6 +853,9 @@ export class Client {
     const defaultConfig = Client.getDefaultClientConfig();
 
     this.tracingSampleRate = getTracingSamplingRate();
+    this.samplingRules = config.samplingRules
+      ? validateSamplingRules(config.samplingRules)
+      : getSamplingRulesFromEnv();
     this.apiUrl = trimQuotes(config.apiUrl ?? defaultConfig.apiUrl) ?? "";
     if (this.apiUrl.endsWith("/")) {
       this.apiUrl = this.apiUrl.slice(0, -1);
@@ -520,8 +887,68 @@ export class Client {
     this.autoBatchTracing = config.autoBatchTracing ?? this.autoBatchTracing;
     this.blockOnRootRunFinalization =
       config.blockOnRootRunFinalization ?? this.blockOnRootRunFinalization;
+    this.waitUntil = toWaitUntil(config.waitUntil);
+    this.payloadLimit = config.payloadLimit
+      ? validatePayloadLimit(config.payloadLimit)
+      : getPayloadLimitFromEnv();
+    this.extractMedia =
+      config.extractMedia ??
+      getLangSmithEnvironmentVariable("TRACING_EXTRACT_MEDIA") === "true";
+    this.circuitBreaker = config.circuitBreaker ?? getCircuitBreakerFromEnv();
     this.batchSizeBytesLimit = config.batchSizeBytesLimit;
+    const compression =
+      config.compression ??
+      (getLangSmithEnvironmentVariable("TRACING_COMPRESSION") as
+        | CompressionMethod
+        | undefined);
+    if (compression && !COMPRESSION_METHODS.includes(compression)) {
+      const methods = COMPRESSION_METHODS.join(", ");
+      throw new Error(
+        `Unsupported compression method "${compression}". Expected one of ${methods}.`
+      );
+    }
+    this.compression = compression || undefined;
+    const envLimit = (name: string) => {
+      const value = getLangSmithEnvironmentVariable(name);
+      return value ? parseInt(value, 10) : undefined;
+    };
+    this.autoBatchQueue = new AutoBatchQueue({
+      maxItems:
+        config.tracingQueueMaxItems ?? envLimit("TRACING_QUEUE_MAX_ITEMS"),
+      maxBytes:
+        config.tracingQueueMaxBytes ?? envLimit("TRACING_QUEUE_MAX_BYTES"),
+      overflowPolicy:
+        config.tracingQueueOverflowPolicy ??
+        (getLangSmithEnvironmentVariable("TRACING_QUEUE_OVERFLOW_POLICY") as
+          | TracingQueueOverflowPolicy
+          | undefined),
+      spillDir:
+        config.tracingQueueSpillDir ??
+        getLangSmithEnvironmentVariable("TRACING_QUEUE_SPILL_DIR"),
+    });
     this.fetchOptions = config.fetchOptions || {};
+    const offlineDir = getLangSmithEnvironmentVariable("TRACING_OFFLINE_DIR");
+    if (
+      config.exporters === undefined &&
+      this.autoBatchTracing &&
+      offlineDir
+    ) {
+      this.exporters = [new OfflineExporter(offlineDir)];
+    }
+    if (config.exporters !== undefined) {
+      if (!this.autoBatchTracing) {
+        throw new Error(
+          "Trace exporters require autoBatchTracing to be enabled."
+        );
+      }
+      for (const exporter of config.exporters) {
+        // eslint-disable-next-line no-instanceof/no-instanceof
+        if (exporter instanceof LangSmithExporter && !exporter.client) {
+          exporter.client = this;
+        }
+      }
+      this.exporters = config.exporters;
+    }
   }
 
   public static getDefaultClientConfig(): {
@@ -724,7 +1151,10 @@ export class Client {
     runs: CreateRunParams[] | UpdateRunParams[],
     patch = false
   ) {
-    if (this.tracingSampleRate === undefined) {
+    if (
+      this.tracingSampleRate === undefined &&
+      this.samplingRules.length === 0
+    ) {
       return runs;
     }
 
@@ -741,11 +1171,19 @@ export class Client {
     } else {
       const sampled = [];
       for (const run of runs) {
-        if (
-          (run.id !== run.trace_id &&
-            !this.filteredPostUuids.has(run.trace_id)) ||
-          Math.random() < this.tracingSampleRate
-        ) {
+        let keep: boolean;
+        if (run.id !== run.trace_id) {
+          // Child runs follow the decision made for their trace
+          keep = !this.filteredPostUuids.has(run.trace_id);
+        } else {
+          const sampleRate = getSampleRate(
+            this.samplingRules,
+            run as KVMap,
+            this.tracingSampleRate
+          );
+          keep = Math.random() < sampleRate;
+        }
+        if (keep) {
           sampled.push(run);
         } else {
           this.filteredPostUuids.add(run.id);
@@ -755,7 +1193,29 @@ export class Client {
     }
   }
 
+  private _exportsToLangSmith(): boolean {
+    return (
+      this.exporters === undefined ||
+      // eslint-disable-next-line no-instanceof/no-instanceof
+      this.exporters.some((exporter) => exporter instanceof LangSmithExporter)
+    );
+  }
+
   private async _getBatchSizeLimitBytes(): Promise<number> {
+    if (!this._exportsToLangSmith()) {
+      // Batches never reach the API, so don't ask it for its limits
+      return this.batchSizeBytesLimit ?? DEFAULT_BATCH_SIZE_LIMIT_BYTES;
+    }
+    const sizeLimitBytes = await this._getRequestSizeLimitBytes();
+    if (await this._getCompression()) {
+      // The limit applies to the compressed body, so fit as many runs in a
+      // batch as the compression of recent requests allows
+      return Math.floor(sizeLimitBytes / this.compressionRatio);
+    }
+    return sizeLimitBytes;
+  }
+
+  private async _getRequestSizeLimitBytes(): Promise<number> {
     const serverInfo = await this._ensureServerInfo();
     return (
       this.batchSizeBytesLimit ??
@@ -764,6 +1224,33 @@ export class Client {
     );
   }
 
+  private async _getCompression(): Promise<CompressionMethod | undefined> {
+    if (!this.compression) {
+      return undefined;
+    }
+    const serverInfo = await this._ensureServerInfo();
+    return negotiateCompression(
+      this.compression,
+      serverInfo.batch_ingest_config?.supported_compression_methods
+    );
+  }
+
+  private async _compressRequest(
+    body: Uint8Array,
+    method: CompressionMethod
+  ): Promise<Uint8Array> {
+    const compressed = await compress(body, method);
+    if (body.length) {
+      const ratio = compressed.length / body.length;
+      // Adapt quickly to batches that compress worse than earlier ones
+      this.compressionRatio = Math.max(
+        ratio,
+        (this.compressionRatio + ratio) / 2
+      );
+    }
+    return compressed;
+  }
+
   private drainAutoBatchQueue(batchSizeLimit: number) {
     while (this.autoBatchQueue.items.length > 0) {
       const [batch, done] = this.autoBatchQueue.pop(batchSizeLimit);
@@ -771,7 +1258,9 @@ export class Client {
         done();
         break;
       }
-      void this._processBatch(batch, done).catch(console.error);
+      const processing = this._processBatch(batch, done).catch(console.error);
+      this.pendingBatches.add(processing);
+      void processing.finally(() => this.pendingBatches.delete(processing));
     }
   }
 
@@ -780,6 +1269,7 @@ export class Client {
       done();
       return;
     }
+    let delivered = false;
     try {
       const ingestParams = {
         runCreates: batch
@@ -789,17 +1279,63 @@ export class Client {
           .filter((item) => item.action === "update")
           .map((item) => item.item) as RunUpdate[],
       };
-      const serverInfo = await this._ensureServerInfo();
-      if (serverInfo?.batch_ingest_config?.use_multipart_endpoint) {
-        await this.multipartIngestRuns(ingestParams);
+      if (this.exporters !== undefined) {
+        delivered = await this._exportBatch(this.exporters, ingestParams);
       } else {
-        await this.batchIngestRuns(ingestParams);
+        delivered = await this._ingestBatch(ingestParams);
       }
     } finally {
+      if (delivered) {
+        this.deliveredRuns += batch.length;
+      } else {
+        this.failedRuns += batch.length;
+      }
       done();
     }
   }
 
+  /** Returns whether every exporter exported the batch. */
+  private async _exportBatch(
+    exporters: TraceExporter[],
+    batch: TraceBatch
+  ): Promise<boolean> {
+    const results = await Promise.all(
+      exporters.map(async (exporter) => {
+        try {
+          await exporter.export(batch);
+          return true;
+        } catch (e) {
+          console.warn(
+            `Failed to export traces with ${exporter.constructor.name}: ${e}`
+          );
+          return false;
+        }
+      })
+    );
+    return results.every(Boolean);
+  }
+
+  /**
+   * Send a batch to the LangSmith API, using the multipart endpoint if the
+   * server supports it.
+   * @returns Whether the server accepted the batch.
+   */
+  async _ingestBatch(batch: TraceBatch): Promise<boolean> {
+    if (this.circuitBreaker && !this.circuitBreaker.allowRequest()) {
+      this.circuitBreaker.recordShortCircuited(
+        batch.runCreates.length + batch.runUpdates.length
+      );
+      return false;
+    }
+    const serverInfo = await this._ensureServerInfo();
+    if (serverInfo?.batch_ingest_config?.use_multipart_endpoint) {
+      return this.multipartIngestRuns(batch);
+    } else {
+      await this.batchIngestRuns(batch);
+      return true;
+    }
+  }
+
   private async processRunOperation(item: AutoBatchQueueItem) {
     const oldTimeout = this.autoBatchTimeout;
     clearTimeout(this.autoBatchTimeout);
@@ -808,6 +1344,7 @@ export class Client {
       item.item = mergeRuntimeEnvIntoRunCreate(item.item as RunCreate);
     }
     const itemPromise = this.autoBatchQueue.push(item);
+    this.waitUntil?.(itemPromise);
     const sizeLimitBytes = await this._getBatchSizeLimitBytes();
     if (this.autoBatchQueue.sizeBytes > sizeLimitBytes) {
       this.drainAutoBatchQueue(sizeLimitBytes);
@@ -886,6 +1423,7 @@ export class Client {
       runCreate.trace_id !== undefined &&
       runCreate.dotted_order !== undefined
     ) {
+      await this.autoBatchQueue.waitForRoom();
       void this.processRunOperation({
         action: "create",
         item: runCreate,
@@ -998,18 +1536,26 @@ export class Client {
   }
 
   private async _postBatchIngestRuns(body: string) {
-    const headers = {
+    const headers: Record<string, string> = {
       ...this.headers,
       "Content-Type": "application/json",
       Accept: "application/json",
     };
-    const response = await this.batchIngestCaller.call(
-      _getFetchImplementation(),
+    let requestBody: string | Uint8Array = body;
+    const compression = await this._getCompression();
+    if (compression) {
+      requestBody = await this._compressRequest(
+        new TextEncoder().encode(body),
+        compression
+      );
+      headers["Content-Encoding"] = compression;
+    }
+    const response = await this._sendIngestRequest(
       `${this.apiUrl}/runs/batch`,
       {
         method: "POST",
         headers,
-        body: body,
+        body: requestBody,
         signal: AbortSignal.timeout(this.timeout_ms),
         ...this.fetchOptions,
       }
@@ -1017,9 +1563,29 @@ export class Client {
     await raiseForStatus(response, "batch create run", true);
   }
 
+  /** Send an ingestion request, recording its outcome with the breaker. */
+  private async _sendIngestRequest(
+    url: string,
+    init: RequestInit
+  ): Promise<Response> {
+    let response: Response | undefined;
+    try {
+      response = await this.batchIngestCaller.call(
+        _getFetchImplementation(),
+        url,
+        init
+      );
+      return response;
+    } finally {
+      this.circuitBreaker?.recordResponse(response);
+    }
+  }
+
   /**
    * Batch ingest/upsert multiple runs in the Langsmith system.
    * @param runs
+   * @returns Whether the server accepted the runs. Failures are logged
+   * rather than thrown.
    */
   public async multipartIngestRuns({
     runCreates,
@@ -1029,7 +1595,7 @@ export class Client {
     runUpdates?: RunUpdate[];
   }) {
     if (runCreates === undefined && runUpdates === undefined) {
-      return;
+      return true;
     }
     // transform and convert to dicts
     const allAttachments: Record<
@@ -1046,12 +1612,15 @@ export class Client {
         allAttachments[preparedCreate.id] = preparedCreate.attachments;
       }
       delete preparedCreate.attachments;
+      this._extractRunMedia(preparedCreate, allAttachments);
       preparedCreateParams.push(preparedCreate);
     }
 
     let preparedUpdateParams = [];
     for (const update of runUpdates ?? []) {
-      preparedUpdateParams.push(this.prepareRunCreateOrUpdateInputs(update));
+      const preparedUpdate = this.prepareRunCreateOrUpdateInputs(update);
+      this._extractRunMedia(preparedUpdate, allAttachments);
+      preparedUpdateParams.push(preparedUpdate);
     }
 
     // require trace_id and dotted_order
@@ -1105,7 +1674,7 @@ export class Client {
       preparedCreateParams.length === 0 &&
       preparedUpdateParams.length === 0
     ) {
-      return;
+      return true;
     }
     // send the runs in multipart requests
     const accumulatedContext: string[] = [];
@@ -1131,7 +1700,26 @@ export class Client {
           if (value === undefined) {
             continue;
           }
-          const stringifiedValue = stringifyForTracing(value);
+          let stringifiedValue = stringifyForTracing(value);
+          if (
+            this.payloadLimit &&
+            key !== "events" &&
+            new TextEncoder().encode(stringifiedValue).length >
+              this.payloadLimit.maxBytes
+          ) {
+            const [limited, offloaded] = applyPayloadLimit(
+              this.payloadLimit,
+              key,
+              value
+            );
+            stringifiedValue = stringifyForTracing(limited);
+            if (payload.id !== undefined) {
+              allAttachments[payload.id] = {
+                ...allAttachments[payload.id],
+                ...offloaded,
+              };
+            }
+          }
           accumulatedParts.push({
             name: `${method}.${payload.id}.${key}`,
             payload: new Blob([stringifiedValue], {
@@ -1160,31 +1748,77 @@ export class Client {
         accumulatedContext.push(`trace=${payload.trace_id},id=${payload.id}`);
       }
     }
-    await this._sendMultipartRequest(
+    return this._sendMultipartRequest(
       accumulatedParts,
       accumulatedContext.join("; ")
     );
   }
 
-  private async _sendMultipartRequest(parts: MultipartPart[], context: string) {
+  /** Move the inline media of a prepared run into its attachments. */
+  private _extractRunMedia(
+    run: RunCreate | RunUpdate,
+    allAttachments: Record<string, Record<string, [string, Uint8Array]>>
+  ) {
+    if (!this.extractMedia || run.id === undefined) {
+      return;
+    }
+    for (const field of ["inputs", "outputs"] as const) {
+      if (run[field] === undefined) {
+        continue;
+      }
+      const [values, media] = extractInlineMedia(field, run[field]);
+      if (Object.keys(media).length > 0) {
+        run[field] = values as KVMap;
+        allAttachments[run.id] = { ...allAttachments[run.id], ...media };
+      }
+    }
+  }
+
+  /** Returns whether the server accepted every part. */
+  private async _sendMultipartRequest(
+    parts: MultipartPart[],
+    context: string
+  ): Promise<boolean> {
     try {
       const formData = new FormData();
       for (const part of parts) {
         formData.append(part.name, part.payload);
       }
-      await this.batchIngestCaller.call(
-        _getFetchImplementation(),
+      const headers: Record<string, string> = { ...this.headers };
+      let body: FormData | Uint8Array = formData;
+      const compression = await this._getCompression();
+      if (compression) {
+        // Encode the form ourselves to compress it, keeping its boundary
+        const encoded = new Response(formData);
+        body = await this._compressRequest(
+          new Uint8Array(await encoded.arrayBuffer()),
+          compression
+        );
+        // Split requests whose compressed body is over the limit between runs
+        const halves = splitMultipartParts(parts);
+        if (halves && body.length > (await this._getRequestSizeLimitBytes())) {
+          let delivered = true;
+          for (const half of halves) {
+            delivered =
+              (await this._sendMultipartRequest(half, context)) && delivered;
+          }
+          return delivered;
+        }
+        headers["Content-Type"] = encoded.headers.get("Content-Type") ?? "";
+        headers["Content-Encoding"] = compression;
+      }
+      const response = await this._sendIngestRequest(
         `${this.apiUrl}/runs/multipart`,
         {
           method: "POST",
-          headers: {
-            ...this.headers,
-          },
-          body: formData,
+          headers,
+          body,
           signal: AbortSignal.timeout(this.timeout_ms),
           ...this.fetchOptions,
         }
       );
+      await raiseForStatus(response, "ingest multipart runs", true);
+      return true;
     } catch (e) {
       let errorMessage = "Failed to multipart ingest runs";
       // eslint-disable-next-line no-instanceof/no-instanceof
@@ -1194,6 +1828,7 @@ export class Client {
         errorMessage += `: ${String(e)}`;
       }
       console.warn(`${errorMessage.trim()}\n\nContext: ${context}`);
+      return false;
     }
   }
 
@@ -1216,10 +1851,12 @@ export class Client {
       data.trace_id !== undefined &&
       data.dotted_order !== undefined
     ) {
+      await this.autoBatchQueue.waitForRoom();
       if (
         run.end_time !== undefined &&
         data.parent_run_id === undefined &&
-        this.blockOnRootRunFinalization
+        this.blockOnRootRunFinalization &&
+        this.waitUntil === undefined
       ) {
         // Trigger batches as soon as a root trace ends and wait to ensure trace finishes
         // in serverless environments.
@@ -4166,7 +4803,84 @@ export class Client {
   public awaitPendingTraceBatches() {
     return Promise.all([
       ...this.autoBatchQueue.items.map(({ itemPromise }) => itemPromise),
+      this.autoBatchQueue.spillWrites,
       this.batchIngestCaller.queue.onIdle(),
     ]);
   }
+
+  /**
+   * Sends the queued runs right away and waits for them, for at most
+   * `timeoutMs` milliseconds.
+   *
+   * @example
+   * ```
+   * const report = await client.flush(5000);
+   * if (report.abandoned > 0) {
+   *   console.warn(`${report.abandoned} runs weren't sent in time`);
+   * }
+   * ```
+   *
+   * @param timeoutMs The maximum time to wait. By default, waits until every
+   * queued run was sent.
+   * @returns How many runs were delivered and failed while waiting, and how
+   * many were still pending at the timeout.
+   */
+  public async flush(timeoutMs?: number): Promise<DeliveryReport> {
+    const delivered = this.deliveredRuns;
+    const failed = this.failedRuns;
+    let timer: ReturnType<typeof setTimeout> | undefined;
+    const timeout =
+      timeoutMs === undefined
+        ? []
+        : [
+            new Promise<void>((resolve) => {
+              timer = setTimeout(resolve, timeoutMs);
+            }),
+          ];
+    const sent = (async () => {
+      if (this.autoBatchQueue.items.length > 0) {
+        // Don't wait for the batch timer
+        clearTimeout(this.autoBatchTimeout);
+        this.autoBatchTimeout = undefined;
+        this.drainAutoBatchQueue(await this._getBatchSizeLimitBytes());
+      }
+      await Promise.all([
+        ...this.pendingBatches,
+        this.awaitPendingTraceBatches(),
+      ]);
+    })();
+    await Promise.race([sent, ...timeout]);
+    clearTimeout(timer);
+    return {
+      delivered: this.deliveredRuns - delivered,
+      failed: this.failedRuns - failed,
+      abandoned:
+        this.autoBatchQueue.items.length + this.autoBatchQueue.inFlightItems,
+    };
+  }
+
+  /**
+   * Flushes the queued runs, then shuts down the client's exporters. Call it
+   * before a serverless handler or CLI tool exits. The client shouldn't be
+   * used for tracing afterwards.
+   *
+   * @param timeoutMs The maximum time to wait for queued runs to be sent.
+   * @returns How many runs were delivered and failed while shutting down, and
+   * how many were abandoned.
+   */
+  public async shutdown(timeoutMs?: number): Promise<DeliveryReport> {
+    const report = await this.flush(timeoutMs);
+    await Promise.all(
+      (this.exporters ?? []).map(async (exporter) => {
+        try {
+          await exporter.shutdown?.();
+        } catch (e) {
+          console.warn(
+            `Failed to shut down ${exporter.constructor.name}: ${e}`
+          );
+        }
+      })
+    );
+    return report;
+  }
 }
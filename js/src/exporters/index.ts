@@ -0,0 +1,432 @@
+import { v4 as uuidv4 } from "uuid";
+import type { Client } from "../client.js";
+import type { KVMap, RunCreate, RunUpdate } from "../schemas.js";
+import { stringify as stringifyForTracing } from "../utils/fast-safe-stringify/index.js";
+
+/**
+ * A batch of runs drained from the client's auto-batch queue.
+ * Inputs and outputs have already been through `hideInputs` and
+ * `hideOutputs`. The create and update of a run may arrive in
+ * different batches.
+ */
+export interface TraceBatch {
+  runCreates: RunCreate[];
+  runUpdates: RunUpdate[];
+}
+
+/**
+ * Receives batches of traces from a client. Exporters share the run objects
+ * in a batch and must not mutate them. Throw to report a failed export.
+ */
+export interface TraceExporter {
+  export(batch: TraceBatch): Promise<void>;
+  /** Called by `client.shutdown()` once the queued runs were exported. */
+  shutdown?(): Promise<void>;
+}
+
+/**
+ * Sends batches to the LangSmith API. This is what a client does when no
+ * exporters are given. Include it to keep sending traces to LangSmith
+ * alongside other exporters. When no client is given, the exporter sends
+ * through the client it is passed to.
+ *
+ * @example
+ * ```ts
+ * import { Client } from "langsmith";
+ * import { JSONLFileExporter, LangSmithExporter } from "langsmith/exporters";
+ *
+ * const client = new Client({
+ *   exporters: [new LangSmithExporter(), new JSONLFileExporter("traces.jsonl")],
+ * });
+ * ```
+ */
+export class LangSmithExporter implements TraceExporter {
+  client?: Client;
+
+  constructor(fields?: { client?: Client }) {
+    this.client = fields?.client;
+  }
+
+  async export(batch: TraceBatch): Promise<void> {
+    if (this.client === undefined) {
+      throw new Error("LangSmithExporter is not attached to a client.");
+    }
+    if (!(await this.client._ingestBatch(batch))) {
+      throw new Error("LangSmith did not accept the batch.");
+    }
+  }
+}
+
+function encodeAttachments<T extends RunCreate | RunUpdate>(payload: T): T {
+  if (!("attachments" in payload) || payload.attachments === undefined) {
+    return payload;
+  }
+  const attachments: Record<string, [string, string]> = {};
+  for (const [name, [contentType, content]] of Object.entries(
+    payload.attachments
+  )) {
+    attachments[name] = [contentType, Buffer.from(content).toString("base64")];
+  }
+  return { ...payload, attachments };
+}
+
+function decodeAttachments(payload: KVMap) {
+  if (payload.attachments === undefined) {
+    return;
+  }
+  const attachments: Record<string, [string, Uint8Array]> = {};
+  for (const [name, [contentType, content]] of Object.entries(
+    payload.attachments as Record<string, [string, string]>
+  )) {
+    attachments[name] = [
+      contentType,
+      new Uint8Array(Buffer.from(content, "base64")),
+    ];
+  }
+  payload.attachments = attachments;
+}
+
+/**
+ * Appends every run event to a JSON Lines file, one
+ * `{"event": "post" | "patch", "payload": {...}}` object per line.
+ * Attachment contents are base64-encoded. Requires a runtime with the
+ * `node:fs` module.
+ */
+export class JSONLFileExporter implements TraceExporter {
+  path: string;
+
+  private writes: Promise<void> = Promise.resolve();
+
+  constructor(path: string) {
+    this.path = path;
+  }
+
+  async export(batch: TraceBatch): Promise<void> {
+    const lines = [
+      ...batch.runCreates.map((payload) => ({
+        event: "post",
+        payload: encodeAttachments(payload),
+      })),
+      ...batch.runUpdates.map((payload) => ({ event: "patch", payload })),
+    ]
+      .map((line) => `${stringifyForTracing(line)}\n`)
+      .join("");
+    // Chain writes so that concurrent batches are appended in order
+    const write = this.writes.then(async () => {
+      const fs = await import("node:fs/promises");
+      const { dirname } = await import("node:path");
+      await fs.mkdir(dirname(this.path), { recursive: true });
+      await fs.appendFile(this.path, lines);
+    });
+    this.writes = write.catch(() => undefined);
+    return write;
+  }
+}
+
+/**
+ * Writes traces to a new JSON Lines file in a directory, to be uploaded
+ * later with `uploadTraces`. Lines hold the same `post` and `patch` payloads
+ * the client would send to the multipart ingest endpoint. Set the
+ * `LANGSMITH_TRACING_OFFLINE_DIR` environment variable to have clients use
+ * this exporter by default.
+ */
+export class OfflineExporter extends JSONLFileExporter {
+  directory: string;
+
+  constructor(directory: string) {
+    const name = `${String(Date.now()).padStart(15, "0")}-${
+      typeof process === "undefined" ? 0 : process.pid
+    }-${uuidv4().slice(0, 8)}.jsonl`;
+    super(`${directory.replace(/\/+$/, "")}/${name}`);
+    this.directory = directory;
+  }
+}
+
+export interface UploadResult {
+  /** The files that were read. */
+  files: string[];
+  /** The number of traces uploaded. */
+  traces: number;
+  /** The number of runs uploaded. */
+  runs: number;
+  /** The number of traces whose upload failed. */
+  failedTraces: number;
+  /** Unparseable lines, or runs missing a trace ID or dotted order. */
+  skippedLines: number;
+}
+
+/**
+ * Uploads traces captured by an `OfflineExporter`.
+ *
+ * The patches of each run are merged into its post. Runs are grouped by
+ * `trace_id` and sent in `dotted_order`, so that parents are created before
+ * their children and a trace is never split across requests.
+ *
+ * @example
+ * ```ts
+ * import { Client } from "langsmith";
+ * import { uploadTraces } from "langsmith/exporters";
+ *
+ * const result = await uploadTraces({
+ *   path: "./traces",
+ *   client: new Client(),
+ *   projectName: "nightly-eval",
+ * });
+ * ```
+ */
+export async function uploadTraces(params: {
+  /** A directory of `.jsonl` files, a single file, or a list of files. */
+  path: string | string[];
+  client: Client;
+  /** Upload every run into this project instead of the one it was traced to. */
+  projectName?: string;
+  /** The maximum number of runs per request. Defaults to 100. */
+  batchSize?: number;
+  /** Whether to delete the files once every trace was uploaded. */
+  deleteFiles?: boolean;
+}): Promise<UploadResult> {
+  const { client, projectName, batchSize = 100, deleteFiles = false } = params;
+  const fs = await import("node:fs/promises");
+  const { join } = await import("node:path");
+  let files: string[];
+  if (Array.isArray(params.path)) {
+    files = params.path;
+  } else if ((await fs.stat(params.path)).isDirectory()) {
+    files = (await fs.readdir(params.path))
+      .filter((name) => name.endsWith(".jsonl"))
+      .sort()
+      .map((name) => join(params.path as string, name));
+  } else {
+    files = [params.path];
+  }
+  const result: UploadResult = {
+    files,
+    traces: 0,
+    runs: 0,
+    failedTraces: 0,
+    skippedLines: 0,
+  };
+
+  const runs = new Map<string, KVMap>();
+  const posted = new Set<string>();
+  for (const file of files) {
+    for (const line of (await fs.readFile(file, "utf-8")).split("\n")) {
+      if (!line.trim()) {
+        continue;
+      }
+      let event: string;
+      let payload: KVMap;
+      try {
+        ({ event, payload } = JSON.parse(line));
+      } catch (e) {
+        // e.g. a partially written line from a crash
+        result.skippedLines += 1;
+        continue;
+      }
+      if (event === "post") {
+        posted.add(payload.id);
+      }
+      mergeRuns([payload], runs);
+    }
+  }
+
+  const traces = new Map<string, KVMap[]>();
+  for (const run of sortByDottedOrder(runs.values())) {
+    if (!run.trace_id || !run.dotted_order) {
+      result.skippedLines += 1;
+      continue;
+    }
+    decodeAttachments(run);
+    if (projectName !== undefined) {
+      run.session_name = projectName;
+      delete run.session_id;
+    }
+    traces.set(run.trace_id, [...(traces.get(run.trace_id) ?? []), run]);
+  }
+
+  let chunk: KVMap[][] = [];
+  const sendChunk = async () => {
+    const chunkRuns = chunk.flat();
+    try {
+      const delivered = await client._ingestBatch({
+        // Patches whose post was lost, e.g. in a crash, are sent as is
+        runCreates: chunkRuns.filter((run) =>
+          posted.has(run.id)
+        ) as RunCreate[],
+        runUpdates: chunkRuns.filter((run) => !posted.has(run.id)),
+      });
+      if (!delivered) {
+        throw new Error("LangSmith did not accept the batch.");
+      }
+      result.traces += chunk.length;
+      result.runs += chunkRuns.length;
+    } catch (e) {
+      console.warn(`Failed to upload ${chunk.length} traces: ${e}`);
+      result.failedTraces += chunk.length;
+    }
+    chunk = [];
+  };
+  for (const traceRuns of traces.values()) {
+    if (
+      chunk.length > 0 &&
+      chunk.flat().length + traceRuns.length > batchSize
+    ) {
+      await sendChunk();
+    }
+    chunk.push(traceRuns);
+  }
+  if (chunk.length > 0) {
+    await sendChunk();
+  }
+
+  if (deleteFiles && result.failedTraces === 0) {
+    await Promise.all(files.map((file) => fs.unlink(file)));
+  }
+  return result;
+}
+
+function mergeRuns(
+  events: (RunCreate | RunUpdate)[],
+  runs: Map<string, KVMap>
+) {
+  for (const event of events) {
+    if (event.id === undefined) {
+      continue;
+    }
+    const run = runs.get(event.id) ?? {};
+    for (const [key, value] of Object.entries(event)) {
+      if (value !== undefined && value !== null) {
+        run[key] = value;
+      }
+    }
+    runs.set(event.id, run);
+  }
+}
+
+function sortByDottedOrder(runs: Iterable<KVMap>): KVMap[] {
+  return [...runs].sort((a, b) =>
+    (a.dotted_order ?? "").localeCompare(b.dotted_order ?? "")
+  );
+}
+
+function describeRun(run: KVMap): string {
+  let description = `${run.name ?? "Unnamed"} (${run.run_type ?? "chain"})`;
+  if (run.start_time !== undefined && run.end_time !== undefined) {
+    const durationMs =
+      new Date(run.end_time).getTime() - new Date(run.start_time).getTime();
+    if (!Number.isNaN(durationMs)) {
+      description += ` ${(durationMs / 1000).toFixed(2)}s`;
+    }
+  }
+  if (run.error) {
+    description += ` [error: ${String(run.error).trim().split("\n")[0]}]`;
+  }
+  return description;
+}
+
+/**
+ * Prints each trace as a tree once its root run ends:
+ *
+ * ```
+ * agent (chain) 2.31s
+ * ├── retrieve (retriever) 0.42s
+ * └── ChatOpenAI (llm) 1.80s [error: RateLimitError]
+ * ```
+ */
+export class ConsoleExporter implements TraceExporter {
+  private log: (message: string) => void;
+
+  private traces = new Map<string, Map<string, KVMap>>();
+
+  constructor(fields?: { log?: (message: string) => void }) {
+    this.log = fields?.log ?? console.log;
+  }
+
+  async export(batch: TraceBatch): Promise<void> {
+    const touched = new Set<string>();
+    for (const run of [...batch.runCreates, ...batch.runUpdates]) {
+      const traceId = run.trace_id ?? run.id;
+      if (traceId === undefined) {
+        continue;
+      }
+      const runs = this.traces.get(traceId) ?? new Map<string, KVMap>();
+      mergeRuns([run], runs);
+      this.traces.set(traceId, runs);
+      touched.add(traceId);
+    }
+    for (const traceId of touched) {
+      const runs = this.traces.get(traceId);
+      if (runs?.get(traceId)?.end_time !== undefined) {
+        this.traces.delete(traceId);
+        this.print(runs);
+      }
+    }
+  }
+
+  /** Print the traces whose root run has not ended yet. */
+  flush() {
+    for (const runs of this.traces.values()) {
+      this.print(runs, true);
+    }
+    this.traces.clear();
+  }
+
+  private print(runs: Map<string, KVMap>, incomplete = false) {
+    const children = new Map<string | undefined, KVMap[]>();
+    for (const run of sortByDottedOrder(runs.values())) {
+      // Runs whose parent wasn't seen are printed at the top level
+      const parentId = runs.has(run.parent_run_id)
+        ? run.parent_run_id
+        : undefined;
+      children.set(parentId, [...(children.get(parentId) ?? []), run]);
+    }
+    const lines: string[] = [];
+    const renderChildren = (runId: string, prefix: string) => {
+      const runChildren = children.get(runId) ?? [];
+      runChildren.forEach((run, i) => {
+        const last = i === runChildren.length - 1;
+        lines.push(`${prefix}${last ? "└── " : "├── "}${describeRun(run)}`);
+        renderChildren(run.id, prefix + (last ? "    " : "│   "));
+      });
+    };
+    for (const run of children.get(undefined) ?? []) {
+      lines.push(describeRun(run) + (incomplete ? " (incomplete)" : ""));
+      renderChildren(run.id, "");
+    }
+    this.log(lines.join("\n"));
+  }
+}
+
+/**
+ * Keeps every batch in memory, e.g. to assert on traces in tests.
+ *
+ * @example
+ * ```ts
+ * const exporter = new InMemoryExporter();
+ * const client = new Client({ exporters: [exporter] });
+ * await traceable(async () => "hi", { client, name: "greet" })();
+ * await client.awaitPendingTraceBatches();
+ * exporter.getRuns(); // [{ name: "greet", ... }]
+ * ```
+ */
+export class InMemoryExporter implements TraceExporter {
+  batches: TraceBatch[] = [];
+
+  async export(batch: TraceBatch): Promise<void> {
+    this.batches.push(batch);
+  }
+
+  /** Every run exported so far, with updates merged into their creates. */
+  getRuns(): KVMap[] {
+    const runs = new Map<string, KVMap>();
+    for (const batch of this.batches) {
+      mergeRuns(batch.runCreates, runs);
+      mergeRuns(batch.runUpdates, runs);
+    }
+    return sortByDottedOrder(runs.values());
+  }
+
+  clear() {
+    this.batches = [];
+  }
+}
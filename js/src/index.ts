@@ -1,4 +1,12 @@
-export { Client, type ClientConfig } from "./client.js";
+export {
+  Client,
+  type ClientConfig,
+  type DeliveryReport,
+  type WaitUntilLike,
+  type PayloadLimit,
+  CircuitBreaker,
+  type CircuitBreakerConfig,
+} from "./client.js";
 
 export type {
   Dataset,
@@ -9,9 +17,20 @@ export type {
   RetrieverOutput,
 } from "./schemas.js";
 
-export { RunTree, type RunTreeConfig } from "./run_trees.js";
+export {
+  RunTree,
+  type RunTreeConfig,
+  validateDottedOrder,
+  repairDottedOrder,
+} from "./run_trees.js";
 
 export { overrideFetchImplementation } from "./singletons/fetch.js";
 
+export {
+  registerSerializer,
+  unregisterSerializer,
+  type SerializerOptions,
+} from "./utils/serializers.js";
+
 // Update using yarn bump-version
 export const __version__ = "0.2.3";
@@ -3,11 +3,13 @@ import { BaseRun, KVMap, RunCreate, RunUpdate } from "./schemas.js";
 import {
   RuntimeEnvironment,
   getEnvironmentVariable,
+  getLangSmithEnvironmentVariable,
   getRuntimeEnvironment,
 } from "./utils/env.js";
 import { Client } from "./client.js";
 import { isTracingEnabled } from "./env.js";
 import { warnOnce } from "./utils/warn.js";
+import { WaitUntilLike, toWaitUntil } from "./utils/wait_until.js";
 import { _LC_CONTEXT_VARIABLES_KEY } from "./singletons/constants.js";
 
 function stripNonAlphanumeric(input: string) {
@@ -29,6 +31,234 @@ export function convertToDottedOrderFormat(
   );
 }
 
+/**
+ * Create the id of a new run: a UUIDv7, which sorts by creation time, if
+ * LANGSMITH_TRACING_UUID7 is "true", and a UUIDv4 otherwise.
+ */
+export function newRunId(): string {
+  return getLangSmithEnvironmentVariable("TRACING_UUID7") === "true"
+    ? uuid.v7()
+    : uuid.v4();
+}
+
+// Segments are a fixed-width start time followed by a run id
+const RUN_ID_LENGTH = 36;
+
+/**
+ * Append a run's segment to its parent's dotted order, never ordering the run
+ * before its parent. The clocks of the services in a distributed trace can
+ * disagree, so a child may seem to start before its parent. Its segment then
+ * takes its parent's start time, and its id breaks the tie.
+ */
+export function appendDottedOrder(
+  parentDottedOrder: string,
+  segment: string
+): string {
+  const parentSegment = parentDottedOrder.slice(
+    parentDottedOrder.lastIndexOf(".") + 1
+  );
+  const parentTime = parentSegment.slice(0, -RUN_ID_LENGTH);
+  if (segment.slice(0, -RUN_ID_LENGTH) < parentTime) {
+    segment = parentTime + segment.slice(-RUN_ID_LENGTH);
+  }
+  return `${parentDottedOrder}.${segment}`;
+}
+
+const SEGMENT_TIME_REGEX = /^\d{8}T\d{12}Z$/;
+
+/** The start time of a dotted order segment, in ms since the epoch. */
+function segmentStartTime(segment: string): number {
+  const time = segment.slice(0, -RUN_ID_LENGTH);
+  return Date.UTC(
+    Number(time.slice(0, 4)),
+    Number(time.slice(4, 6)) - 1,
+    Number(time.slice(6, 8)),
+    Number(time.slice(9, 11)),
+    Number(time.slice(11, 13)),
+    Number(time.slice(13, 15)),
+    Number(time.slice(15, 18))
+  );
+}
+
+/**
+ * Describe what's wrong with a dotted order, if anything. Each segment must be
+ * a start time and a run id, and start no earlier than the segment before it.
+ */
+export function validateDottedOrder(dottedOrder: string): string[] {
+  const problems: string[] = [];
+  let previous: string | undefined;
+  dottedOrder.split(".").forEach((segment, i) => {
+    const time = segment.slice(0, -RUN_ID_LENGTH);
+    const runId = segment.slice(-RUN_ID_LENGTH);
+    if (!SEGMENT_TIME_REGEX.test(time) || !UUID_REGEX.test(runId)) {
+      problems.push(`Segment ${i} is malformed: '${segment}'`);
+      return;
+    }
+    if (previous !== undefined && time < previous) {
+      problems.push(
+        `Segment ${i} starts at ${time}, before its parent at ${previous}`
+      );
+    }
+    if (previous === undefined || time > previous) {
+      previous = time;
+    }
+  });
+  return problems;
+}
+
+/**
+ * Move each segment of a dotted order up to its parent's start time. Only
+ * segments that start before their parent change, so repairing the dotted
+ * orders of a parent and a child keeps one the prefix of the other.
+ */
+export function repairDottedOrder(dottedOrder: string): string {
+  const segments = dottedOrder.split(".");
+  for (let i = 1; i < segments.length; i += 1) {
+    const parentTime = segments[i - 1].slice(0, -RUN_ID_LENGTH);
+    if (segments[i].slice(0, -RUN_ID_LENGTH) < parentTime) {
+      segments[i] = parentTime + segments[i].slice(-RUN_ID_LENGTH);
+    }
+  }
+  return segments.join(".");
+}
+
+const BASE64URL_ALPHABET =
+  "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+const UUID_REGEX =
+  /^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$/i;
+
+function toBase64Url(bytes: Uint8Array): string {
+  let result = "";
+  let buffer = 0;
+  let bits = 0;
+  for (const byte of bytes) {
+    buffer = ((buffer << 8) | byte) & 0xffffff;
+    bits += 8;
+    while (bits >= 6) {
+      bits -= 6;
+      result += BASE64URL_ALPHABET[(buffer >> bits) & 63];
+    }
+  }
+  if (bits > 0) result += BASE64URL_ALPHABET[(buffer << (6 - bits)) & 63];
+  return result;
+}
+
+function fromBase64Url(value: string): Uint8Array {
+  const bytes: number[] = [];
+  let buffer = 0;
+  let bits = 0;
+  for (const char of value) {
+    const index = BASE64URL_ALPHABET.indexOf(char);
+    if (index === -1) throw new Error(`Invalid base64url character: ${char}`);
+    buffer = ((buffer << 6) | index) & 0xffffff;
+    bits += 6;
+    if (bits >= 8) {
+      bits -= 8;
+      bytes.push((buffer >> bits) & 0xff);
+    }
+  }
+  return new Uint8Array(bytes);
+}
+
+function uuidToBytes(id: string): Uint8Array {
+  const hex = id.replace(/-/g, "");
+  const bytes = new Uint8Array(16);
+  for (let i = 0; i < 16; i++) {
+    bytes[i] = parseInt(hex.slice(i * 2, i * 2 + 2), 16);
+  }
+  return bytes;
+}
+
+function bytesToUuid(bytes: Uint8Array): string {
+  const hex = Array.from(bytes, (byte) => byte.toString(16).padStart(2, "0"));
+  return [
+    hex.slice(0, 4),
+    hex.slice(4, 6),
+    hex.slice(6, 8),
+    hex.slice(8, 10),
+    hex.slice(10, 16),
+  ]
+    .map((group) => group.join(""))
+    .join("-");
+}
+
+function toTraceparent(traceId: string, runId: string): string | undefined {
+  if (!UUID_REGEX.test(traceId) || !UUID_REGEX.test(runId)) return undefined;
+  const traceHex = traceId.replace(/-/g, "").toLowerCase();
+  const parentHex = runId.replace(/-/g, "").toLowerCase().slice(16);
+  return `00-${traceHex}-${parentHex}-01`;
+}
+
+/**
+ * Encode a dotted order as the value of a `tracestate` entry, see
+ * {@link RunTree.toHeaders}.
+ */
+function dottedOrderToTracestate(dottedOrder: string): string | undefined {
+  const segments: string[] = [];
+  for (const part of dottedOrder.split(".")) {
+    const [strTime, runId] = part.split("Z");
+    if (!/^\d{8}T\d{12}$/.test(strTime) || !UUID_REGEX.test(runId)) {
+      return undefined;
+    }
+    const seconds =
+      Date.UTC(
+        Number(strTime.slice(0, 4)),
+        Number(strTime.slice(4, 6)) - 1,
+        Number(strTime.slice(6, 8)),
+        Number(strTime.slice(9, 11)),
+        Number(strTime.slice(11, 13)),
+        Number(strTime.slice(13, 15))
+      ) / 1000;
+    const micros = seconds * 1e6 + Number(strTime.slice(15, 21));
+    const bytes = new Uint8Array(24);
+    const view = new DataView(bytes.buffer);
+    view.setUint32(0, Math.floor(micros / 2 ** 32));
+    view.setUint32(4, micros % 2 ** 32);
+    bytes.set(uuidToBytes(runId), 8);
+    segments.push(toBase64Url(bytes));
+  }
+  const value = segments.join(".");
+  return value.length <= 256 ? value : undefined;
+}
+
+/**
+ * Return the dotted order from the `langsmith` entry of `tracestate`, if it
+ * belongs to the trace in `traceparent`.
+ */
+function dottedOrderFromTraceContext(
+  traceparent: string | undefined,
+  tracestate: string | undefined
+): string | undefined {
+  if (!traceparent || !tracestate) return undefined;
+  const traceHex = traceparent.trim().split("-")[1]?.toLowerCase();
+  if (traceHex?.length !== 32) return undefined;
+  for (const member of tracestate.split(",")) {
+    const [key, value] = member.trim().split("=");
+    if (key !== "langsmith" || !value) continue;
+    try {
+      const segments = value.split(".").map((segment) => {
+        const bytes = fromBase64Url(segment);
+        if (bytes.length !== 24) throw new Error("Invalid segment length");
+        const view = new DataView(bytes.buffer);
+        const micros = view.getUint32(0) * 2 ** 32 + view.getUint32(4);
+        const seconds = Math.floor(micros / 1e6);
+        const strSeconds = stripNonAlphanumeric(
+          new Date(seconds * 1000).toISOString().slice(0, 19)
+        );
+        const strMicros = String(micros - seconds * 1e6).padStart(6, "0");
+        return `${strSeconds}${strMicros}Z${bytesToUuid(bytes.slice(8))}`;
+      });
+      const traceId = segments[0].slice(-36).replace(/-/g, "");
+      return traceId === traceHex ? segments.join(".") : undefined;
+    } catch (e) {
+      console.warn(`Error parsing tracestate header: ${e}`);
+      return undefined;
+    }
+  }
+  return undefined;
+}
+
 export interface RunTreeConfig {
   name: string;
   run_type?: string;
@@ -55,6 +285,13 @@ export interface RunTreeConfig {
 
   trace_id?: string;
   dotted_order?: string;
+
+  /**
+   * A platform hook, such as a Cloudflare Workers `ExecutionContext`, that
+   * keeps a serverless invocation alive until the trace sends. Registered
+   * when a root run ends.
+   */
+  waitUntil?: WaitUntilLike;
 }
 
 export interface RunnableConfigLike {
@@ -173,6 +410,8 @@ export class RunTree implements BaseRun {
   execution_order: number;
   child_execution_order: number;
 
+  waitUntil?: WaitUntilLike;
+
   constructor(originalConfig: RunTreeConfig | RunTree) {
     // If you pass in a run tree directly, return a shallow clone
     if (isRunTree(originalConfig)) {
@@ -206,8 +445,17 @@ export class RunTree implements BaseRun {
         this.execution_order
       );
       if (this.parent_run) {
-        this.dotted_order =
-          this.parent_run.dotted_order + "." + currentDottedOrder;
+        this.dotted_order = appendDottedOrder(
+          this.parent_run.dotted_order,
+          currentDottedOrder
+        );
+        if (!this.dotted_order.endsWith(`.${currentDottedOrder}`)) {
+          // A child that seems to start before its parent takes its parent's
+          // start time in the dotted order, so keep its start time the same
+          this.start_time = segmentStartTime(
+            this.dotted_order.slice(this.dotted_order.lastIndexOf(".") + 1)
+          );
+        }
       } else {
         this.dotted_order = currentDottedOrder;
       }
@@ -216,7 +464,7 @@ export class RunTree implements BaseRun {
 
   private static getDefaultConfig(): object {
     return {
-      id: uuid.v4(),
+      id: newRunId(),
       run_type: "chain",
       project_name:
         getEnvironmentVariable("LANGCHAIN_PROJECT") ??
@@ -410,6 +658,9 @@ export class RunTree implements BaseRun {
       };
 
       await this.client.updateRun(this.id, runUpdate);
+      if (this.parent_run === undefined && this.waitUntil !== undefined) {
+        toWaitUntil(this.waitUntil)?.(this.client.awaitPendingTraceBatches());
+      }
     } catch (error) {
       console.error(`Error in patchRun for run ${this.id}`, error);
     }
@@ -489,11 +740,23 @@ export class RunTree implements BaseRun {
         ? {
             "langsmith-trace": headers.get("langsmith-trace"),
             baggage: headers.get("baggage"),
+            traceparent: headers.get("traceparent"),
+            tracestate: headers.get("tracestate"),
           }
         : (headers as Record<string, string | string[]>);
 
-    const headerTrace = rawHeaders["langsmith-trace"];
-    if (!headerTrace || typeof headerTrace !== "string") return undefined;
+    const rawTrace = rawHeaders["langsmith-trace"];
+    let headerTrace =
+      rawTrace && typeof rawTrace === "string" ? rawTrace : undefined;
+    if (!headerTrace) {
+      // e.g. a gateway that only forwards standard tracing headers
+      const { traceparent, tracestate } = rawHeaders;
+      headerTrace = dottedOrderFromTraceContext(
+        typeof traceparent === "string" ? traceparent : undefined,
+        typeof tracestate === "string" ? tracestate : undefined
+      );
+    }
+    if (!headerTrace) return undefined;
 
     const parentDottedOrder = headerTrace.trim();
     const parsedDottedOrder = parentDottedOrder.split(".").map((part) => {
@@ -522,11 +785,36 @@ export class RunTree implements BaseRun {
     return new RunTree(config);
   }
 
+  /**
+   * Return the run tree as headers, to continue the trace in another service.
+   *
+   * Besides the `langsmith-trace` and `baggage` headers, the run is propagated
+   * with the W3C `traceparent` and `tracestate` headers, which proxies and
+   * service meshes forward even when they drop other headers:
+   *
+   * - The trace ID is the 128 bits of the LangSmith trace ID, i.e. of the ID
+   *   of the root run.
+   * - The parent ID is the last 64 bits of the run ID.
+   * - The `langsmith` entry of `tracestate` holds the dotted order. Each of its
+   *   segments is encoded as the big-endian microseconds since the epoch
+   *   (8 bytes) followed by the run ID (16 bytes), in unpadded URL-safe base64,
+   *   and segments are joined with `.`.
+   *
+   * `tracestate` entries are limited to 256 characters, so the entry is left
+   * out for runs more than 7 levels deep, counting the root. Services then
+   * need the `langsmith-trace` header to continue the trace.
+   */
   toHeaders(headers?: HeadersLike) {
-    const result = {
+    const result: Record<string, string> = {
       "langsmith-trace": this.dotted_order,
       baggage: new Baggage(this.extra?.metadata, this.tags).toHeader(),
     };
+    const traceparent = toTraceparent(this.trace_id, this.id);
+    if (traceparent) {
+      result.traceparent = traceparent;
+      const tracestate = dottedOrderToTracestate(this.dotted_order);
+      if (tracestate) result.tracestate = `langsmith=${tracestate}`;
+    }
 
     if (headers) {
       for (const [key, value] of Object.entries(result)) {
@@ -1,6 +1,8 @@
 /* eslint-disable @typescript-eslint/no-explicit-any */
 /* eslint-disable prefer-const */
 import { jest } from "@jest/globals";
+import { randomBytes } from "node:crypto";
+import { gunzipSync } from "node:zlib";
 import { v4 as uuidv4 } from "uuid";
 import { Client, mergeRuntimeEnvIntoRunCreate } from "../client.js";
 import { convertToDottedOrderFormat } from "../run_trees.js";
@@ -136,6 +138,64 @@ describe.each(ENDPOINT_TYPES)(
       );
     });
 
+    it("should compress requests when the server supports it", async () => {
+      const client = new Client({
+        apiKey: "test-api-key",
+        compression: "gzip",
+      });
+      const callSpy = jest
+        .spyOn((client as any).batchIngestCaller, "call")
+        .mockResolvedValue({
+          ok: true,
+          text: () => "",
+        });
+      jest.spyOn(client as any, "_getServerInfo").mockImplementation(() => {
+        return {
+          version: "foo",
+          batch_ingest_config: {
+            ...extraBatchIngestConfig,
+            supported_compression_methods: ["gzip"],
+          },
+        };
+      });
+      const runId = uuidv4();
+      const dottedOrder = convertToDottedOrderFormat(
+        new Date().getTime() / 1000,
+        runId
+      );
+      const text = "hello world ".repeat(1000);
+      await client.createRun({
+        id: runId,
+        project_name: "__test_batch",
+        name: "test_run",
+        run_type: "llm",
+        inputs: { text },
+        trace_id: runId,
+        dotted_order: dottedOrder,
+      });
+      await client.awaitPendingTraceBatches();
+
+      const { headers, body }: any = callSpy.mock.calls[0][2];
+      expect(headers["Content-Encoding"]).toBe("gzip");
+      expect(body.length).toBeLessThan(text.length);
+      const decompressed = gunzipSync(body);
+      const parsed =
+        endpointType === "batch"
+          ? decompressed.toString()
+          : await new Response(decompressed, {
+              headers: { "Content-Type": headers["Content-Type"] },
+            }).formData();
+      expect(await parseMockRequestBody(parsed)).toEqual({
+        post: [
+          expect.objectContaining({
+            id: runId,
+            inputs: { text },
+          }),
+        ],
+        patch: [],
+      });
+    });
+
     it("should not throw an error if fetch fails for batch requests", async () => {
       const client = new Client({
         apiKey: "test-api-key",
@@ -903,3 +963,61 @@ describe.each(ENDPOINT_TYPES)(
     });
   }
 );
+
+describe("Compressed multipart requests", () => {
+  it("should split requests that are over the size limit once compressed", async () => {
+    const client = new Client({
+      apiKey: "test-api-key",
+      compression: "gzip",
+      batchSizeBytesLimit: 2000,
+    });
+    const callSpy = jest
+      .spyOn((client as any).batchIngestCaller, "call")
+      .mockResolvedValue({
+        ok: true,
+        text: () => "",
+      });
+    jest.spyOn(client as any, "_getServerInfo").mockImplementation(() => {
+      return {
+        version: "foo",
+        batch_ingest_config: {
+          use_multipart_endpoint: true,
+          supported_compression_methods: ["gzip"],
+        },
+      };
+    });
+    const runCreates = (makeInputs: () => string) =>
+      [uuidv4(), uuidv4(), uuidv4()].map((runId) => ({
+        id: runId,
+        name: "test_run",
+        run_type: "llm",
+        inputs: { text: makeInputs() },
+        trace_id: runId,
+        dotted_order: convertToDottedOrderFormat(
+          new Date().getTime() / 1000,
+          runId
+        ),
+      }));
+
+    // Repetitive inputs fit in one request once compressed, random ones don't
+    await client.multipartIngestRuns({
+      runCreates: runCreates(() => "a".repeat(3000)),
+    });
+    expect(callSpy).toHaveBeenCalledTimes(1);
+
+    callSpy.mockClear();
+    const runs = runCreates(() => randomBytes(1000).toString("hex"));
+    await client.multipartIngestRuns({ runCreates: runs });
+    expect(callSpy).toHaveBeenCalledTimes(3);
+    for (const [i, call] of callSpy.mock.calls.entries()) {
+      const { headers, body }: any = call[2];
+      const formData = await new Response(gunzipSync(body), {
+        headers: { "Content-Type": headers["Content-Type"] },
+      }).formData();
+      expect(await parseMockRequestBody(formData)).toEqual({
+        post: [expect.objectContaining({ id: runs[i].id })],
+        patch: [],
+      });
+    }
+  });
+});
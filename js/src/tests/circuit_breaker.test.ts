@@ -0,0 +1,121 @@
+/* eslint-disable @typescript-eslint/no-explicit-any */
+import { jest } from "@jest/globals";
+import { v4 as uuidv4 } from "uuid";
+import { Client } from "../client.js";
+import { convertToDottedOrderFormat } from "../run_trees.js";
+import { CircuitBreaker } from "../utils/circuit_breaker.js";
+
+const createRun = (client: Client) => {
+  const id = uuidv4();
+  return client.createRun({
+    id,
+    name: "run",
+    run_type: "chain",
+    inputs: {},
+    trace_id: id,
+    dotted_order: convertToDottedOrderFormat(Date.now() / 1000, id),
+  });
+};
+
+const response = (status: number) => ({
+  ok: status < 300,
+  status,
+  statusText: "",
+  text: () => Promise.resolve(""),
+});
+
+describe("CircuitBreaker", () => {
+  beforeEach(() => {
+    jest.spyOn(console, "warn").mockImplementation(() => {});
+    jest.spyOn(console, "info").mockImplementation(() => {});
+  });
+
+  afterEach(() => {
+    jest.restoreAllMocks();
+  });
+
+  it("should open after repeated failures and probe once at a time", () => {
+    let now = 0;
+    const breaker = new CircuitBreaker({
+      failureThreshold: 2,
+      resetTimeoutMs: 1000,
+      clock: () => now,
+    });
+    breaker.recordResponse(response(503));
+    expect(breaker.state).toBe("closed");
+    breaker.recordResponse(undefined);
+    expect(breaker.state).toBe("open");
+    expect(breaker.allowRequest()).toBe(false);
+
+    now = 1000;
+    expect(breaker.state).toBe("half_open");
+    expect(breaker.allowRequest()).toBe(true);
+    // Only one probe is let through until its outcome is recorded
+    expect(breaker.allowRequest()).toBe(false);
+    breaker.recordResponse(response(500));
+    expect(breaker.state).toBe("open");
+    expect(breaker.timesOpened).toBe(2);
+
+    now = 2000;
+    expect(breaker.allowRequest()).toBe(true);
+    breaker.recordResponse(response(202));
+    expect(breaker.state).toBe("closed");
+    expect(breaker.allowRequest()).toBe(true);
+  });
+
+  it("should not count rejected requests as an outage", () => {
+    const breaker = new CircuitBreaker({ failureThreshold: 1 });
+    breaker.recordResponse(response(422));
+    expect(breaker.state).toBe("closed");
+    breaker.recordResponse(response(429));
+    expect(breaker.state).toBe("open");
+  });
+
+  it("should stop sending batches while the API is down", async () => {
+    let now = 0;
+    const breaker = new CircuitBreaker({
+      failureThreshold: 1,
+      resetTimeoutMs: 1000,
+      clock: () => now,
+    });
+    const client = new Client({
+      apiKey: "test-api-key",
+      circuitBreaker: breaker,
+    });
+    jest.spyOn(client as any, "_getServerInfo").mockResolvedValue({
+      batch_ingest_config: { use_multipart_endpoint: true },
+    });
+    const callSpy = jest
+      .spyOn((client as any).batchIngestCaller, "call")
+      .mockResolvedValue(response(503));
+
+    await createRun(client);
+    expect(await client.flush()).toEqual({
+      delivered: 0,
+      failed: 1,
+      abandoned: 0,
+    });
+    expect(breaker.state).toBe("open");
+
+    // Short-circuited batches aren't sent, and count as failed
+    await createRun(client);
+    expect(await client.flush()).toEqual({
+      delivered: 0,
+      failed: 1,
+      abandoned: 0,
+    });
+    expect(callSpy).toHaveBeenCalledTimes(1);
+    expect(breaker.shortCircuitedRuns).toBe(1);
+
+    now = 1000;
+    callSpy.mockResolvedValue(response(202));
+    await createRun(client);
+    expect(await client.flush()).toEqual({
+      delivered: 1,
+      failed: 0,
+      abandoned: 0,
+    });
+    expect(callSpy).toHaveBeenCalledTimes(2);
+    expect(breaker.state).toBe("closed");
+  });
+});
@@ -0,0 +1,245 @@
+/* eslint-disable @typescript-eslint/no-explicit-any */
+import { jest } from "@jest/globals";
+import * as fs from "node:fs";
+import * as os from "node:os";
+import * as path from "node:path";
+import { v4 as uuidv4 } from "uuid";
+import { Client } from "../client.js";
+import {
+  ConsoleExporter,
+  InMemoryExporter,
+  JSONLFileExporter,
+  LangSmithExporter,
+  OfflineExporter,
+  uploadTraces,
+} from "../exporters/index.js";
+import { convertToDottedOrderFormat } from "../run_trees.js";
+
+const START = Date.UTC(2024, 0, 1);
+
+const makeTrace = (error?: string) => {
+  const rootId = uuidv4();
+  const childId = uuidv4();
+  const rootOrder = convertToDottedOrderFormat(START / 1000, rootId);
+  const childOrder = `${rootOrder}.${convertToDottedOrderFormat(
+    START / 1000,
+    childId
+  )}`;
+  return [
+    {
+      id: rootId,
+      trace_id: rootId,
+      dotted_order: rootOrder,
+      name: "agent",
+      run_type: "chain",
+      start_time: START,
+    },
+    {
+      id: childId,
+      trace_id: rootId,
+      parent_run_id: rootId,
+      dotted_order: childOrder,
+      name: "llm",
+      run_type: "llm",
+      start_time: START,
+      end_time: START + 500,
+      error,
+    },
+  ];
+};
+
+describe("Trace exporters", () => {
+  it("should replace the API", async () => {
+    const exporter = new InMemoryExporter();
+    const client = new Client({
+      apiKey: "test-api-key",
+      exporters: [exporter],
+    });
+    const serverInfoSpy = jest.spyOn(client as any, "_getServerInfo");
+    const callSpy = jest.spyOn((client as any).batchIngestCaller, "call");
+    const [root, child] = makeTrace();
+    await client.createRun({ ...root, inputs: { q: "hi" } });
+    await client.createRun({ ...child, inputs: {} });
+    await client.updateRun(root.id, {
+      outputs: { a: "hello" },
+      end_time: START + 1000,
+      trace_id: root.trace_id,
+      dotted_order: root.dotted_order,
+    });
+    await new Promise((resolve) => setTimeout(resolve, 300));
+    await client.awaitPendingTraceBatches();
+
+    const runs = exporter.getRuns();
+    expect(runs.map((run) => run.name)).toEqual(["agent", "llm"]);
+    expect(runs[0]).toMatchObject({
+      inputs: { q: "hi" },
+      outputs: { a: "hello" },
+    });
+    expect(serverInfoSpy).not.toHaveBeenCalled();
+    expect(callSpy).not.toHaveBeenCalled();
+  });
+
+  it("should chain exporters", async () => {
+    const dir = fs.mkdtempSync(path.join(os.tmpdir(), "langsmith-exporters-"));
+    const filePath = path.join(dir, "audit", "traces.jsonl");
+    const memory = new InMemoryExporter();
+    const broken = {
+      export: async () => {
+        throw new Error("disk full");
+      },
+    };
+    const client = new Client({
+      apiKey: "test-api-key",
+      exporters: [
+        new LangSmithExporter(),
+        broken,
+        new JSONLFileExporter(filePath),
+        memory,
+      ],
+    });
+    const ingestSpy = jest
+      .spyOn(client, "_ingestBatch")
+      .mockResolvedValue(true);
+    jest.spyOn(client as any, "_getServerInfo").mockResolvedValue({});
+    const warnSpy = jest.spyOn(console, "warn").mockImplementation(() => {});
+    const [root, child] = makeTrace();
+    await client.createRun({ ...root, inputs: {} });
+    await client.createRun({ ...child, inputs: {} });
+    await new Promise((resolve) => setTimeout(resolve, 300));
+    await client.awaitPendingTraceBatches();
+
+    expect(ingestSpy).toHaveBeenCalled();
+    expect(warnSpy).toHaveBeenCalledWith(expect.stringContaining("disk full"));
+    const lines = fs
+      .readFileSync(filePath, "utf-8")
+      .trim()
+      .split("\n")
+      .map((line) => JSON.parse(line));
+    expect(lines.map((line) => line.event)).toEqual(["post", "post"]);
+    expect(new Set(lines.map((line) => line.payload.id))).toEqual(
+      new Set([root.id, child.id])
+    );
+    expect(memory.getRuns()).toHaveLength(2);
+    warnSpy.mockRestore();
+  });
+
+  it("should print finished traces to the console", async () => {
+    const logged: string[] = [];
+    const exporter = new ConsoleExporter({
+      log: (message) => logged.push(message),
+    });
+    const [root, child] = makeTrace("boom");
+    await exporter.export({ runCreates: [root, child], runUpdates: [] });
+    expect(logged).toEqual([]);
+
+    await exporter.export({
+      runCreates: [],
+      runUpdates: [{ ...root, end_time: START + 2000 }],
+    });
+    expect(logged).toEqual([
+      "agent (chain) 2.00s\n└── llm (llm) 0.50s [error: boom]",
+    ]);
+
+    await exporter.export({
+      runCreates: makeTrace().slice(0, 1),
+      runUpdates: [],
+    });
+    exporter.flush();
+    expect(logged[1]).toEqual("agent (chain) (incomplete)");
+  });
+
+  it("should upload offline traces", async () => {
+    const dir = fs.mkdtempSync(path.join(os.tmpdir(), "langsmith-offline-"));
+    const exporter = new OfflineExporter(dir);
+    const first = makeTrace();
+    const second = makeTrace();
+    const posts = [...first, ...second].map((run) => ({
+      ...run,
+      inputs: { q: "hi" },
+      attachments: {
+        image: ["image/png", new Uint8Array([1, 2, 3])] as [
+          string,
+          Uint8Array
+        ],
+      },
+    }));
+    const patches = [...first, ...second].map((run) => ({
+      ...run,
+      outputs: { a: "hello" },
+      end_time: START + 1000,
+    }));
+    // children are written before their parents, and patches in another batch
+    await exporter.export({ runCreates: posts.reverse(), runUpdates: [] });
+    await exporter.export({ runCreates: [], runUpdates: patches });
+    fs.appendFileSync(exporter.path, '{"event": "post", "payl');
+
+    const client = new Client({ apiKey: "test-api-key" });
+    const ingestSpy = jest
+      .spyOn(client, "_ingestBatch")
+      .mockResolvedValue(true);
+    const result = await uploadTraces({
+      path: dir,
+      client,
+      projectName: "uploaded",
+      batchSize: 1,
+      deleteFiles: true,
+    });
+
+    expect(result).toMatchObject({ traces: 2, runs: 4, skippedLines: 1 });
+    expect(ingestSpy).toHaveBeenCalledTimes(2);
+    for (const [batch] of ingestSpy.mock.calls) {
+      expect(batch.runUpdates).toEqual([]);
+      const trace =
+        batch.runCreates[0].trace_id === first[0].id ? first : second;
+      expect(batch.runCreates.map((run) => run.id)).toEqual(
+        trace.map((run) => run.id)
+      );
+      for (const run of batch.runCreates) {
+        expect(run).toMatchObject({
+          session_name: "uploaded",
+          inputs: { q: "hi" },
+          outputs: { a: "hello" },
+          attachments: { image: ["image/png", new Uint8Array([1, 2, 3])] },
+        });
+      }
+    }
+    expect(fs.readdirSync(dir)).toEqual([]);
+  });
+
+  it("should capture offline when the offline dir is set", async () => {
+    const dir = fs.mkdtempSync(path.join(os.tmpdir(), "langsmith-offline-"));
+    // eslint-disable-next-line no-process-env
+    process.env.LANGSMITH_TRACING_OFFLINE_DIR = dir;
+    try {
+      const client = new Client({ apiKey: "test-api-key" });
+      const callSpy = jest.spyOn((client as any).batchIngestCaller, "call");
+      const [root] = makeTrace();
+      await client.createRun({ ...root, inputs: { q: "hi" } });
+      await new Promise((resolve) => setTimeout(resolve, 300));
+      await client.awaitPendingTraceBatches();
+      const [file] = fs.readdirSync(dir);
+      const line = JSON.parse(
+        fs.readFileSync(path.join(dir, file), "utf-8").trim()
+      );
+      expect(line).toMatchObject({
+        event: "post",
+        payload: { id: root.id, inputs: { q: "hi" } },
+      });
+      expect(callSpy).not.toHaveBeenCalled();
+    } finally {
+      // eslint-disable-next-line no-process-env
+      delete process.env.LANGSMITH_TRACING_OFFLINE_DIR;
+    }
+  });
+
+  it("should require auto batching", () => {
+    expect(
+      () =>
+        new Client({
+          apiKey: "test-api-key",
+          autoBatchTracing: false,
+          exporters: [new InMemoryExporter()],
+        })
+    ).toThrow("autoBatchTracing");
+  });
+});
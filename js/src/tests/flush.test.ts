@@ -0,0 +1,92 @@
+/* eslint-disable @typescript-eslint/no-explicit-any */
+import { jest } from "@jest/globals";
+import { v4 as uuidv4 } from "uuid";
+import { Client } from "../client.js";
+import { TraceExporter } from "../exporters/index.js";
+import { convertToDottedOrderFormat } from "../run_trees.js";
+
+const createRun = (client: Client) => {
+  const id = uuidv4();
+  return client.createRun({
+    id,
+    name: "run",
+    run_type: "chain",
+    inputs: {},
+    trace_id: id,
+    dotted_order: convertToDottedOrderFormat(Date.now() / 1000, id),
+  });
+};
+
+const makeClient = (config: { exporters?: TraceExporter[] } = {}) => {
+  const client = new Client({ apiKey: "test-api-key", ...config });
+  jest
+    .spyOn(client as any, "_getServerInfo")
+    .mockResolvedValue({ batch_ingest_config: {} });
+  return client;
+};
+
+describe("Client flush and shutdown", () => {
+  it("should report delivered and failed runs", async () => {
+    const client = makeClient();
+    const ingestSpy = jest
+      .spyOn(client, "_ingestBatch")
+      .mockResolvedValue(true);
+    await createRun(client);
+    await createRun(client);
+    expect(await client.flush()).toEqual({
+      delivered: 2,
+      failed: 0,
+      abandoned: 0,
+    });
+    ingestSpy.mockResolvedValue(false);
+    await createRun(client);
+    expect(await client.flush()).toEqual({
+      delivered: 0,
+      failed: 1,
+      abandoned: 0,
+    });
+  });
+
+  it("should stop waiting at the timeout", async () => {
+    const client = makeClient();
+    let release: () => void = () => {};
+    const sent = new Promise<void>((resolve) => {
+      release = resolve;
+    });
+    jest
+      .spyOn(client, "_ingestBatch")
+      .mockImplementation(() => sent.then(() => true));
+    await createRun(client);
+    expect(await client.flush(50)).toEqual({
+      delivered: 0,
+      failed: 0,
+      abandoned: 1,
+    });
+    release();
+    expect(await client.flush()).toEqual({
+      delivered: 1,
+      failed: 0,
+      abandoned: 0,
+    });
+  });
+
+  it("should shut down exporters after flushing", async () => {
+    const calls: string[] = [];
+    const exporter = {
+      export: jest.fn(async () => {
+        calls.push("export");
+      }),
+      shutdown: jest.fn(async () => {
+        calls.push("shutdown");
+      }),
+    };
+    const client = makeClient({ exporters: [exporter] });
+    await createRun(client);
+    expect(await client.shutdown(1000)).toEqual({
+      delivered: 1,
+      failed: 0,
+      abandoned: 0,
+    });
+    expect(calls).toEqual(["export", "shutdown"]);
+  });
+});
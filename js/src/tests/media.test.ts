@@ -0,0 +1,92 @@
+/* eslint-disable @typescript-eslint/no-explicit-any */
+import { jest } from "@jest/globals";
+import { v4 as uuidv4 } from "uuid";
+import { Client } from "../client.js";
+import { convertToDottedOrderFormat } from "../run_trees.js";
+import { extractInlineMedia } from "../utils/media.js";
+
+const PNG = new Uint8Array([137, 80, 78, 71, 13, 10, 26, 10]);
+const PNG_B64 = btoa(String.fromCharCode(...PNG));
+
+const messages = () => ({
+  messages: [
+    {
+      role: "user",
+      content: [
+        { type: "text", text: "What's this?" },
+        {
+          type: "image_url",
+          image_url: { url: `data:image/png;base64,${PNG_B64}` },
+        },
+        { type: "input_audio", input_audio: { data: "UklG", format: "wav" } },
+      ],
+    },
+    {
+      role: "user",
+      content: [
+        {
+          type: "image",
+          source: { type: "base64", media_type: "image/jpeg", data: PNG_B64 },
+        },
+      ],
+    },
+  ],
+});
+
+describe("extractInlineMedia", () => {
+  it("should move inline media into attachments", () => {
+    const inputs = messages();
+    const [extracted, attachments] = extractInlineMedia("inputs", inputs);
+    const [openai, anthropic] = (extracted as any).messages;
+    expect(openai.content[1].image_url.url).toBe("attachment://inputs-media-0");
+    expect(openai.content[2].input_audio).toEqual({
+      data: "attachment://inputs-media-1",
+      format: "wav",
+    });
+    expect(anthropic.content[0].source.data).toBe(
+      "attachment://inputs-media-2"
+    );
+    expect(attachments).toEqual({
+      "inputs-media-0": ["image/png", PNG],
+      "inputs-media-1": ["audio/wav", new Uint8Array([82, 73, 70])],
+      "inputs-media-2": ["image/jpeg", PNG],
+    });
+    // The values passed in are left as they were, and unchanged ones reused
+    expect(inputs).toEqual(messages());
+    expect(openai.content[0]).toBe(inputs.messages[0].content[0]);
+    expect(extractInlineMedia("outputs", { answer: "hi" })).toEqual([
+      { answer: "hi" },
+      {},
+    ]);
+  });
+});
+
+describe("Client extractMedia", () => {
+  it("should send inline media as attachments", async () => {
+    const client = new Client({ apiKey: "test-api-key", extractMedia: true });
+    const sendSpy = jest
+      .spyOn(client as any, "_sendMultipartRequest")
+      .mockResolvedValue(true);
+    const id = uuidv4();
+    await client.multipartIngestRuns({
+      runCreates: [
+        {
+          id,
+          name: "llm",
+          run_type: "llm",
+          inputs: messages(),
+          trace_id: id,
+          dotted_order: convertToDottedOrderFormat(Date.now() / 1000, id),
+        },
+      ],
+    });
+    const parts = sendSpy.mock.calls[0][0] as { name: string; payload: Blob }[];
+    const byName = Object.fromEntries(parts.map((p) => [p.name, p.payload]));
+    const inputs = JSON.parse(await byName[`post.${id}.inputs`].text());
+    expect(inputs.messages[0].content[1].image_url.url).toBe(
+      "attachment://inputs-media-0"
+    );
+    const image = byName[`attachment.${id}.inputs-media-0`];
+    expect(new Uint8Array(await image.arrayBuffer())).toEqual(PNG);
+  });
+});
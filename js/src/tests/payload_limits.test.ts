@@ -0,0 +1,82 @@
+/* eslint-disable @typescript-eslint/no-explicit-any */
+import { jest } from "@jest/globals";
+import { v4 as uuidv4 } from "uuid";
+import { Client } from "../client.js";
+import { convertToDottedOrderFormat } from "../run_trees.js";
+import { applyPayloadLimit } from "../utils/payload_limits.js";
+
+describe("applyPayloadLimit", () => {
+  it("should move large values to attachments", () => {
+    const values = { question: "hi", document: "x".repeat(1000) };
+    const [limited, attachments] = applyPayloadLimit(
+      { maxBytes: 100 },
+      "inputs",
+      values
+    );
+    expect(limited).toEqual({
+      question: "hi",
+      document: "[1002 bytes moved to attachment 'inputs-document']",
+    });
+    expect(Object.keys(attachments)).toEqual(["inputs-document"]);
+    expect(attachments["inputs-document"][0]).toBe("application/json");
+    expect(values.document).toBe("x".repeat(1000));
+  });
+
+  it("should truncate and summarize large values", () => {
+    const values = { document: "é".repeat(1000) };
+    const [truncated] = applyPayloadLimit(
+      { maxBytes: 100, action: "truncate" },
+      "outputs",
+      values
+    );
+    const document = (truncated as any).document as string;
+    expect(document.startsWith("é")).toBe(true);
+    expect(document.endsWith("... [truncated 2000 bytes]")).toBe(true);
+    expect(new TextEncoder().encode(document).length).toBeLessThanOrEqual(100);
+
+    const [summarized] = applyPayloadLimit(
+      {
+        maxBytes: 100,
+        action: "summarize",
+        summarizer: (key, value) => `${(value as string).length} ${key}`,
+      },
+      "outputs",
+      values
+    );
+    expect(summarized).toEqual({ document: "1000 document" });
+  });
+});
+
+describe("Client payload limit", () => {
+  it("should send oversized inputs as attachments", async () => {
+    const client = new Client({
+      apiKey: "test-api-key",
+      payloadLimit: { maxBytes: 100 },
+    });
+    const sendSpy = jest
+      .spyOn(client as any, "_sendMultipartRequest")
+      .mockResolvedValue(true);
+    const id = uuidv4();
+    await client.multipartIngestRuns({
+      runCreates: [
+        {
+          id,
+          name: "run",
+          run_type: "chain",
+          inputs: { question: "hi", document: "x".repeat(1000) },
+          trace_id: id,
+          dotted_order: convertToDottedOrderFormat(Date.now() / 1000, id),
+        },
+      ],
+    });
+    const parts = sendSpy.mock.calls[0][0] as { name: string; payload: Blob }[];
+    const byName = Object.fromEntries(parts.map((p) => [p.name, p.payload]));
+    expect(JSON.parse(await byName[`post.${id}.inputs`].text())).toEqual({
+      question: "hi",
+      document: "[1002 bytes moved to attachment 'inputs-document']",
+    });
+    expect(await byName[`attachment.${id}.inputs-document`].text()).toBe(
+      JSON.stringify("x".repeat(1000))
+    );
+  });
+});
@@ -2,7 +2,11 @@
 
 import { jest } from "@jest/globals";
 import { Client } from "../client.js";
-import { RunTree } from "../run_trees.js";
+import {
+  RunTree,
+  repairDottedOrder,
+  validateDottedOrder,
+} from "../run_trees.js";
 
 const _DATE = 1620000000000;
 Date.now = jest.fn(() => _DATE);
@@ -121,3 +125,108 @@ test("shared client between run trees", () => {
   expect(runTree2.client).toBeDefined();
   expect(runTree1.client).toBe(runTree2.client);
 });
+
+test("propagates W3C trace context headers", () => {
+  // The Python SDK is tested against the same headers
+  const dottedOrder =
+    "20261016T060611006928Zc3868daa-7d8b-45d0-9c67-93261b813aac.20261016T060611007137Ze0466f8c-5036-4fb3-9ceb-ea8df98211c2";
+  const traceparent = "00-c3868daa7d8b45d09c6793261b813aac-9cebea8df98211c2-01";
+  const tracestate =
+    "langsmith=AAZd7vX_NdDDho2qfYtF0JxnkyYbgTqs.AAZd7vX_NqHgRm-MUDZPs5zr6o35ghHC";
+
+  const parent = RunTree.fromHeaders({
+    traceparent,
+    tracestate: `vendor=abc,${tracestate}`,
+  });
+  expect(parent).toMatchObject({
+    id: "e0466f8c-5036-4fb3-9ceb-ea8df98211c2",
+    trace_id: "c3868daa-7d8b-45d0-9c67-93261b813aac",
+    dotted_order: dottedOrder,
+  });
+  expect(parent?.toHeaders()).toMatchObject({
+    "langsmith-trace": dottedOrder,
+    traceparent,
+    tracestate,
+  });
+
+  // The tracestate entry of another trace is ignored
+  const other = new RunTree({ name: "other" }).toHeaders();
+  expect(
+    RunTree.fromHeaders({ traceparent: other.traceparent, tracestate })
+  ).toBeUndefined();
+  expect(RunTree.fromHeaders({ traceparent })).toBeUndefined();
+
+  let run = new RunTree({ name: "root" });
+  for (let depth = 2; depth <= 8; depth++) {
+    run = run.createChild({ name: `level ${depth}` });
+    expect("tracestate" in run.toHeaders()).toBe(depth <= 7);
+  }
+});
+
+test("uses UUIDv7 run ids when enabled", () => {
+  process.env.LANGSMITH_TRACING_UUID7 = "true";
+  try {
+    const parent = new RunTree({ name: "parent" });
+    const child = parent.createChild({ name: "child" });
+    expect(parent.id[14]).toBe("7");
+    expect(child.id[14]).toBe("7");
+    expect(parent.trace_id).toBe(parent.id);
+  } finally {
+    delete process.env.LANGSMITH_TRACING_UUID7;
+  }
+  expect(new RunTree({ name: "v4" }).id[14]).toBe("4");
+});
+
+test("orders children with skewed clocks after their parent", () => {
+  const id = "00000000-0000-0000-0000-00000000000";
+  const parent = new RunTree({ name: "parent", id: `${id}0` });
+  // A child whose clock is a minute behind its parent's
+  const child = parent.createChild({
+    name: "child",
+    id: `${id}1`,
+    start_time: _DATE - 60_000,
+  });
+  expect(child.dotted_order).toBe(
+    `20210503T000000000001Z${id}0.20210503T000000000001Z${id}1`
+  );
+  // The start time is moved up with the dotted order, so the two agree
+  expect(child.start_time).toBe(_DATE);
+  expect(validateDottedOrder(child.dotted_order)).toEqual([]);
+  // Children that start after their parent keep their own start time
+  const sibling = parent.createChild({
+    name: "sibling",
+    id: `${id}2`,
+    start_time: _DATE + 60_000,
+  });
+  expect(sibling.dotted_order).toBe(
+    `20210503T000000000001Z${id}0.20210503T000100000001Z${id}2`
+  );
+  expect(sibling.start_time).toBe(_DATE + 60_000);
+});
+
+test("validates and repairs dotted orders", () => {
+  const parent = "20240101T120000000000Z152ce25c-064e-4742-bf36-8bb0389f8805";
+  const child = "20240101T115900000000Zfe8b541f-e75a-4ee6-b92d-732710897194";
+  const grandchild =
+    "20240101T115930000000Z625b30ed-2fbb-4387-81b1-cb5d6221e5b4";
+  const dottedOrder = `${parent}.${child}.${grandchild}`;
+  const problems = validateDottedOrder(dottedOrder);
+  expect(problems).toHaveLength(2);
+  expect(problems[0]).toBe(
+    "Segment 1 starts at 20240101T115900000000Z, before its parent at " +
+      "20240101T120000000000Z"
+  );
+  expect(validateDottedOrder(`${parent}.not-a-segment`)).toEqual([
+    "Segment 1 is malformed: 'not-a-segment'",
+  ]);
+
+  const repaired = repairDottedOrder(dottedOrder);
+  expect(repaired).toBe(
+    `${parent}.20240101T120000000000Zfe8b541f-e75a-4ee6-b92d-732710897194` +
+      ".20240101T120000000000Z625b30ed-2fbb-4387-81b1-cb5d6221e5b4"
+  );
+  expect(validateDottedOrder(repaired)).toEqual([]);
+  // Repairing keeps a dotted order the prefix of its children's
+  const repairedChild = repairDottedOrder(`${parent}.${child}`);
+  expect(repaired.startsWith(repairedChild)).toBe(true);
+});
@@ -0,0 +1,109 @@
+/* eslint-disable @typescript-eslint/no-explicit-any */
+import { v4 as uuidv4 } from "uuid";
+import { Client } from "../client.js";
+import {
+  getSampleRate,
+  getSamplingRulesFromEnv,
+  matchesSamplingRule,
+  SamplingRule,
+} from "../utils/sampling.js";
+
+describe("sampling rules", () => {
+  const run = {
+    session_name: "prod-agents",
+    name: "retriever_docs",
+    run_type: "retriever",
+    tags: ["rag"],
+    extra: { metadata: { user_id: "1" } },
+  };
+
+  test("match every given condition", () => {
+    expect(matchesSamplingRule({ sampleRate: 0, name: "retriever*" }, run)).toBe(
+      true
+    );
+    expect(
+      matchesSamplingRule(
+        { sampleRate: 0, sessionName: "prod-*", tags: ["rag"] },
+        run
+      )
+    ).toBe(true);
+    expect(
+      matchesSamplingRule({ sampleRate: 0, metadataKeys: ["user_id"] }, run)
+    ).toBe(true);
+    expect(matchesSamplingRule({ sampleRate: 0, runType: "llm" }, run)).toBe(
+      false
+    );
+    expect(matchesSamplingRule({ sampleRate: 0, tags: ["agent"] }, run)).toBe(
+      false
+    );
+    expect(
+      matchesSamplingRule({ sampleRate: 0, metadataKeys: ["org_id"] }, run)
+    ).toBe(false);
+  });
+
+  test("first matching rule wins", () => {
+    const rules: SamplingRule[] = [
+      { sampleRate: 1, name: "agent" },
+      { sampleRate: 0.1, runType: "retriever" },
+      { sampleRate: 0.5 },
+    ];
+    expect(getSampleRate(rules, { name: "agent", run_type: "retriever" })).toBe(
+      1
+    );
+    expect(getSampleRate(rules, { name: "docs", run_type: "retriever" })).toBe(
+      0.1
+    );
+    expect(getSampleRate(rules, { name: "docs" })).toBe(0.5);
+    expect(getSampleRate(rules.slice(0, 1), { name: "docs" }, 0.2)).toBe(0.2);
+    expect(getSampleRate([], { name: "docs" })).toBe(1);
+  });
+
+  test("read from the environment", () => {
+    // eslint-disable-next-line no-process-env
+    process.env.LANGSMITH_TRACING_SAMPLING_RULES =
+      '[{"name": "retriever*", "sample_rate": 0}]';
+    try {
+      expect(getSamplingRulesFromEnv()).toEqual([
+        {
+          sampleRate: 0,
+          name: "retriever*",
+          sessionName: undefined,
+          runType: undefined,
+          tags: undefined,
+          metadataKeys: undefined,
+        },
+      ]);
+    } finally {
+      // eslint-disable-next-line no-process-env
+      delete process.env.LANGSMITH_TRACING_SAMPLING_RULES;
+    }
+  });
+
+  test("reject invalid sample rates", () => {
+    expect(
+      () =>
+        new Client({
+          apiKey: "test-api-key",
+          samplingRules: [{ sampleRate: 2 }],
+        })
+    ).toThrow("Sample rate must be between 0 and 1");
+  });
+
+  test("client drops whole traces", () => {
+    const client = new Client({
+      apiKey: "test-api-key",
+      samplingRules: [{ sampleRate: 0, name: "retriever*" }],
+    });
+    const rootId = uuidv4();
+    const otherId = uuidv4();
+    const sampled = (client as any)._filterForSampling([
+      { id: rootId, trace_id: rootId, name: "retriever_docs" },
+      { id: uuidv4(), trace_id: rootId, name: "child" },
+      { id: otherId, trace_id: otherId, name: "agent" },
+    ]);
+    expect(sampled.map((run: any) => run.name)).toEqual(["agent"]);
+    expect(
+      (client as any)._filterForSampling([{ id: rootId }], true)
+    ).toEqual([]);
+  });
+});
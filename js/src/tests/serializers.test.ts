@@ -0,0 +1,74 @@
+import { stringify } from "../utils/fast-safe-stringify/index.js";
+import {
+  registerSerializer,
+  unregisterSerializer,
+} from "../utils/serializers.js";
+
+class Celsius {
+  constructor(public degrees: number) {}
+}
+
+class Boiling extends Celsius {
+  constructor() {
+    super(100);
+  }
+}
+
+afterEach(() => {
+  for (const type of [Celsius, Boiling, Map, Date]) {
+    unregisterSerializer(type);
+  }
+});
+
+describe("registerSerializer", () => {
+  it("should encode registered classes and their subclasses", () => {
+    registerSerializer(Celsius, (c) => ({ celsius: c.degrees }));
+    registerSerializer(Map, (map) => Object.fromEntries(map));
+    const value = {
+      t: new Celsius(21.5),
+      b: new Boiling(),
+      m: new Map([["a", 1]]),
+    };
+    expect(JSON.parse(stringify(value))).toEqual({
+      t: { celsius: 21.5 },
+      b: { celsius: 100 },
+      m: { a: 1 },
+    });
+    // The most specific registered class wins
+    registerSerializer(Boiling, () => "boiling");
+    expect(JSON.parse(stringify([new Celsius(1), new Boiling()]))).toEqual([
+      { celsius: 1 },
+      "boiling",
+    ]);
+    unregisterSerializer(Map);
+    expect(JSON.parse(stringify(new Map([["a", 1]])))).toEqual({});
+  });
+
+  it("should encode objects before toJSON", () => {
+    registerSerializer(Date, (date) => date.getTime());
+    expect(stringify({ at: new Date(1000) })).toBe('{"at":1000}');
+  });
+
+  it("should cap the size of encodings", () => {
+    registerSerializer(Celsius, (c) => "x".repeat(c.degrees), {
+      maxBytes: 50,
+    });
+    expect(JSON.parse(stringify(new Celsius(10)))).toBe("x".repeat(10));
+    const capped = JSON.parse(stringify(new Celsius(100)));
+    expect(capped.startsWith('"xxx')).toBe(true);
+    expect(capped.endsWith("... [truncated 102 bytes]")).toBe(true);
+    expect(capped.length).toBeLessThanOrEqual(50);
+  });
+
+  it("should fall back when an encoder throws", () => {
+    registerSerializer(Celsius, () => {
+      throw new Error("nope");
+    });
+    expect(JSON.parse(stringify(new Celsius(1)))).toEqual({ degrees: 1 });
+  });
+
+  it("should reject plain objects and arrays", () => {
+    expect(() => registerSerializer(Object, String)).toThrow();
+    expect(() => registerSerializer(Array, String)).toThrow();
+  });
+});
@@ -0,0 +1,161 @@
+/* eslint-disable @typescript-eslint/no-explicit-any */
+import { jest } from "@jest/globals";
+import * as fs from "node:fs";
+import * as os from "node:os";
+import * as path from "node:path";
+import { v4 as uuidv4 } from "uuid";
+import { AutoBatchQueue, Client } from "../client.js";
+import { convertToDottedOrderFormat } from "../run_trees.js";
+
+const makeItem = (name: string, size = 10) => ({
+  action: "create" as const,
+  item: { id: uuidv4(), name, inputs: { text: "a".repeat(size) } },
+});
+
+const names = (queue: AutoBatchQueue) =>
+  queue.items.map((item) => (item.payload as any).name);
+
+describe("AutoBatchQueue limits", () => {
+  let warnSpy: any;
+  beforeEach(() => {
+    warnSpy = jest.spyOn(console, "warn").mockImplementation(() => {});
+  });
+  afterEach(() => {
+    warnSpy.mockRestore();
+  });
+
+  it("should drop the newest runs", async () => {
+    const queue = new AutoBatchQueue({ maxItems: 2 });
+    const promises = ["a", "b", "c"].map((name) => queue.push(makeItem(name)));
+    expect(names(queue)).toEqual(["a", "b"]);
+    expect(queue.droppedItems).toBe(1);
+    // Dropped runs don't keep awaitPendingTraceBatches waiting
+    await promises[2];
+    expect(warnSpy).toHaveBeenCalledTimes(1);
+  });
+
+  it("should drop the oldest runs to stay within the byte budget", async () => {
+    const queue = new AutoBatchQueue({
+      maxBytes: 400,
+      overflowPolicy: "drop_oldest",
+    });
+    const promises = ["a", "b", "c"].map((name) =>
+      queue.push(makeItem(name, 100))
+    );
+    expect(names(queue)).toEqual(["b", "c"]);
+    expect(queue.droppedItems).toBe(1);
+    expect(queue.droppedBytes).toBe(queue.sizeBytes / 2);
+    await promises[0];
+  });
+
+  it("should count in-flight runs until their batch is sent", () => {
+    const queue = new AutoBatchQueue({ maxItems: 2 });
+    void queue.push(makeItem("a"));
+    void queue.push(makeItem("b"));
+    const [batch, done] = queue.pop(1_000_000);
+    expect(batch).toHaveLength(2);
+    void queue.push(makeItem("c"));
+    expect(queue.droppedItems).toBe(1);
+    done();
+    void queue.push(makeItem("d"));
+    expect(names(queue)).toEqual(["d"]);
+  });
+
+  it("should block callers until there is room", async () => {
+    const queue = new AutoBatchQueue({ maxItems: 1, overflowPolicy: "block" });
+    void queue.push(makeItem("a"));
+    let waited = false;
+    const wait = queue.waitForRoom().then(() => {
+      waited = true;
+    });
+    await new Promise((resolve) => setTimeout(resolve, 10));
+    expect(waited).toBe(false);
+    const [, done] = queue.pop(1_000_000);
+    done();
+    await wait;
+    expect(waited).toBe(true);
+  });
+
+  it("should let blocked callers through one at a time", async () => {
+    const queue = new AutoBatchQueue({ maxItems: 1, overflowPolicy: "block" });
+    void queue.push(makeItem("a"));
+    const order: string[] = [];
+    const producers = ["b", "c", "d"].map(async (name) => {
+      await queue.waitForRoom();
+      order.push(name);
+      void queue.push(makeItem(name));
+    });
+    await new Promise((resolve) => setTimeout(resolve, 10));
+    expect(order).toEqual([]);
+    for (const name of ["b", "c", "d"]) {
+      const [, done] = queue.pop(1_000_000);
+      done();
+      await new Promise((resolve) => setTimeout(resolve, 10));
+      // Only one caller got through, into the room the batch made
+      expect(names(queue)).toEqual([name]);
+    }
+    await Promise.all(producers);
+    expect(order).toEqual(["b", "c", "d"]);
+  });
+
+  it("should spill runs that don't fit to disk", async () => {
+    const dir = fs.mkdtempSync(path.join(os.tmpdir(), "langsmith-spill-"));
+    const queue = new AutoBatchQueue({
+      maxItems: 1,
+      overflowPolicy: "spill",
+      spillDir: dir,
+    });
+    void queue.push(makeItem("a"));
+    await queue.push(makeItem("b"));
+    await queue.spillWrites;
+    expect(names(queue)).toEqual(["a"]);
+    expect(queue.spilledItems).toBe(1);
+    const [file] = fs.readdirSync(dir);
+    const line = JSON.parse(fs.readFileSync(path.join(dir, file), "utf-8"));
+    expect(line).toMatchObject({ event: "post", payload: { name: "b" } });
+    expect(() => new AutoBatchQueue({ overflowPolicy: "spill" })).toThrow(
+      "spill directory"
+    );
+  });
+
+  it("should make the client wait for room with the block policy", async () => {
+    const client = new Client({
+      apiKey: "test-api-key",
+      tracingQueueMaxItems: 1,
+      tracingQueueOverflowPolicy: "block",
+    });
+    let release: () => void = () => {};
+    const sent = new Promise<void>((resolve) => {
+      release = resolve;
+    });
+    jest
+      .spyOn(client as any, "_getServerInfo")
+      .mockResolvedValue({ batch_ingest_config: {} });
+    const ingestSpy = jest
+      .spyOn(client, "_ingestBatch")
+      .mockImplementation(() => sent.then(() => true));
+    const createRun = () => {
+      const id = uuidv4();
+      return client.createRun({
+        id,
+        name: "run",
+        run_type: "chain",
+        inputs: {},
+        trace_id: id,
+        dotted_order: convertToDottedOrderFormat(Date.now() / 1000, id),
+      });
+    };
+    await createRun();
+    let created = false;
+    const second = createRun().then(() => {
+      created = true;
+    });
+    await new Promise((resolve) => setTimeout(resolve, 400));
+    expect(ingestSpy).toHaveBeenCalledTimes(1);
+    expect(created).toBe(false);
+    release();
+    await second;
+    await client.awaitPendingTraceBatches();
+    expect(ingestSpy).toHaveBeenCalledTimes(2);
+  });
+});
@@ -0,0 +1,81 @@
+/* eslint-disable @typescript-eslint/no-explicit-any */
+import { jest } from "@jest/globals";
+import { v4 as uuidv4 } from "uuid";
+import { Client, ClientConfig } from "../client.js";
+import { convertToDottedOrderFormat } from "../run_trees.js";
+import { traceable } from "../traceable.js";
+
+const makeClient = (config: ClientConfig = {}) => {
+  const client = new Client({ apiKey: "test-api-key", ...config });
+  jest
+    .spyOn(client as any, "_getServerInfo")
+    .mockResolvedValue({ batch_ingest_config: {} });
+  return client;
+};
+
+describe("waitUntil", () => {
+  it("should register queued runs without blocking root runs", async () => {
+    const registered: Promise<unknown>[] = [];
+    const client = makeClient({
+      blockOnRootRunFinalization: true,
+      waitUntil: (promise) => registered.push(promise),
+    });
+    let release: () => void = () => {};
+    const sent = new Promise<void>((resolve) => {
+      release = resolve;
+    });
+    const ingestSpy = jest
+      .spyOn(client, "_ingestBatch")
+      .mockImplementation(() => sent.then(() => true));
+    const id = uuidv4();
+    const dotted_order = convertToDottedOrderFormat(Date.now() / 1000, id);
+    await client.createRun({
+      id,
+      name: "run",
+      run_type: "chain",
+      inputs: {},
+      trace_id: id,
+      dotted_order,
+    });
+    // Ending the root run returns before its trace is sent
+    await client.updateRun(id, {
+      end_time: Date.now(),
+      outputs: {},
+      trace_id: id,
+      dotted_order,
+    });
+    expect(registered).toHaveLength(2);
+    let settled = false;
+    const all = Promise.all(registered).then(() => {
+      settled = true;
+    });
+    await new Promise((resolve) => setTimeout(resolve, 300));
+    expect(ingestSpy).toHaveBeenCalled();
+    expect(settled).toBe(false);
+    release();
+    await all;
+  });
+
+  it("should register the trace of a traceable root run", async () => {
+    const client = makeClient();
+    const ingestSpy = jest
+      .spyOn(client, "_ingestBatch")
+      .mockResolvedValue(true);
+    const ctx = { waitUntil: jest.fn<(promise: Promise<unknown>) => void>() };
+    const child = traceable(async (x: number) => x + 1, { name: "child" });
+    const parent = traceable(async (x: number) => child(x), {
+      name: "parent",
+      client,
+      waitUntil: ctx,
+      tracingEnabled: true,
+    });
+    expect(await parent(1)).toBe(2);
+    // Only the root run registers with the hook
+    expect(ctx.waitUntil).toHaveBeenCalledTimes(1);
+    await ctx.waitUntil.mock.calls[0][0];
+    const runs = ingestSpy.mock.calls.flatMap(([batch]) => batch.runCreates);
+    expect(new Set(runs.map((run) => run.name))).toEqual(
+      new Set(["parent", "child"])
+    );
+  });
+});
@@ -0,0 +1,194 @@
+import { getLangSmithEnvironmentVariable } from "./env.js";
+
+export type CircuitState = "closed" | "open" | "half_open";
+
+export interface CircuitBreakerConfig {
+  /** Consecutive failed requests that open the breaker. Defaults to 5. */
+  failureThreshold?: number;
+  /** How long the breaker stays open before a probe. Defaults to 30s. */
+  resetTimeoutMs?: number;
+  /** Returns the current time in milliseconds. Defaults to `Date.now`. */
+  clock?: () => number;
+}
+
+// Besides server errors, the statuses that mean the API is unavailable
+const RETRYABLE_STATUSES = [408, 429];
+
+/**
+ * Whether a response, or the lack of one, suggests the API is unavailable.
+ * Other client errors, like a rejected payload, only say the request was
+ * bad, and sending it again won't help.
+ */
+export function isRetryableResponse(
+  response?: Pick<Response, "ok" | "status">
+): boolean {
+  if (response === undefined) {
+    return true;
+  }
+  if (response.ok) {
+    return false;
+  }
+  return (
+    response.status >= 500 || RETRYABLE_STATUSES.includes(response.status)
+  );
+}
+
+/**
+ * Stop sending traces after repeated failures, until the API recovers.
+ *
+ * The breaker opens after `failureThreshold` consecutive ingestion requests
+ * fail because the API is unreachable, overloaded or erroring. While it is
+ * open, batches are dropped instead of sent: they count as failed in the
+ * client's `flush()` report, and in `shortCircuitedRuns`. After
+ * `resetTimeoutMs` a single batch is let through as a probe. If it is
+ * delivered the breaker closes, and if not it opens again.
+ *
+ * @example
+ * ```ts
+ * const client = new Client({
+ *   circuitBreaker: new CircuitBreaker({
+ *     failureThreshold: 3,
+ *     resetTimeoutMs: 60_000,
+ *   }),
+ * });
+ * ```
+ */
+export class CircuitBreaker {
+  failureThreshold: number;
+
+  resetTimeoutMs: number;
+
+  timesOpened = 0;
+
+  shortCircuitedRuns = 0;
+
+  private clock: () => number;
+
+  private _state: CircuitState = "closed";
+
+  private failures = 0;
+
+  private openedAt = 0;
+
+  private probeStarted?: number;
+
+  constructor(config: CircuitBreakerConfig = {}) {
+    this.failureThreshold = config.failureThreshold ?? 5;
+    this.resetTimeoutMs = config.resetTimeoutMs ?? 30_000;
+    this.clock = config.clock ?? Date.now;
+    if (!(this.failureThreshold >= 1)) {
+      throw new Error("Circuit breaker failureThreshold must be at least 1.");
+    }
+  }
+
+  /** Whether requests are sent ("closed"), paused ("open") or probing. */
+  get state(): CircuitState {
+    if (this._state === "open" && this.probeDue()) {
+      return "half_open";
+    }
+    return this._state;
+  }
+
+  /**
+   * Whether a batch may be sent now.
+   *
+   * Once the reset timeout has passed, returns true for a single probe until
+   * its outcome is recorded, or for another one if no outcome is recorded
+   * within the reset timeout.
+   */
+  allowRequest(): boolean {
+    if (this._state === "closed") {
+      return true;
+    }
+    if (this._state === "open" && this.probeDue()) {
+      this._state = "half_open";
+    }
+    if (
+      this._state === "half_open" &&
+      (this.probeStarted === undefined ||
+        this.clock() - this.probeStarted >= this.resetTimeoutMs)
+    ) {
+      this.probeStarted = this.clock();
+      return true;
+    }
+    return false;
+  }
+
+  /** Record a request that reached the API, closing the breaker. */
+  recordSuccess(): void {
+    if (this._state !== "closed") {
+      console.info("LangSmith API recovered, resuming tracing.");
+    }
+    this._state = "closed";
+    this.failures = 0;
+    this.probeStarted = undefined;
+  }
+
+  /** Record a failed request, opening the breaker past the threshold. */
+  recordFailure(): void {
+    this.failures += 1;
+    this.probeStarted = undefined;
+    if (
+      this._state === "half_open" ||
+      (this._state === "closed" && this.failures >= this.failureThreshold)
+    ) {
+      if (this._state === "closed") {
+        console.warn(
+          `Pausing tracing for ${this.resetTimeoutMs / 1000}s after ` +
+            `${this.failures} failed requests to the LangSmith API.`
+        );
+      }
+      this._state = "open";
+      this.openedAt = this.clock();
+      this.timesOpened += 1;
+    }
+  }
+
+  /**
+   * Record the response to a request, or undefined if it got none, counting
+   * only signs of an outage as failures. Other errors leave the count of
+   * failures as it is.
+   */
+  recordResponse(response?: Pick<Response, "ok" | "status">): void {
+    if (response?.ok) {
+      this.recordSuccess();
+    } else if (isRetryableResponse(response)) {
+      this.recordFailure();
+    } else {
+      // Let another probe through rather than wait out its timeout
+      this.probeStarted = undefined;
+    }
+  }
+
+  /** Count runs in a batch that wasn't sent because the breaker was open. */
+  recordShortCircuited(nRuns: number): void {
+    this.shortCircuitedRuns += nRuns;
+  }
+
+  private probeDue(): boolean {
+    return this.clock() - this.openedAt >= this.resetTimeoutMs;
+  }
+}
+
+/**
+ * Create a circuit breaker from the environment, if one is configured.
+ *
+ * Reads LANGSMITH_TRACING_CIRCUIT_BREAKER_THRESHOLD, the number of
+ * consecutive failures that open the breaker, and optionally
+ * LANGSMITH_TRACING_CIRCUIT_BREAKER_RESET_TIMEOUT, in seconds.
+ */
+export function getCircuitBreakerFromEnv(): CircuitBreaker | undefined {
+  const threshold = getLangSmithEnvironmentVariable(
+    "TRACING_CIRCUIT_BREAKER_THRESHOLD"
+  );
+  if (!threshold) {
+    return undefined;
+  }
+  const resetTimeout = getLangSmithEnvironmentVariable(
+    "TRACING_CIRCUIT_BREAKER_RESET_TIMEOUT"
+  );
+  return new CircuitBreaker({
+    failureThreshold: parseInt(threshold, 10),
+    resetTimeoutMs: resetTimeout ? parseFloat(resetTimeout) * 1000 : undefined,
+  });
+}
@@ -0,0 +1,51 @@
+export type CompressionMethod = "gzip" | "zstd";
+
+export const COMPRESSION_METHODS: CompressionMethod[] = ["gzip", "zstd"];
+
+/**
+ * Whether this runtime can compress request bodies with a method. gzip is
+ * widely available through `CompressionStream`; zstd only where the runtime
+ * adds it.
+ */
+export function isCompressionAvailable(method: CompressionMethod): boolean {
+  if (typeof CompressionStream === "undefined") {
+    return false;
+  }
+  try {
+    new CompressionStream(method as CompressionFormat);
+    return true;
+  } catch {
+    return false;
+  }
+}
+
+/**
+ * Choose the compression method for requests to the ingestion endpoints,
+ * falling back to gzip when the preferred one isn't available. Servers that
+ * don't list any methods in their batch ingest config are sent uncompressed
+ * requests.
+ */
+export function negotiateCompression(
+  preferred?: CompressionMethod,
+  supported?: string[]
+): CompressionMethod | undefined {
+  if (!preferred || !supported?.length) {
+    return undefined;
+  }
+  for (const method of [preferred, "gzip"] as CompressionMethod[]) {
+    if (supported.includes(method) && isCompressionAvailable(method)) {
+      return method;
+    }
+  }
+  return undefined;
+}
+
+export async function compress(
+  data: Uint8Array,
+  method: CompressionMethod
+): Promise<Uint8Array> {
+  const stream = new Blob([data])
+    .stream()
+    .pipeThrough(new CompressionStream(method as CompressionFormat));
+  return new Uint8Array(await new Response(stream).arrayBuffer());
+}
@@ -1,5 +1,7 @@
 /* eslint-disable */
 // @ts-nocheck
+import { hasSerializers, serializerReplacer } from "../serializers.js";
+
 var LIMIT_REPLACE_NODE = "[...]";
 var CIRCULAR_REPLACE_NODE = { result: "[Circular]" };
 
@@ -15,6 +17,9 @@ function defaultOptions() {
 
 // Regular stringify
 export function stringify(obj, replacer?, spacer?, options?) {
+  if (replacer === undefined && hasSerializers()) {
+    replacer = serializerReplacer;
+  }
   try {
     return JSON.stringify(obj, replacer, spacer);
   } catch (e: any) {
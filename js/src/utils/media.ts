@@ -0,0 +1,106 @@
+// data:[<media type>][;<parameter>]*;base64,<data>
+const DATA_URL =
+  /^data:([\w.+-]+\/[\w.+-]+)(?:;[\w.+-]+=[\w.+-]+)*;base64,([\s\S]*)$/;
+
+function decodeBase64(data: string): Uint8Array | undefined {
+  try {
+    return Uint8Array.from(atob(data), (char) => char.charCodeAt(0));
+  } catch {
+    return undefined;
+  }
+}
+
+function isPlainObject(value: unknown): value is Record<string, unknown> {
+  return (
+    typeof value === "object" &&
+    value !== null &&
+    Object.getPrototypeOf(value) === Object.prototype
+  );
+}
+
+class MediaExtractor {
+  field: string;
+
+  attachments: Record<string, [string, Uint8Array]> = {};
+
+  constructor(field: string) {
+    this.field = field;
+  }
+
+  /** Add an attachment, returning the reference to leave in its place. */
+  attach(contentType: string, data: string): string | undefined {
+    const decoded = decodeBase64(data);
+    if (decoded === undefined) {
+      return undefined;
+    }
+    const name = `${this.field}-media-${Object.keys(this.attachments).length}`;
+    this.attachments[name] = [contentType, decoded];
+    return `attachment://${name}`;
+  }
+
+  /** Return the value with its media replaced, copying what changes. */
+  visit(value: unknown): unknown {
+    if (typeof value === "string") {
+      const match = value.startsWith("data:") ? DATA_URL.exec(value) : null;
+      return (match && this.attach(match[1], match[2])) ?? value;
+    }
+    if (Array.isArray(value)) {
+      const items = value.map((item) => this.visit(item));
+      return items.every((item, i) => item === value[i]) ? value : items;
+    }
+    if (isPlainObject(value)) {
+      const replaced = this.visitMediaBlock(value);
+      if (replaced !== undefined) {
+        return replaced;
+      }
+      const entries = Object.entries(value).map(
+        ([key, item]) => [key, this.visit(item)] as const
+      );
+      return entries.every(([key, item]) => item === value[key])
+        ? value
+        : Object.fromEntries(entries);
+    }
+    return value;
+  }
+
+  private visitMediaBlock(
+    block: Record<string, unknown>
+  ): Record<string, unknown> | undefined {
+    const { data } = block;
+    if (typeof data !== "string") {
+      return undefined;
+    }
+    // Anthropic: { type: "base64", media_type: "image/png", data }
+    if (block.type === "base64" && typeof block.media_type === "string") {
+      const reference = this.attach(block.media_type, data);
+      if (reference !== undefined) {
+        return { ...block, data: reference };
+      }
+    }
+    // OpenAI input audio: { data, format: "wav" }
+    if (Object.keys(block).length === 2 && typeof block.format === "string") {
+      const reference = this.attach(`audio/${block.format}`, data);
+      if (reference !== undefined) {
+        return { ...block, data: reference };
+      }
+    }
+    return undefined;
+  }
+}
+
+/**
+ * Move the base64 media in a run's inputs or outputs into attachments.
+ *
+ * Finds `data:` URLs, such as the image URLs of OpenAI messages, and the
+ * base64 sources of Anthropic content blocks and OpenAI input audio. Each is
+ * replaced by an `attachment://{field}-media-{n}` reference to the attachment
+ * its decoded contents are moved to. The values passed in are left as they
+ * are.
+ */
+export function extractInlineMedia(
+  field: string,
+  values: unknown
+): [unknown, Record<string, [string, Uint8Array]>] {
+  const extractor = new MediaExtractor(field);
+  return [extractor.visit(values), extractor.attachments];
+}
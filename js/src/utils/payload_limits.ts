@@ -0,0 +1,98 @@
+import { getLangSmithEnvironmentVariable } from "./env.js";
+import { stringify as stringifyForTracing } from "./fast-safe-stringify/index.js";
+import { truncateText } from "./serializers.js";
+
+export type PayloadLimitAction = "attach" | "truncate" | "summarize";
+
+/**
+ * What to do with input and output values too large to send as they are.
+ * Applies to each top-level key of a run's inputs and outputs whose
+ * serialized value is over `maxBytes`:
+ *
+ * - "attach" (the default) moves the value into a JSON attachment of the run
+ *   named `{inputs|outputs}-{key}`.
+ * - "truncate" cuts the value down to `maxBytes`, ending it with a marker.
+ * - "summarize" replaces the value with `summarizer(key, value)`, which is
+ *   truncated if it's still too large.
+ */
+export interface PayloadLimit {
+  maxBytes: number;
+  action?: PayloadLimitAction;
+  summarizer?: (key: string, value: unknown) => unknown;
+}
+
+const ACTIONS: PayloadLimitAction[] = ["attach", "truncate", "summarize"];
+
+const encoder = new TextEncoder();
+
+export function validatePayloadLimit(limit: PayloadLimit): PayloadLimit {
+  const action = limit.action ?? "attach";
+  if (!ACTIONS.includes(action)) {
+    const actions = ACTIONS.join(", ");
+    throw new Error(
+      `Unsupported payload limit action "${action}". Expected one of ${actions}.`
+    );
+  }
+  if (action === "summarize" && limit.summarizer === undefined) {
+    throw new Error(
+      `The "summarize" payload limit action requires a summarizer.`
+    );
+  }
+  return limit;
+}
+
+export function getPayloadLimitFromEnv(): PayloadLimit | undefined {
+  const maxBytes = getLangSmithEnvironmentVariable("TRACING_PAYLOAD_MAX_BYTES");
+  if (!maxBytes) {
+    return undefined;
+  }
+  const action = getLangSmithEnvironmentVariable(
+    "TRACING_PAYLOAD_LIMIT_ACTION"
+  ) as PayloadLimitAction | undefined;
+  return validatePayloadLimit({ maxBytes: parseInt(maxBytes, 10), action });
+}
+
+function truncate(limit: PayloadLimit, value: unknown, serialized: Uint8Array) {
+  // Keep strings as they are, and anything else as JSON text
+  const text = typeof value === "string" ? encoder.encode(value) : serialized;
+  return truncateText(text, limit.maxBytes);
+}
+
+/**
+ * Shrink the oversized values of a run's inputs or outputs, returning the
+ * values to send and the attachments to send with them.
+ */
+export function applyPayloadLimit(
+  limit: PayloadLimit,
+  field: string,
+  values: unknown
+): [unknown, Record<string, [string, Uint8Array]>] {
+  const attachments: Record<string, [string, Uint8Array]> = {};
+  if (typeof values !== "object" || values === null || Array.isArray(values)) {
+    return [values, attachments];
+  }
+  let limited: Record<string, unknown> | undefined;
+  for (const [key, value] of Object.entries(values)) {
+    const serialized = encoder.encode(stringifyForTracing(value));
+    if (serialized.length <= limit.maxBytes) {
+      continue;
+    }
+    limited ??= { ...values };
+    const action = limit.action ?? "attach";
+    if (action === "attach") {
+      const name = `${field}-${key}`;
+      attachments[name] = ["application/json", serialized];
+      limited[key] = `[${serialized.length} bytes moved to attachment '${name}']`;
+    } else if (action === "summarize" && limit.summarizer) {
+      const summary = limit.summarizer(key, value);
+      const summaryBytes = encoder.encode(stringifyForTracing(summary));
+      limited[key] =
+        summaryBytes.length <= limit.maxBytes
+          ? summary
+          : truncate(limit, summary, summaryBytes);
+    } else {
+      limited[key] = truncate(limit, value, serialized);
+    }
+  }
+  return [limited ?? values, attachments];
+}
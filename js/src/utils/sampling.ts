@@ -0,0 +1,139 @@
+import type { KVMap } from "../schemas.js";
+import { getLangSmithEnvironmentVariable } from "./env.js";
+
+/**
+ * Sets the sample rate for root runs matching every given condition.
+ * String conditions are glob patterns (`*` and `?`), e.g. `name: "retriever*"`.
+ * Rules are evaluated in order and the first match decides whether a trace
+ * is sent. Child runs follow their trace's decision.
+ */
+export interface SamplingRule {
+  /** The fraction of matching traces to send, between 0 and 1. */
+  sampleRate: number;
+  /** Match the project the run is logged to. */
+  sessionName?: string;
+  /** Match the name of the run. */
+  name?: string;
+  /** Match the run type, e.g. "llm" or "retriever". */
+  runType?: string;
+  /** Match runs carrying any of these tags. */
+  tags?: string[];
+  /** Match runs whose metadata has all of these keys. */
+  metadataKeys?: string[];
+}
+
+const globCache = new Map<string, RegExp>();
+
+function globToRegExp(pattern: string): RegExp {
+  let regex = globCache.get(pattern);
+  if (regex === undefined) {
+    const source = pattern
+      .split("")
+      .map((char) => {
+        if (char === "*") return ".*";
+        if (char === "?") return ".";
+        return char.replace(/[.+^${}()|[\]\\]/g, "\\$&");
+      })
+      .join("");
+    regex = new RegExp(`^${source}$`);
+    globCache.set(pattern, regex);
+  }
+  return regex;
+}
+
+function matchesGlob(pattern: string | undefined, value: unknown): boolean {
+  if (pattern === undefined) {
+    return true;
+  }
+  if (value === undefined || value === null) {
+    return false;
+  }
+  return globToRegExp(pattern).test(String(value));
+}
+
+export function matchesSamplingRule(rule: SamplingRule, run: KVMap): boolean {
+  if (
+    !matchesGlob(rule.sessionName, run.session_name ?? run.project_name) ||
+    !matchesGlob(rule.name, run.name) ||
+    !matchesGlob(rule.runType, run.run_type)
+  ) {
+    return false;
+  }
+  if (rule.tags !== undefined) {
+    const runTags: string[] = run.tags ?? [];
+    if (
+      !runTags.some((tag) =>
+        rule.tags?.some((pattern) => matchesGlob(pattern, tag))
+      )
+    ) {
+      return false;
+    }
+  }
+  if (rule.metadataKeys !== undefined) {
+    const metadata: KVMap = run.extra?.metadata ?? {};
+    if (!rule.metadataKeys.every((key) => key in metadata)) {
+      return false;
+    }
+  }
+  return true;
+}
+
+/**
+ * Returns the sample rate of the first rule matching a root run, or
+ * `defaultRate` (1 if undefined) when none match.
+ */
+export function getSampleRate(
+  rules: SamplingRule[],
+  run: KVMap,
+  defaultRate?: number
+): number {
+  for (const rule of rules) {
+    if (matchesSamplingRule(rule, run)) {
+      return rule.sampleRate;
+    }
+  }
+  return defaultRate ?? 1;
+}
+
+export function validateSamplingRules(rules: SamplingRule[]): SamplingRule[] {
+  for (const rule of rules) {
+    if (
+      typeof rule.sampleRate !== "number" ||
+      rule.sampleRate < 0 ||
+      rule.sampleRate > 1
+    ) {
+      throw new Error(
+        `Sample rate must be between 0 and 1. Got: ${rule.sampleRate}`
+      );
+    }
+  }
+  return rules;
+}
+
+/**
+ * Reads rules from the LANGSMITH_TRACING_SAMPLING_RULES environment variable,
+ * a JSON list such as `[{"name": "retriever*", "sample_rate": 0.01}]`.
+ * Keys may be given in snake_case to share the value with the Python SDK.
+ */
+export function getSamplingRulesFromEnv(): SamplingRule[] {
+  const rulesStr = getLangSmithEnvironmentVariable("TRACING_SAMPLING_RULES");
+  if (!rulesStr) {
+    return [];
+  }
+  let parsed: KVMap[];
+  try {
+    parsed = JSON.parse(rulesStr);
+  } catch (e) {
+    throw new Error(`Invalid LANGSMITH_TRACING_SAMPLING_RULES: ${e}`);
+  }
+  return validateSamplingRules(
+    parsed.map((rule) => ({
+      sampleRate: rule.sampleRate ?? rule.sample_rate,
+      sessionName: rule.sessionName ?? rule.session_name,
+      name: rule.name,
+      runType: rule.runType ?? rule.run_type,
+      tags: rule.tags,
+      metadataKeys: rule.metadataKeys ?? rule.metadata_keys,
+    }))
+  );
+}
@@ -0,0 +1,121 @@
+// eslint-disable-next-line @typescript-eslint/no-explicit-any
+type Constructor<T> = abstract new (...args: any[]) => T;
+
+export interface SerializerOptions {
+  /**
+   * The most bytes an encoded instance takes when serialized. Larger
+   * encodings are replaced with their JSON text, truncated to `maxBytes` and
+   * ending with a marker.
+   */
+  maxBytes?: number;
+}
+
+interface Serializer {
+  encoder: (value: unknown) => unknown;
+  maxBytes?: number;
+}
+
+const serializers = new Map<unknown, Serializer>();
+
+const encoder = new TextEncoder();
+
+/**
+ * Serialize instances of a class with an encoder when tracing.
+ *
+ * Applies to instances of `type` and its subclasses wherever LangSmith
+ * serializes run data, before their `toJSON` method, so the encoder sees a
+ * `Date` rather than its string. The encoder returns any JSON-serializable
+ * value. If it throws, the object is serialized as if no encoder was
+ * registered. Registering a class again replaces its encoder.
+ *
+ * @example
+ * ```ts
+ * registerSerializer(Map, (map) => Object.fromEntries(map), {
+ *   maxBytes: 10_000,
+ * });
+ * ```
+ */
+export function registerSerializer<T>(
+  type: Constructor<T>,
+  encoder: (value: T) => unknown,
+  options?: SerializerOptions
+): void {
+  if ((type as unknown) === Object || (type as unknown) === Array) {
+    throw new Error(
+      `Can't register a serializer for ${type.name}, which is always ` +
+        `serialized as JSON directly.`
+    );
+  }
+  if (options?.maxBytes !== undefined && options.maxBytes <= 0) {
+    throw new Error("maxBytes must be positive.");
+  }
+  serializers.set(type, {
+    encoder: encoder as (value: unknown) => unknown,
+    maxBytes: options?.maxBytes,
+  });
+}
+
+/** Go back to the built-in serialization of a class. */
+export function unregisterSerializer(type: Constructor<unknown>): void {
+  serializers.delete(type);
+}
+
+export function hasSerializers(): boolean {
+  return serializers.size > 0;
+}
+
+/** Cut UTF-8 text down to `maxBytes`, ending it with a marker. */
+export function truncateText(text: Uint8Array, maxBytes: number): string {
+  const marker = `... [truncated ${text.length} bytes]`;
+  const kept = text.slice(0, Math.max(0, maxBytes - marker.length));
+  // Cutting may split a multi-byte character, so drop its remains
+  return new TextDecoder().decode(kept).replace(/\uFFFD+$/, "") + marker;
+}
+
+function findSerializer(value: unknown): Serializer | undefined {
+  if (typeof value !== "object" || value === null) {
+    return undefined;
+  }
+  // The most specific registered class wins
+  for (
+    let proto = Object.getPrototypeOf(value);
+    proto !== null;
+    proto = Object.getPrototypeOf(proto)
+  ) {
+    const serializer = serializers.get(proto.constructor);
+    if (serializer !== undefined) {
+      return serializer;
+    }
+  }
+  return undefined;
+}
+
+/** A `JSON.stringify` replacer applying the registered serializers. */
+export function serializerReplacer(
+  this: Record<string, unknown>,
+  key: string,
+  value: unknown
+): unknown {
+  const original = this[key];
+  const serializer = findSerializer(original);
+  if (serializer === undefined) {
+    return value;
+  }
+  let encoded: unknown;
+  try {
+    encoded = serializer.encoder(original);
+  } catch (e) {
+    console.warn(`Registered serializer failed: ${e}`);
+    return value;
+  }
+  if (serializer.maxBytes === undefined) {
+    return encoded;
+  }
+  const serialized = encoder.encode(
+    JSON.stringify(encoded, serializerReplacer)
+  );
+  if (serialized.length <= serializer.maxBytes) {
+    return encoded;
+  }
+  return truncateText(serialized, serializer.maxBytes);
+}
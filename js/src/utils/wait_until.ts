@@ -0,0 +1,22 @@
+/**
+ * Keeps a serverless invocation alive until a promise settles, e.g.
+ * `waitUntil` from `@vercel/functions`.
+ */
+export type WaitUntil = (promise: Promise<unknown>) => void;
+
+/**
+ * A `waitUntil` function, or an object with a `waitUntil` method such as a
+ * Cloudflare Workers `ExecutionContext`.
+ */
+export type WaitUntilLike = WaitUntil | { waitUntil: WaitUntil };
+
+export function toWaitUntil(hook?: WaitUntilLike): WaitUntil | undefined {
+  if (hook === undefined) {
+    return undefined;
+  }
+  if (typeof hook === "function") {
+    return hook;
+  }
+  // Methods like ExecutionContext.waitUntil must be called on their context
+  return (promise) => hook.waitUntil(promise);
+}
@@ -6,7 +6,9 @@ from bench.dumps_json import (
     DeeplyNestedModelV1,
     create_nested_instance,
 )
+from bench.snapshot import create_large_inputs
 from langsmith.client import _dumps_json
+from langsmith.utils import deepish_copy, snapshot
 
 
 class MyClass:
@@ -62,6 +64,16 @@ benchmarks = (
         lambda x: _dumps_json({"input": x}),
         create_nested_instance(50, 100, branch_constructor=DeeplyNestedModelV1),
     ),
+    (
+        "deepish_copy_large_inputs",
+        deepish_copy,
+        create_large_inputs(),
+    ),
+    (
+        "snapshot_large_inputs",
+        snapshot,
+        create_large_inputs(),
+    ),
 )
 
 
@@ -0,0 +1,34 @@
+import uuid
+from datetime import datetime, timezone
+from typing import Any, Dict
+
+
+def create_large_inputs(n_messages: int = 500) -> Dict[str, Any]:
+    """Inputs shaped like a long chat model call."""
+    return {
+        "messages": [
+            {
+                "role": "user" if i % 2 else "assistant",
+                "content": [
+                    {"type": "text", "text": f"Message {i} " * 20},
+                    {"type": "tool_use", "id": str(uuid.uuid4()), "input": {"q": i}},
+                ],
+                "created_at": datetime.now(timezone.utc),
+            }
+            for i in range(n_messages)
+        ],
+        "model": "a-model",
+        "temperature": 0.2,
+        "stop": ("\n\n", "END"),
+    }
+
+
+if __name__ == "__main__":
+    import timeit
+
+    from langsmith.utils import deepish_copy, snapshot
+
+    inputs = create_large_inputs()
+    for fn in (deepish_copy, snapshot):
+        seconds = min(timeit.repeat(lambda: fn(inputs), number=20, repeat=5)) / 20
+        print(f"{fn.__name__}: {seconds * 1000:.2f} ms")
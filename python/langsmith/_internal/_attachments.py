@@ -0,0 +1,159 @@
+"""Attachments held in memory, in files, or in streams."""
+
+from __future__ import annotations
+
+import base64
+import io
+import os
+import pathlib
+from typing import IO, Any, Dict, Optional, Tuple, Union
+
+AttachmentData = Union[bytes, pathlib.Path, IO[bytes]]
+
+
+class AttachmentReader:
+    """Stream a file or stream attachment into a multipart request body.
+
+    Files are opened on the first read and closed once they're read. Streams
+    are read from the position they had when the reader was created.
+    ``len()`` is the number of bytes left to read, as a MultipartEncoder
+    expects of the bodies it streams.
+    """
+
+    def __init__(self, data: Union[pathlib.Path, IO[bytes]]) -> None:
+        """Initialize the reader, without reading anything yet."""
+        self._path: Optional[pathlib.Path] = None
+        self._source: Optional[IO[bytes]] = None
+        if isinstance(data, pathlib.Path):
+            self._path = data
+        else:
+            self._source = data
+        self._start = 0 if self._source is None else self._source.tell()
+        self._file: Optional[IO[bytes]] = None
+        self.size = attachment_size(data)
+        self._read = 0
+
+    def __len__(self) -> int:
+        """Return the number of bytes left to read."""
+        return self.size - self._read
+
+    def read(self, size: Optional[int] = -1) -> bytes:
+        """Read up to ``size`` bytes, or everything that's left."""
+        stream = self._open()
+        left = len(self)
+        chunk = stream.read(left if size is None or size < 0 else min(size, left))
+        self._read += len(chunk)
+        if not chunk:
+            # The file shrank since its size was taken
+            self._read = self.size
+        if not len(self):
+            self._close()
+        return chunk
+
+    def rewind(self) -> None:
+        """Start reading from the beginning again, e.g. to retry a request."""
+        self._close()
+        self._read = 0
+
+    def _open(self) -> IO[bytes]:
+        if self._file is None:
+            if self._path is not None:
+                self._file = open(self._path, "rb")
+            else:
+                assert self._source is not None
+                self._source.seek(self._start)
+                self._file = self._source
+        return self._file
+
+    def _close(self) -> None:
+        # Streams belong to the caller, so only close files opened here
+        if self._file is not None and self._path is not None:
+            self._file.close()
+        self._file = None
+
+
+def attachment_size(data: Any) -> int:
+    """Get the number of bytes an attachment will send."""
+    if isinstance(data, (bytes, bytearray)):
+        return len(data)
+    if isinstance(data, pathlib.Path):
+        return os.path.getsize(data)
+    position = data.tell()
+    end = data.seek(0, io.SEEK_END)
+    data.seek(position)
+    return end - position
+
+
+def read_attachment(data: Any) -> bytes:
+    """Read the whole of an attachment, leaving a stream where it was."""
+    if isinstance(data, (bytes, bytearray)):
+        return bytes(data)
+    if isinstance(data, pathlib.Path):
+        return data.read_bytes()
+    position = data.tell()
+    try:
+        return data.read()
+    finally:
+        data.seek(position)
+
+
+def as_part_body(data: Any) -> Union[bytes, AttachmentReader]:
+    """Get a body for the multipart part of an attachment."""
+    if isinstance(data, (bytes, bytearray)):
+        return data
+    if not isinstance(data, pathlib.Path) and not data.seekable():
+        # A stream that can't be sent again on retry
+        return data.read()
+    return AttachmentReader(data)
+
+
+def read_streams(
+    attachments: Dict[str, Tuple[str, Any]],
+) -> Dict[str, Tuple[str, Any]]:
+    """Read the stream attachments, so they can be sent more than once at a time.
+
+    Files are left to be read by each request.
+    """
+    return {
+        name: (
+            content_type,
+            data
+            if isinstance(data, (bytes, bytearray, pathlib.Path))
+            else read_attachment(data),
+        )
+        for name, (content_type, data) in attachments.items()
+    }
+
+
+def encode_attachments(
+    attachments: Dict[str, Tuple[str, Any]], *, inline_files: bool
+) -> Dict[str, Tuple[str, Any]]:
+    """Make attachments JSON-serializable, to be written to disk.
+
+    Contents are base64-encoded by the serializer. Files are referenced by
+    path unless ``inline_files``, and streams are read.
+    """
+    return {
+        name: (
+            content_type,
+            {"path": str(data)}
+            if isinstance(data, pathlib.Path) and not inline_files
+            else read_attachment(data),
+        )
+        for name, (content_type, data) in attachments.items()
+    }
+
+
+def decode_attachments(
+    attachments: Dict[str, Tuple[str, Any]],
+) -> Dict[str, Tuple[str, AttachmentData]]:
+    """Restore attachments written by encode_attachments."""
+    return {
+        name: (
+            content_type,
+            pathlib.Path(data["path"])
+            if isinstance(data, dict)
+            else base64.b64decode(data),
+        )
+        for name, (content_type, data) in attachments.items()
+    }
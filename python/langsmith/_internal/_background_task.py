@@ -0,0 +1,88 @@
+"""The asyncio counterpart of the background tracing thread, for AsyncClient."""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import weakref
+from typing import TYPE_CHECKING, List
+
+from langsmith._internal._background_thread import TracingQueueItem
+
+if TYPE_CHECKING:
+    from langsmith.async_client import AsyncClient
+
+logger = logging.getLogger("langsmith.client")
+
+
+async def _tracing_task_drain_queue(
+    tracing_queue: asyncio.Queue, limit: int = 100
+) -> List[TracingQueueItem]:
+    next_batch: List[TracingQueueItem] = []
+    try:
+        # wait 250ms for the first item, then
+        # - drain the queue with a 50ms timeout
+        # - stop draining if we hit the limit
+        # as _tracing_thread_drain_queue does for the sync client
+        next_batch.append(await asyncio.wait_for(tracing_queue.get(), 0.25))
+        while not limit or len(next_batch) < limit:
+            next_batch.append(await asyncio.wait_for(tracing_queue.get(), 0.05))
+    except asyncio.TimeoutError:
+        pass
+    return next_batch
+
+
+async def _tracing_task_handle_batch(
+    client: AsyncClient,
+    tracing_queue: asyncio.Queue,
+    batch: List[TracingQueueItem],
+    use_multipart: bool,
+) -> None:
+    create = [it.item for it in batch if it.action == "create"]
+    update = [it.item for it in batch if it.action == "update"]
+    breaker = client._circuit_breaker
+    if breaker is not None and not breaker.allow_request():
+        # the API is down, so drop the batch rather than retry it
+        breaker.record_short_circuited(len(batch))
+        for _ in batch:
+            tracing_queue.task_done()
+        return
+    try:
+        if use_multipart:
+            await client._amultipart_ingest(create, update)
+        else:
+            await client._abatch_ingest_runs(create, update)
+    except Exception:
+        logger.error("Error in tracing queue", exc_info=True)
+        # exceptions are logged elsewhere, but we need to make sure the
+        # background task continues to run
+    finally:
+        for _ in batch:
+            tracing_queue.task_done()
+
+
+async def tracing_control_task_func(
+    client_ref: weakref.ref[AsyncClient], tracing_queue: asyncio.Queue
+) -> None:
+    """Send batches of the runs on the queue until the client is closed."""
+    client = client_ref()
+    if client is None:
+        return
+    batch_ingest_config = await client._aget_batch_ingest_config()
+    size_limit: int = batch_ingest_config["size_limit"]
+    use_multipart = batch_ingest_config.get("use_multipart_endpoint", False)
+    # don't keep the client alive while waiting for runs
+    del client
+
+    while True:
+        next_batch = await _tracing_task_drain_queue(tracing_queue, limit=size_limit)
+        client = client_ref()
+        if client is None:
+            for _ in next_batch:
+                tracing_queue.task_done()
+            return
+        if next_batch:
+            await _tracing_task_handle_batch(
+                client, tracing_queue, next_batch, use_multipart
+            )
+        del client
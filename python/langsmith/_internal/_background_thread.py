@@ -1,15 +1,21 @@
 from __future__ import annotations
 
+import functools
 import logging
+import math
 import sys
 import threading
+import time
 import weakref
 from dataclasses import dataclass, field
 from queue import Empty, Queue
 from typing import (
     TYPE_CHECKING,
     Any,
+    Dict,
+    FrozenSet,
     List,
+    Optional,
 )
 
 from langsmith import schemas as ls_schemas
@@ -18,8 +24,10 @@ from langsmith._internal._constants import (
     _AUTO_SCALE_UP_NTHREADS_LIMIT,
     _AUTO_SCALE_UP_QSIZE_TRIGGER,
 )
+from langsmith._internal._delivery import BatchDelivery
 
 if TYPE_CHECKING:
+    from langsmith._internal._tracing_queue import BoundedTracingQueue
     from langsmith.client import Client
 
 logger = logging.getLogger("langsmith.client")
@@ -33,11 +41,20 @@ class TracingQueueItem:
         priority (str): The priority of the item.
         action (str): The action associated with the item.
         item (Any): The item itself.
+        spool_id (Optional[int]): The id of the item in the client's on-disk
+            spool, if it was persisted.
+        size_bytes (int): The (estimated) serialized size of the item, if
+            the tracing queue has a byte budget.
+        sent_to (FrozenSet[str]): The targets a spooled item was already
+            delivered to, and isn't sent to again when it is retried.
     """
 
     priority: str
     action: str
     item: Any = field(compare=False)
+    spool_id: Optional[int] = field(default=None, compare=False)
+    size_bytes: int = field(default=0, compare=False)
+    sent_to: FrozenSet[str] = field(default=frozenset(), compare=False)
 
 
 def _tracing_thread_drain_queue(
@@ -67,28 +84,99 @@ def _tracing_thread_handle_batch(
     batch: List[TracingQueueItem],
     use_multipart: bool,
 ) -> None:
-    create = [it.item for it in batch if it.action == "create"]
-    update = [it.item for it in batch if it.action == "update"]
-    feedback = [it.item for it in batch if it.action == "feedback"]
-    try:
-        if use_multipart:
-            client.multipart_ingest(
-                create=create, update=update, feedback=feedback, pre_sampled=True
+    client._tracing_stats.increment("batches")
+    client._tracing_stats.observe("batch_size", len(batch))
+    # Spooled items that are retried skip the targets they were delivered to
+    by_sent_to: Dict[FrozenSet[str], List[TracingQueueItem]] = {}
+    for item in batch:
+        by_sent_to.setdefault(item.sent_to, []).append(item)
+    for sent_to, items in by_sent_to.items():
+        # Sending the same items would fail the same way
+        delivery = BatchDelivery("rejected")
+        try:
+            delivery = _tracing_thread_send(client, items, use_multipart, sent_to)
+        except Exception:
+            logger.error("Error in tracing queue", exc_info=True)
+            # exceptions are logged elsewhere, but we need to make sure the
+            # background thread continues to run
+            pass
+        finally:
+            # Settled once every URL is done with the items, which may still
+            # be in progress on their lanes, so the next batch isn't held up
+            # by the slowest URL
+            delivery.add_done_callback(
+                functools.partial(_tracing_thread_settle, client, tracing_queue, items)
             )
+
+
+def _tracing_thread_send(
+    client: Client,
+    items: List[TracingQueueItem],
+    use_multipart: bool,
+    sent_to: FrozenSet[str],
+) -> BatchDelivery:
+    create = [it.item for it in items if it.action == "create"]
+    update = [it.item for it in items if it.action == "update"]
+    feedback = [it.item for it in items if it.action == "feedback"]
+    if client._exporters is not None:
+        return client._export_batch(create, update, feedback, skip=sent_to)
+    if use_multipart:
+        return client._multipart_ingest(
+            create=create,
+            update=update,
+            feedback=feedback,
+            pre_sampled=True,
+            skip=sent_to,
+        )
+    return client._batch_ingest_runs(
+        create=create, update=update, pre_sampled=True, skip=sent_to
+    )
+
+
+def _tracing_thread_settle(
+    client: Client,
+    tracing_queue: Queue,
+    items: List[TracingQueueItem],
+    delivery: BatchDelivery,
+) -> None:
+    targets = [t for t in client._delivery_targets() if t not in items[0].sent_to]
+    retry = delivery.to_retry(targets)
+    client._tracing_stats.increment(
+        (
+            "runs_sent"
+            if all(delivery.outcome(t) == "delivered" for t in targets)
+            else "runs_failed"
+        ),
+        len(items),
+    )
+    if client._tracing_spool is not None:
+        # Targets that rejected the items won't accept them later either
+        if not retry:
+            client._tracing_spool.ack(items)
         else:
-            client.batch_ingest_runs(create=create, update=update, pre_sampled=True)
-    except Exception:
-        logger.error("Error in tracing queue", exc_info=True)
-        # exceptions are logged elsewhere, but we need to make sure the
-        # background thread continues to run
-        pass
-    finally:
-        for _ in batch:
-            tracing_queue.task_done()
+            if settled := [t for t in targets if t not in retry]:
+                client._tracing_spool.ack(items, settled)
+            client._tracing_spool.nack(items)
+    for _ in items:
+        tracing_queue.task_done()
+
+
+def _tracing_thread_requeue_spooled(
+    client: Client, tracing_queue: BoundedTracingQueue, size_limit: int
+) -> None:
+    if client._tracing_spool is None:
+        return
+    # Retries bypass the queue's limits, so only read up to a batch of them
+    # back from disk at a time
+    room = size_limit - tracing_queue.qsize()
+    if room <= 0:
+        return
+    for item in client._tracing_spool.due_retries(limit=room):
+        tracing_queue.requeue(item)
 
 
 def _ensure_ingest_config(
-    info: ls_schemas.LangSmithInfo,
+    info: Optional[ls_schemas.LangSmithInfo],
 ) -> ls_schemas.BatchIngestConfig:
     default_config = ls_schemas.BatchIngestConfig(
         use_multipart_endpoint=False,
@@ -108,13 +196,42 @@ def _ensure_ingest_config(
         return default_config
 
 
+def _get_ingest_config(client: Client) -> ls_schemas.BatchIngestConfig:
+    if not client._exports_to_api():
+        # Batches never reach the API, so don't ask it for its limits
+        return _ensure_ingest_config(None)
+    return _ensure_ingest_config(client.info)
+
+
+def _shutdown_exporters(client: Client) -> None:
+    for exporter in client._exporters or ():
+        try:
+            exporter.shutdown()
+        except Exception as e:
+            logger.warning(
+                f"Failed to shut down {type(exporter).__name__}: {e!r}", exc_info=True
+            )
+
+
+def _shutdown_time_left(client: Client) -> Optional[float]:
+    deadline = client._tracing_shutdown_deadline
+    if deadline is None or deadline == math.inf:
+        return None
+    return max(0.0, deadline - time.monotonic())
+
+
+def _before_shutdown_deadline(client: Client) -> bool:
+    time_left = _shutdown_time_left(client)
+    return time_left is None or time_left > 0
+
+
 def tracing_control_thread_func(client_ref: weakref.ref[Client]) -> None:
     client = client_ref()
     if client is None:
         return
     tracing_queue = client.tracing_queue
     assert tracing_queue is not None
-    batch_ingest_config = _ensure_ingest_config(client.info)
+    batch_ingest_config = _get_ingest_config(client)
     size_limit: int = batch_ingest_config["size_limit"]
     scale_up_nthreads_limit: int = batch_ingest_config["scale_up_nthreads_limit"]
     scale_up_qsize_trigger: int = batch_ingest_config["scale_up_qsize_trigger"]
@@ -124,35 +241,71 @@ def tracing_control_thread_func(client_ref: weakref.ref[Client]) -> None:
     # 1 for this func, 1 for getrefcount, 1 for _get_data_type_cached
     num_known_refs = 3
 
+    # replay items a previous process persisted but never delivered, which
+    # are read back from disk as they're retried
+    if client._tracing_spool is not None:
+        client._tracing_spool.recover()
+
     # loop until
     while (
         # the main thread dies
         threading.main_thread().is_alive()
         # or we're the only remaining reference to the client
         and sys.getrefcount(client) > num_known_refs + len(sub_threads)
+        # or the client is shutting down
+        and client._tracing_shutdown_deadline is None
     ):
         for thread in sub_threads:
             if not thread.is_alive():
                 sub_threads.remove(thread)
+        client._tracing_stats.sub_threads = len(sub_threads)
         if (
             len(sub_threads) < scale_up_nthreads_limit
             and tracing_queue.qsize() > scale_up_qsize_trigger
+            # more threads won't help while the API is down
+            and (
+                not client._circuit_breakers
+                or any(
+                    breaker.state == "closed"
+                    for breaker in client._circuit_breakers.values()
+                )
+            )
         ):
             new_thread = threading.Thread(
                 target=_tracing_sub_thread_func,
                 args=(weakref.ref(client), use_multipart),
+                daemon=True,
             )
             sub_threads.append(new_thread)
             new_thread.start()
+            client._tracing_stats.increment("sub_threads_started")
+        _tracing_thread_requeue_spooled(client, tracing_queue, size_limit)
+        if client._tail_sampler is not None:
+            client._put_tracing_items(
+                client._tail_sampler.release_expired(), requeue=True
+            )
         if next_batch := _tracing_thread_drain_queue(tracing_queue, limit=size_limit):
             _tracing_thread_handle_batch(
                 client, tracing_queue, next_batch, use_multipart
             )
-    # drain the queue on exit
-    while next_batch := _tracing_thread_drain_queue(
-        tracing_queue, limit=size_limit, block=False
+    # decide on traces that never finished, then drain the queue on exit
+    if client._tail_sampler is not None:
+        client._put_tracing_items(client._tail_sampler.release_all(), requeue=True)
+    while _before_shutdown_deadline(client) and (
+        next_batch := _tracing_thread_drain_queue(
+            tracing_queue, limit=size_limit, block=False
+        )
     ):
         _tracing_thread_handle_batch(client, tracing_queue, next_batch, use_multipart)
+    # sub-threads are daemons, so let them finish the batches they're sending
+    for thread in sub_threads:
+        thread.join(_shutdown_time_left(client))
+    for lane in client._send_lanes.values():
+        lane.wait(_shutdown_time_left(client))
+    _shutdown_exporters(client)
+    # anything still unacknowledged is replayed by the next process
+    if client._tracing_spool is not None:
+        client._tracing_spool.close()
 
 
 def _tracing_sub_thread_func(
@@ -163,14 +316,12 @@ def _tracing_sub_thread_func(
     if client is None:
         return
     try:
-        if not client.info:
-            return
+        batch_ingest_config = _get_ingest_config(client)
     except BaseException as e:
         logger.debug("Error in tracing control thread: %s", e)
         return
     tracing_queue = client.tracing_queue
     assert tracing_queue is not None
-    batch_ingest_config = _ensure_ingest_config(client.info)
     size_limit = batch_ingest_config.get("size_limit", 100)
     seen_successive_empty_queues = 0
 
@@ -181,6 +332,8 @@ def _tracing_sub_thread_func(
         # or we've seen the queue empty 4 times in a row
         and seen_successive_empty_queues
         <= batch_ingest_config["scale_down_nempty_trigger"]
+        # or the client is shutting down
+        and client._tracing_shutdown_deadline is None
     ):
         if next_batch := _tracing_thread_drain_queue(tracing_queue, limit=size_limit):
             seen_successive_empty_queues = 0
@@ -191,7 +344,9 @@ def _tracing_sub_thread_func(
             seen_successive_empty_queues += 1
 
     # drain the queue on exit
-    while next_batch := _tracing_thread_drain_queue(
-        tracing_queue, limit=size_limit, block=False
+    while _before_shutdown_deadline(client) and (
+        next_batch := _tracing_thread_drain_queue(
+            tracing_queue, limit=size_limit, block=False
+        )
     ):
         _tracing_thread_handle_batch(client, tracing_queue, next_batch, use_multipart)
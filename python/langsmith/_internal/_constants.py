@@ -3,3 +3,9 @@ _AUTO_SCALE_UP_QSIZE_TRIGGER = 200
 _AUTO_SCALE_UP_NTHREADS_LIMIT = 32
 _AUTO_SCALE_DOWN_NEMPTY_TRIGGER = 4
 _BLOCKSIZE_BYTES = 1024 * 1024  # 1MB
+_SPOOL_MAX_BYTES = 1024 * 1024 * 1024  # 1GB
+_SPOOL_SEGMENT_BYTES = 16 * 1024 * 1024  # 16MB
+_SPOOL_RETRY_INTERVAL_S = 5.0
+_SPOOL_MAX_RETRY_INTERVAL_S = 300.0
+_SPOOL_MAX_AGE_S = 24 * 60 * 60.0  # 1 day
+_SEND_LANE_MAX_PENDING_BATCHES = 64
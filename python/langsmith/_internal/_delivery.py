@@ -0,0 +1,208 @@
+"""Where a batch of tracing items was delivered."""
+
+from __future__ import annotations
+
+import concurrent.futures as cf
+import functools
+import logging
+import os
+import threading
+import weakref
+from typing import Any, Callable, Dict, Iterable, List, Literal, Optional
+
+import httpx
+import requests
+
+from langsmith import utils as ls_utils
+from langsmith._internal._constants import (
+    _AUTO_SCALE_UP_NTHREADS_LIMIT,
+    _SEND_LANE_MAX_PENDING_BATCHES,
+)
+
+logger = logging.getLogger("langsmith.client")
+
+# "retry" if the target may accept the batch later, and "rejected" if it won't
+Outcome = Literal["delivered", "rejected", "retry"]
+_PRECEDENCE = {"delivered": 0, "rejected": 1, "retry": 2}
+
+# Errors without a response that suggest the target is unavailable
+_RETRYABLE_ERRORS = (
+    ls_utils.LangSmithAPIError,
+    ls_utils.LangSmithConnectionError,
+    ls_utils.LangSmithRateLimitError,
+    ls_utils.LangSmithRequestTimeout,
+    requests.ConnectionError,
+    requests.Timeout,
+    httpx.TransportError,
+)
+# Statuses of responses that suggest the target is unavailable, rather than
+# that a request was bad
+_RETRYABLE_STATUSES = frozenset({408, 429})
+
+
+def _response_status(error: BaseException) -> Optional[int]:
+    """Get the status of the response that caused an error, if there was one."""
+    seen = set()
+    current: Optional[BaseException] = error
+    while current is not None and id(current) not in seen:
+        seen.add(id(current))
+        status = getattr(getattr(current, "response", None), "status_code", None)
+        if isinstance(status, int):
+            return status
+        current = current.__cause__ or current.__context__
+    return None
+
+
+def is_retryable_error(error: BaseException) -> bool:
+    """Whether a failed request suggests the API is unavailable.
+
+    Server errors, timeouts, rate limits and connection errors do. Other
+    client errors, like a rejected payload, only say the request was bad, and
+    sending it again won't help.
+    """
+    status = _response_status(error)
+    if status is not None:
+        return status >= 500 or status in _RETRYABLE_STATUSES
+    return isinstance(error, _RETRYABLE_ERRORS)
+
+
+def outcome_of(error: Optional[BaseException]) -> Outcome:
+    """Get the outcome of a request that raised ``error``, if anything."""
+    if error is None:
+        return "delivered"
+    return "retry" if is_retryable_error(error) else "rejected"
+
+
+def combine_outcomes(*outcomes: Outcome) -> Outcome:
+    """Get the outcome of a batch sent to a target in several requests."""
+    return max(outcomes, key=_PRECEDENCE.__getitem__, default="delivered")
+
+
+class BatchDelivery:
+    """The outcome of sending a batch to each of its targets.
+
+    Targets are the exporters of a client, or else its write API URLs. Those
+    the batch wasn't sent to, e.g. because every run in it was sampled out,
+    have the ``default`` outcome. Sends still running on a ``SendLane`` are
+    tracked until they finish, so call ``wait`` or ``add_done_callback``
+    before reading the outcomes.
+    """
+
+    def __init__(self, default: Outcome = "delivered") -> None:
+        """Initialize a delivery with no outcomes recorded yet."""
+        self.default = default
+        self.outcomes: Dict[str, Outcome] = {}
+        self._lock = threading.Lock()
+        self._pending = 0
+        self._done = threading.Event()
+        self._done.set()
+        self._callbacks: List[Callable[[BatchDelivery], Any]] = []
+
+    def record(self, target: str, outcome: Outcome) -> None:
+        """Record the outcome of sending (part of) the batch to a target."""
+        previous = self.outcomes.get(target, outcome)
+        self.outcomes[target] = combine_outcomes(previous, outcome)
+
+    def track(self, target: str, future: cf.Future) -> None:
+        """Record the outcome of a send to a target once it finishes."""
+        with self._lock:
+            self._pending += 1
+            self._done.clear()
+        future.add_done_callback(functools.partial(self._finish, target))
+
+    def add_done_callback(self, fn: Callable[[BatchDelivery], Any]) -> None:
+        """Call ``fn`` with the delivery once every send finished."""
+        with self._lock:
+            if self._pending:
+                self._callbacks.append(fn)
+                return
+        fn(self)
+
+    def wait(self, timeout: Optional[float] = None) -> bool:
+        """Wait for every send to finish, returning whether they did."""
+        return self._done.wait(timeout)
+
+    def _finish(self, target: str, future: cf.Future) -> None:
+        error = future.exception()
+        outcome = future.result() if error is None else outcome_of(error)
+        with self._lock:
+            self.record(target, outcome)
+            self._pending -= 1
+            if self._pending:
+                return
+            callbacks, self._callbacks = self._callbacks, []
+            self._done.set()
+        for fn in callbacks:
+            try:
+                fn(self)
+            except Exception:
+                logger.error("Error settling a delivered batch", exc_info=True)
+
+    def outcome(self, target: str) -> Outcome:
+        """Get the outcome of sending the batch to a target."""
+        return self.outcomes.get(target, self.default)
+
+    def to_retry(self, targets: Iterable[str]) -> List[str]:
+        """Get the targets the batch should be sent to again."""
+        return [target for target in targets if self.outcome(target) == "retry"]
+
+
+class SendLane:
+    """Sends batches to one write API URL on threads of its own.
+
+    Each URL gets a lane so that one that is slow or down doesn't hold up
+    the others. Once ``max_pending`` batches are waiting on it, new ones are
+    turned away, rather than piling up in memory.
+    """
+
+    def __init__(self, max_pending: int = _SEND_LANE_MAX_PENDING_BATCHES) -> None:
+        """Initialize the lane. Its threads are started as they're needed."""
+        self.max_pending = max_pending
+        self._executor = cf.ThreadPoolExecutor(
+            max_workers=_AUTO_SCALE_UP_NTHREADS_LIMIT,
+            thread_name_prefix="langsmith-send",
+        )
+        self._pending = 0
+        self._idle = threading.Condition()
+        _SEND_LANES.add(self)
+
+    def submit(self, fn: Callable[[], Outcome]) -> Optional[cf.Future]:
+        """Send a batch, or return None if too many are waiting already."""
+        with self._idle:
+            if self._pending >= self.max_pending:
+                return None
+            self._pending += 1
+        future = self._executor.submit(fn)
+        future.add_done_callback(self._finish)
+        return future
+
+    def wait(self, timeout: Optional[float] = None) -> bool:
+        """Wait for the batches on the lane to be sent, returning whether they were."""
+        with self._idle:
+            return self._idle.wait_for(lambda: not self._pending, timeout)
+
+    def _finish(self, future: cf.Future) -> None:
+        with self._idle:
+            self._pending -= 1
+            self._idle.notify_all()
+
+    def _reset_after_fork(self) -> None:
+        # a forked child inherits neither the threads nor their batches
+        self._executor = cf.ThreadPoolExecutor(
+            max_workers=_AUTO_SCALE_UP_NTHREADS_LIMIT,
+            thread_name_prefix="langsmith-send",
+        )
+        self._pending = 0
+        self._idle = threading.Condition()
+
+
+_SEND_LANES: weakref.WeakSet[SendLane] = weakref.WeakSet()
+
+
+def _reset_send_lanes_after_fork() -> None:
+    for lane in list(_SEND_LANES):
+        lane._reset_after_fork()
+
+
+if hasattr(os, "register_at_fork"):
+    os.register_at_fork(after_in_child=_reset_send_lanes_after_fork)
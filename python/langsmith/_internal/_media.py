@@ -0,0 +1,97 @@
+"""Move base64 media inlined in run inputs and outputs into attachments."""
+
+from __future__ import annotations
+
+import base64
+import binascii
+import re
+from typing import Any, Dict, Optional, Tuple
+
+# data:[<media type>][;<parameter>]*;base64,<data>
+_DATA_URL = re.compile(
+    r"^data:(?P<type>[\w.+-]+/[\w.+-]+)(?:;[\w.+-]+=[\w.+-]+)*;base64,(?P<data>.*)$",
+    re.DOTALL,
+)
+
+
+def _decode(data: str) -> Optional[bytes]:
+    try:
+        return base64.b64decode(data, validate=True)
+    except (binascii.Error, ValueError):
+        return None
+
+
+class _Extractor:
+    def __init__(self, field: str) -> None:
+        self.field = field
+        self.attachments: Dict[str, Tuple[str, bytes]] = {}
+
+    def attach(self, content_type: str, data: str) -> Optional[str]:
+        """Add an attachment, returning the reference to leave in its place."""
+        decoded = _decode(data)
+        if decoded is None:
+            return None
+        name = f"{self.field}-media-{len(self.attachments)}"
+        self.attachments[name] = (content_type, decoded)
+        return f"attachment://{name}"
+
+    def visit(self, value: Any) -> Any:
+        """Return the value with its media replaced, copying what changes."""
+        if isinstance(value, str):
+            if value.startswith("data:") and (match := _DATA_URL.match(value)):
+                return self.attach(match["type"], match["data"]) or value
+            return value
+        if isinstance(value, dict):
+            replaced = self._visit_media_block(value)
+            if replaced is not None:
+                return replaced
+            changed = {key: self.visit(item) for key, item in value.items()}
+            return value if _same(changed.values(), value.values()) else changed
+        if isinstance(value, (list, tuple)):
+            items = [self.visit(item) for item in value]
+            return value if _same(items, value) else type(value)(items)
+        return value
+
+    def _visit_media_block(self, block: dict) -> Optional[dict]:
+        # Anthropic: {"type": "base64", "media_type": "image/png", "data": ...}
+        if (
+            block.get("type") == "base64"
+            and isinstance(block.get("media_type"), str)
+            and isinstance(block.get("data"), str)
+        ):
+            if reference := self.attach(block["media_type"], block["data"]):
+                return {**block, "data": reference}
+        # OpenAI input audio: {"data": ..., "format": "wav"}
+        if (
+            set(block) == {"data", "format"}
+            and isinstance(block["data"], str)
+            and isinstance(block["format"], str)
+        ):
+            if reference := self.attach(f"audio/{block['format']}", block["data"]):
+                return {**block, "data": reference}
+        return None
+
+
+def _same(new: Any, old: Any) -> bool:
+    return all(a is b for a, b in zip(new, old))
+
+
+def extract_inline_media(
+    field: str, values: Any
+) -> Tuple[Any, Dict[str, Tuple[str, bytes]]]:
+    """Move the base64 media in a run's inputs or outputs into attachments.
+
+    Finds ``data:`` URLs, such as the image URLs of OpenAI messages, and the
+    base64 sources of Anthropic content blocks and OpenAI input audio. Each is
+    replaced by an ``attachment://{field}-media-{n}`` reference to the
+    attachment its decoded contents are moved to.
+
+    Args:
+        field: "inputs" or "outputs".
+        values: The run's inputs or outputs, which are left as they are.
+
+    Returns:
+        The values to send, and the attachments to send with them.
+    """
+    extractor = _Extractor(field)
+    return extractor.visit(values), extractor.attachments
@@ -0,0 +1,43 @@
+"""The optional compiled core, used when langsmith-native is installed.
+
+Each function returns the same bytes as the pure-Python path it replaces, or
+None when the core isn't installed or can't handle the input, in which case
+the caller falls back to the pure-Python path. Set
+LANGSMITH_DISABLE_NATIVE_CORE=true to always use the pure-Python path.
+"""
+
+from __future__ import annotations
+
+import os
+from typing import Any, Callable, List, Optional
+
+try:
+    import langsmith_native as _core  # type: ignore[import-not-found]
+except ImportError:
+    _core = None  # type: ignore[assignment]
+
+if os.environ.get("LANGSMITH_DISABLE_NATIVE_CORE") == "true":
+    _core = None  # type: ignore[assignment]
+
+AVAILABLE = _core is not None
+
+
+def dumps(obj: Any, default: Callable[[Any], Any]) -> Optional[bytes]:
+    """Serialize an object as orjson does in _serde.dumps_json."""
+    if _core is None:
+        return None
+    return _core.dumps(obj, default)
+
+
+def elide_surrogates(data: bytes) -> Optional[bytes]:
+    """Remove the surrogate escapes from JSON."""
+    if _core is None:
+        return None
+    return _core.elide_surrogates(data)
+
+
+def encode_multipart(parts: List[Any], boundary: str) -> Optional[bytes]:
+    """Encode the body a MultipartEncoder streams, if every part body is bytes."""
+    if _core is None:
+        return None
+    return _core.encode_multipart(parts, boundary)
@@ -2,6 +2,7 @@ from __future__ import annotations
 
 import base64
 import collections
+import dataclasses
 import datetime
 import decimal
 import ipaddress
@@ -13,11 +14,14 @@ import uuid
 from typing import (
     Any,
     Callable,
+    Dict,
     Optional,
 )
 
 import orjson
 
+from langsmith._internal import _native
+
 try:
     from zoneinfo import ZoneInfo  # type: ignore[import-not-found]
 except ImportError:
@@ -28,12 +32,88 @@ except ImportError:
 
 logger = logging.getLogger(__name__)
 
+_ORJSON_OPTIONS = (
+    orjson.OPT_SERIALIZE_NUMPY
+    | orjson.OPT_SERIALIZE_DATACLASS
+    | orjson.OPT_SERIALIZE_UUID
+    | orjson.OPT_NON_STR_KEYS
+)
+
+
+@dataclasses.dataclass(frozen=True)
+class _Serializer:
+    encoder: Callable[[Any], Any]
+    max_bytes: Optional[int]
+
+
+# Registered with langsmith.serializers.register_serializer
+_serializers: Dict[type, _Serializer] = {}
+# Options making orjson call default for the registered types it would
+# otherwise serialize itself
+_orjson_options = _ORJSON_OPTIONS
+_NOT_REGISTERED = object()
+
+
+def _is_numpy_type(cls: type) -> bool:
+    return cls.__module__.split(".")[0] == "numpy"
+
+
+def _set_serializer(cls: type, serializer: Optional[_Serializer]) -> None:
+    global _orjson_options
+    if serializer is None:
+        _serializers.pop(cls, None)
+    else:
+        _serializers[cls] = serializer
+    options = _ORJSON_OPTIONS
+    for registered in _serializers:
+        if dataclasses.is_dataclass(registered):
+            options |= orjson.OPT_PASSTHROUGH_DATACLASS
+        if issubclass(registered, (datetime.date, datetime.time)):
+            options |= orjson.OPT_PASSTHROUGH_DATETIME
+        if issubclass(registered, (str, int, dict, list)):
+            options |= orjson.OPT_PASSTHROUGH_SUBCLASS
+        if _is_numpy_type(registered):
+            options &= ~orjson.OPT_SERIALIZE_NUMPY
+    _orjson_options = options
+
+
+def truncate_text(text: bytes, max_bytes: int) -> str:
+    """Cut UTF-8 text down to max_bytes, ending it with a marker."""
+    marker = f"... [truncated {len(text)} bytes]"
+    kept = text[: max(0, max_bytes - len(marker))]
+    # Cutting may split a multi-byte character, so drop its remains
+    return kept.decode("utf-8", errors="ignore") + marker
+
+
+def _encode_registered(obj: Any) -> Any:
+    if not _serializers:
+        return _NOT_REGISTERED
+    for cls in type(obj).__mro__:
+        serializer = _serializers.get(cls)
+        if serializer is not None:
+            break
+    else:
+        return _NOT_REGISTERED
+    try:
+        encoded = serializer.encoder(obj)
+    except Exception as e:
+        logger.debug(f"Registered serializer failed for {type(obj)}: {repr(e)}")
+        return _NOT_REGISTERED
+    if serializer.max_bytes is None:
+        return encoded
+    serialized = dumps_json(encoded)
+    if len(serialized) <= serializer.max_bytes:
+        return encoded
+    return truncate_text(serialized, serializer.max_bytes)
+
 
 def _simple_default(obj):
     try:
+        if (encoded := _encode_registered(obj)) is not _NOT_REGISTERED:
+            return encoded
         # Only need to handle types that orjson doesn't serialize by default
         # https://github.com/ijl/orjson#serialize
-        if isinstance(obj, datetime.datetime):
+        if isinstance(obj, (datetime.datetime, datetime.date, datetime.time)):
             return obj.isoformat()
         if isinstance(obj, uuid.UUID):
             return str(obj)
@@ -73,6 +153,17 @@ def _simple_default(obj):
             return obj.pattern
         elif isinstance(obj, (bytes, bytearray)):
             return base64.b64encode(obj).decode()
+        # The rest are only passed through by orjson to registered serializers
+        elif dataclasses.is_dataclass(obj) and not isinstance(obj, type):
+            return {f.name: getattr(obj, f.name) for f in dataclasses.fields(obj)}
+        elif _is_numpy_type(type(obj)) and hasattr(obj, "tolist"):
+            return obj.tolist()
+        elif isinstance(obj, dict):
+            return dict(obj)
+        elif isinstance(obj, list):
+            return list(obj)
+        elif isinstance(obj, int):
+            return int(obj)
         return str(obj)
     except BaseException as e:
         logger.debug(f"Failed to serialize {type(obj)} to JSON: {e}")
@@ -81,6 +172,8 @@ def _simple_default(obj):
 
 def _serialize_json(obj: Any) -> Any:
     try:
+        if (encoded := _encode_registered(obj)) is not _NOT_REGISTERED:
+            return encoded
         if isinstance(obj, (set, tuple)):
             if hasattr(obj, "_asdict") and callable(obj._asdict):
                 # NamedTuple
@@ -115,6 +208,8 @@ def _serialize_json(obj: Any) -> Any:
 
 
 def _elide_surrogates(s: bytes) -> bytes:
+    if (result := _native.elide_surrogates(s)) is not None:
+        return result
     pattern = re.compile(rb"\\ud[89a-f][0-9a-f]{2}", re.IGNORECASE)
     result = pattern.sub(b"", s)
     return result
@@ -123,14 +218,14 @@ def _elide_surrogates(s: bytes) -> bytes:
 def _dumps_json_single(
     obj: Any, default: Optional[Callable[[Any], Any]] = None
 ) -> bytes:
+    # The compiled core serializes types registered serializers may override
+    if _orjson_options == _ORJSON_OPTIONS and (
+        (result := _native.dumps(obj, default or _simple_default)) is not None
+    ):
+        return result
     try:
         return orjson.dumps(
-            obj,
-            default=default or _simple_default,
-            option=orjson.OPT_SERIALIZE_NUMPY
-            | orjson.OPT_SERIALIZE_DATACLASS
-            | orjson.OPT_SERIALIZE_UUID
-            | orjson.OPT_NON_STR_KEYS,
+            obj, default=default or _simple_default, option=_orjson_options
         )
     except TypeError as e:
         # Usually caused by UTF surrogate characters
@@ -0,0 +1,460 @@
+"""Durable on-disk spool for the tracing queue."""
+
+from __future__ import annotations
+
+import collections
+import logging
+import os
+import sys
+import threading
+import time
+import uuid
+from dataclasses import dataclass, field
+from typing import IO, Any, Dict, Iterable, List, Optional, Set, Tuple
+
+import orjson
+
+from langsmith._internal._attachments import decode_attachments, encode_attachments
+from langsmith._internal._background_thread import TracingQueueItem
+from langsmith._internal._constants import (
+    _SPOOL_MAX_AGE_S,
+    _SPOOL_MAX_BYTES,
+    _SPOOL_MAX_RETRY_INTERVAL_S,
+    _SPOOL_RETRY_INTERVAL_S,
+    _SPOOL_SEGMENT_BYTES,
+)
+from langsmith._internal._serde import dumps_json
+
+logger = logging.getLogger("langsmith.client")
+
+_SEGMENT_SUFFIX = ".jsonl"
+# Nonces of the spools that are alive in this process. Segments are named
+# "{created_ns}-{pid}-{nonce}.jsonl" so that a restarted process that happens
+# to reuse a pid (e.g. pid 1 in a container) still replays its predecessor.
+_LIVE_NONCES: Set[str] = set()
+
+
+@dataclass
+class _Segment:
+    path: str
+    size: int = 0
+    pending: Set[int] = field(default_factory=set)
+
+
+@dataclass
+class _Record:
+    segment: _Segment
+    offset: int
+    length: int
+    # When the item was spooled, as a Unix timestamp
+    created: float
+    # Targets the item was delivered to, while others still need it
+    sent_to: Set[str] = field(default_factory=set)
+
+
+def _pid_alive(pid: int) -> bool:
+    if sys.platform == "win32":
+        # os.kill(pid, 0) sends CTRL_C_EVENT on Windows. Renaming a segment
+        # that is still open in another process fails there anyway.
+        return False
+    try:
+        os.kill(pid, 0)
+    except ProcessLookupError:
+        return False
+    except OSError:
+        # e.g. EPERM: the process exists but belongs to someone else
+        return True
+    return True
+
+
+def _parse_segment_name(name: str) -> Optional[Tuple[str, int, str]]:
+    if not name.endswith(_SEGMENT_SUFFIX):
+        return None
+    parts = name[: -len(_SEGMENT_SUFFIX)].split("-")
+    if len(parts) != 3 or not parts[1].isdigit():
+        return None
+    return parts[0], int(parts[1]), parts[2]
+
+
+def _encode_payload(payload: Any) -> Any:
+    if isinstance(payload, dict) and payload.get("attachments"):
+        # Files are written as references, streams are read
+        payload = {
+            **payload,
+            "attachments": encode_attachments(
+                payload["attachments"], inline_files=False
+            ),
+        }
+    return payload
+
+
+def _encode_item(item: TracingQueueItem) -> bytes:
+    record = {
+        "priority": item.priority,
+        "action": item.action,
+        "item": _encode_payload(item.item),
+        "time": time.time(),
+    }
+    return dumps_json(record) + b"\n"
+
+
+def _decode_item(
+    record: dict, spool_id: Optional[int], sent_to: Iterable[str] = ()
+) -> TracingQueueItem:
+    payload = record["item"]
+    if isinstance(payload, dict) and payload.get("attachments"):
+        payload["attachments"] = decode_attachments(payload["attachments"])
+    return TracingQueueItem(
+        record["priority"],
+        record["action"],
+        payload,
+        spool_id=spool_id,
+        sent_to=frozenset(sent_to),
+    )
+
+
+class TracingSpool:
+    """A bounded, append-only on-disk log of tracing queue items.
+
+    Every item is appended to the current segment file before it is put on the
+    in-memory tracing queue, and is only forgotten once the batch containing it
+    was accepted by every target: each exporter, or else each write API URL.
+    Items from batches that failed to send are re-read from disk and retried
+    with exponential backoff, only to the targets that didn't accept them yet,
+    and items left behind by a previous process (crash, deploy, outage) are
+    replayed on start.
+
+    Items that were rejected, or that are still undelivered ``max_age``
+    seconds after they were spooled, are dropped. Segment files are deleted
+    once every item written to them is acknowledged. When the directory
+    reaches ``max_bytes``, new items are still traced but are no longer
+    persisted.
+    """
+
+    def __init__(
+        self,
+        directory: str,
+        *,
+        max_bytes: int = _SPOOL_MAX_BYTES,
+        segment_bytes: int = _SPOOL_SEGMENT_BYTES,
+        retry_interval: float = _SPOOL_RETRY_INTERVAL_S,
+        max_age: float = _SPOOL_MAX_AGE_S,
+    ) -> None:
+        """Initialize the spool, creating the directory if needed."""
+        self.directory = os.path.abspath(directory)
+        os.makedirs(self.directory, exist_ok=True)
+        self.max_bytes = max_bytes
+        self.segment_bytes = segment_bytes
+        self.retry_interval = retry_interval
+        self.max_age = max_age
+        self._nonce = uuid.uuid4().hex[:12]
+        _LIVE_NONCES.add(self._nonce)
+        self._lock = threading.Lock()
+        self._segments: List[_Segment] = []
+        self._records: Dict[int, _Record] = {}
+        self._next_id = 0
+        self._active: Optional[_Segment] = None
+        self._active_file: Optional[IO[bytes]] = None
+        self._failed: collections.OrderedDict[int, None] = collections.OrderedDict()
+        self._next_retry = 0.0
+        self._backoff = retry_interval
+        self._warned_full = False
+
+    @property
+    def size_bytes(self) -> int:
+        """The number of bytes currently stored in the spool directory."""
+        return sum(seg.size for seg in self._segments)
+
+    @property
+    def pending(self) -> int:
+        """The number of items that have not been acknowledged yet."""
+        return len(self._records)
+
+    def append(self, item: TracingQueueItem) -> bool:
+        """Persist an item, returning whether it was written to disk."""
+        try:
+            line = _encode_item(item)
+        except Exception as e:
+            logger.warning(f"Failed to serialize tracing item for the spool: {e!r}")
+            return False
+        with self._lock:
+            if self.size_bytes + len(line) > self.max_bytes:
+                if not self._warned_full:
+                    self._warned_full = True
+                    logger.warning(
+                        f"Tracing spool at {self.directory} is full"
+                        f" ({self.max_bytes} bytes). New runs will not be"
+                        " persisted until pending runs are sent."
+                    )
+                return False
+            try:
+                segment, file = self._ensure_active(len(line))
+                file.write(line)
+                file.flush()
+            except OSError as e:
+                logger.warning(f"Failed to write to tracing spool: {e!r}")
+                return False
+            self._warned_full = False
+            item.size_bytes = len(line)
+            spool_id = self._next_id
+            self._next_id += 1
+            self._records[spool_id] = _Record(
+                segment, segment.size, len(line), time.time()
+            )
+            segment.size += len(line)
+            segment.pending.add(spool_id)
+        item.spool_id = spool_id
+        return True
+
+    def ack(
+        self, items: Iterable[TracingQueueItem], targets: Optional[List[str]] = None
+    ) -> None:
+        """Forget items that were delivered.
+
+        Args:
+            items: The items that were delivered.
+            targets: Only note that the items were delivered to these targets,
+                and keep them for the others.
+        """
+        self._ack(
+            [item.spool_id for item in items if item.spool_id is not None], targets
+        )
+
+    def _ack(self, spool_ids: List[int], targets: Optional[List[str]]) -> None:
+        acked: Dict[int, List[dict]] = collections.defaultdict(list)
+        segments: Dict[int, _Segment] = {}
+        with self._lock:
+            for spool_id in spool_ids:
+                if targets is not None:
+                    record = self._records.get(spool_id)
+                    if record is None:
+                        continue
+                    for target in targets:
+                        if target not in record.sent_to:
+                            record.sent_to.add(target)
+                            acked[id(record.segment)].append(
+                                {"ack": record.offset, "target": target}
+                            )
+                else:
+                    record = self._records.pop(spool_id, None)
+                    if record is None:
+                        continue
+                    self._failed.pop(spool_id, None)
+                    record.segment.pending.discard(spool_id)
+                    acked[id(record.segment)].append({"ack": record.offset})
+                segments[id(record.segment)] = record.segment
+            if not acked:
+                return
+            self._backoff = self.retry_interval
+            for key, acks in acked.items():
+                segment = segments[key]
+                if not segment.pending and segment is not self._active:
+                    self._remove_segment(segment)
+                    continue
+                lines = b"".join(orjson.dumps(ack) + b"\n" for ack in acks)
+                try:
+                    if segment is self._active and self._active_file is not None:
+                        self._active_file.write(lines)
+                        self._active_file.flush()
+                    else:
+                        with open(segment.path, "ab") as f:
+                            f.write(lines)
+                    segment.size += len(lines)
+                except OSError as e:
+                    # Worst case these items are sent again after a restart,
+                    # which the API deduplicates.
+                    logger.debug(f"Failed to acknowledge spooled items: {e!r}")
+
+    def nack(self, items: Iterable[TracingQueueItem]) -> None:
+        """Schedule items that failed to send for a later retry."""
+        with self._lock:
+            added = False
+            for item in items:
+                if item.spool_id is not None and item.spool_id in self._records:
+                    self._failed[item.spool_id] = None
+                    added = True
+            if added:
+                self._next_retry = time.monotonic() + self._backoff
+                self._backoff = min(self._backoff * 2, _SPOOL_MAX_RETRY_INTERVAL_S)
+
+    def due_retries(self, limit: Optional[int] = None) -> List[TracingQueueItem]:
+        """Re-read failed items from disk once their backoff has elapsed.
+
+        Items older than ``max_age`` are dropped instead.
+
+        Args:
+            limit: The maximum number of items to read. The others stay due
+                and are returned by the next calls.
+        """
+        expired: List[int] = []
+        with self._lock:
+            if not self._failed or time.monotonic() < self._next_retry:
+                return []
+            items: List[TracingQueueItem] = []
+            while self._failed and (limit is None or len(items) < limit):
+                spool_id, _ = self._failed.popitem(last=False)
+                record = self._records.get(spool_id)
+                if record is None:
+                    continue
+                if time.time() - record.created > self.max_age:
+                    expired.append(spool_id)
+                    continue
+                try:
+                    if record.segment is self._active and self._active_file:
+                        self._active_file.flush()
+                    with open(record.segment.path, "rb") as f:
+                        f.seek(record.offset)
+                        raw = f.read(record.length)
+                    items.append(
+                        _decode_item(orjson.loads(raw), spool_id, record.sent_to)
+                    )
+                except (OSError, KeyError, TypeError, ValueError) as e:
+                    logger.warning(f"Dropping unreadable spooled tracing item: {e!r}")
+                    self._records.pop(spool_id, None)
+                    record.segment.pending.discard(spool_id)
+        if expired:
+            self._warn_expired(len(expired))
+            self._ack(expired, None)
+        return items
+
+    def recover(self) -> int:
+        """Claim the segments left behind by previous processes.
+
+        The items from those segments that were never acknowledged are due
+        for a retry right away, and are read back by ``due_retries``.
+
+        Returns:
+            The number of items recovered.
+        """
+        recovered = 0
+        try:
+            names = sorted(os.listdir(self.directory))
+        except OSError as e:
+            logger.warning(f"Failed to list tracing spool directory: {e!r}")
+            return recovered
+        for name in names:
+            parsed = _parse_segment_name(name)
+            if parsed is None:
+                continue
+            created, pid, nonce = parsed
+            if pid == os.getpid():
+                if nonce in _LIVE_NONCES:
+                    continue
+            elif _pid_alive(pid):
+                continue
+            path = os.path.join(self.directory, name)
+            claimed = os.path.join(
+                self.directory,
+                f"{created}-{os.getpid()}-{self._nonce}{_SEGMENT_SUFFIX}",
+            )
+            try:
+                # Renaming is atomic, so only one process replays a segment.
+                os.rename(path, claimed)
+                with open(claimed, "rb") as f:
+                    content = f.read()
+            except OSError:
+                continue
+            recovered += self._load_segment(claimed, content)
+        if recovered:
+            logger.info(
+                f"Replaying {recovered} tracing items from spool at {self.directory}"
+            )
+        return recovered
+
+    def close(self) -> None:
+        """Close the active segment, removing it if nothing is pending."""
+        with self._lock:
+            self._close_active()
+        _LIVE_NONCES.discard(self._nonce)
+
+    def _load_segment(self, path: str, content: bytes) -> int:
+        segment = _Segment(path, size=len(content))
+        records: Dict[int, Tuple[int, dict]] = {}
+        acked: Set[int] = set()
+        sent_to: Dict[int, Set[str]] = collections.defaultdict(set)
+        offset = 0
+        for line in content.splitlines(keepends=True):
+            try:
+                obj = orjson.loads(line)
+                if "ack" in obj and "target" in obj:
+                    sent_to[obj["ack"]].add(obj["target"])
+                elif "ack" in obj:
+                    acked.add(obj["ack"])
+                else:
+                    records[offset] = (len(line), obj)
+            except orjson.JSONDecodeError:
+                # Partially written line from a crash.
+                pass
+            offset += len(line)
+        expired = 0
+        now = time.time()
+        with self._lock:
+            for record_offset, (length, obj) in records.items():
+                if record_offset in acked:
+                    continue
+                # Items spooled before their time was recorded expire from now
+                created = obj.get("time", now)
+                if now - created > self.max_age:
+                    expired += 1
+                    continue
+                spool_id = self._next_id
+                self._next_id += 1
+                self._records[spool_id] = _Record(
+                    segment, record_offset, length, created, set(sent_to[record_offset])
+                )
+                segment.pending.add(spool_id)
+                self._failed[spool_id] = None
+            recovered = len(segment.pending)
+            if recovered:
+                self._segments.append(segment)
+            else:
+                self._remove_segment(segment)
+        if expired:
+            self._warn_expired(expired)
+        return recovered
+
+    def _warn_expired(self, n_items: int) -> None:
+        logger.warning(
+            f"Dropping {n_items} spooled tracing items that weren't delivered"
+            f" within {self.max_age:g}s."
+        )
+
+    def _ensure_active(self, nbytes: int) -> Tuple[_Segment, IO[bytes]]:
+        active, file = self._active, self._active_file
+        if (
+            active is not None
+            and file is not None
+            and (active.size == 0 or active.size + nbytes <= self.segment_bytes)
+        ):
+            return active, file
+        self._close_active()
+        path = os.path.join(
+            self.directory,
+            f"{time.time_ns():020d}-{os.getpid()}-{self._nonce}{_SEGMENT_SUFFIX}",
+        )
+        file = open(path, "ab")
+        active = _Segment(path)
+        self._active, self._active_file = active, file
+        self._segments.append(active)
+        return active, file
+
+    def _close_active(self) -> None:
+        if self._active_file is not None:
+            try:
+                self._active_file.close()
+            except OSError:
+                pass
+        segment = self._active
+        self._active = None
+        self._active_file = None
+        if segment is not None and not segment.pending:
+            self._remove_segment(segment)
+
+    def _remove_segment(self, segment: _Segment) -> None:
+        try:
+            os.remove(segment.path)
+        except FileNotFoundError:
+            pass
+        except OSError as e:
+            logger.debug(f"Failed to remove tracing spool segment: {e!r}")
+        self._segments = [s for s in self._segments if s is not segment]
@@ -0,0 +1,189 @@
+"""Checks of hand-built runs against the runs of their trace sent before them."""
+
+from __future__ import annotations
+
+import collections
+import datetime
+import threading
+from typing import Any, List, Mapping, NamedTuple, Optional, Union
+
+# The segments of a dotted order end with a run id
+_ID_LENGTH = 36
+
+
+class _SentRun(NamedTuple):
+    name: Optional[str]
+    trace_id: str
+    dotted_order: str
+    start_time: Optional[datetime.datetime]
+
+
+def _as_datetime(
+    value: Union[datetime.datetime, str, None],
+) -> Optional[datetime.datetime]:
+    if isinstance(value, str):
+        try:
+            value = datetime.datetime.fromisoformat(value.replace("Z", "+00:00"))
+        except ValueError:
+            return None
+    if value is not None and value.tzinfo is None:
+        value = value.replace(tzinfo=datetime.timezone.utc)
+    return value
+
+
+def _as_str(value: Any) -> Optional[str]:
+    return str(value) if value is not None else None
+
+
+class TraceValidator:
+    """Find the mistakes in runs before they are sent.
+
+    Each run is checked against itself and the runs of its trace this client
+    sent before it:
+
+    - ``trace_id`` and ``dotted_order`` are set.
+    - The ``dotted_order`` is well formed, ends with the run's id, and starts
+      with the ``dotted_order`` of its parent.
+    - The ``trace_id`` is the id of the root of the ``dotted_order``, and the
+      ``parent_run_id`` the id of the parent in it.
+    - Parents are created before their children. This is only checked for
+      traces whose root run was created by this client, since the other
+      runs of a distributed trace are created by other services.
+    - Runs are created once, and created before they are updated, with the
+      same ``trace_id`` and ``dotted_order``.
+    - ``end_time`` isn't before ``start_time``.
+
+    The last ``max_runs`` runs created are remembered.
+    """
+
+    def __init__(self, max_runs: int = 10_000) -> None:
+        """Initialize the validator."""
+        self.max_runs = max_runs
+        self._runs: collections.OrderedDict[str, _SentRun] = (
+            collections.OrderedDict()
+        )
+        self._lock = threading.Lock()
+
+    def check(self, run: Mapping[str, Any], *, update: bool = False) -> List[str]:
+        """Check a run about to be created, or updated.
+
+        Returns:
+            The problems found, each prefixed with the path of the run in its
+            trace, as the names of its ancestors and its own name and id.
+        """
+        with self._lock:
+            run_id = _as_str(run.get("id"))
+            trace_id = _as_str(run.get("trace_id"))
+            dotted_order = run.get("dotted_order")
+            if update:
+                problems = self._check_update(run, run_id, trace_id, dotted_order)
+            else:
+                problems = self._check_create(run, run_id, trace_id, dotted_order)
+            path = self._path(run, run_id, dotted_order)
+            return [f"{path}: {problem}" for problem in problems]
+
+    def _check_create(
+        self,
+        run: Mapping[str, Any],
+        run_id: Optional[str],
+        trace_id: Optional[str],
+        dotted_order: Optional[str],
+    ) -> List[str]:
+        from langsmith.run_trees import validate_dotted_order
+
+        if not trace_id or not dotted_order:
+            return ["trace_id and dotted_order must be set"]
+        problems = [
+            f"dotted_order is invalid. {problem}"
+            for problem in validate_dotted_order(dotted_order)
+        ]
+        ids = [segment[-_ID_LENGTH:] for segment in dotted_order.split(".")]
+        if ids[-1] != run_id:
+            problems.append(f"dotted_order ends with {ids[-1]}, not the run id")
+        if ids[0] != trace_id:
+            problems.append(
+                f"trace_id {trace_id} isn't the root {ids[0]} of the dotted_order"
+            )
+        parent_id = _as_str(run.get("parent_run_id"))
+        expected_parent_id = ids[-2] if len(ids) > 1 else None
+        if parent_id != expected_parent_id:
+            problems.append(
+                f"parent_run_id {parent_id} isn't the parent {expected_parent_id}"
+                " in the dotted_order"
+            )
+        if parent_id is not None and (parent := self._runs.get(parent_id)):
+            if not dotted_order.startswith(parent.dotted_order + "."):
+                problems.append(
+                    "dotted_order doesn't start with the dotted_order of its parent"
+                    f" {parent.dotted_order}"
+                )
+            if parent.trace_id != trace_id:
+                problems.append(
+                    f"trace_id {trace_id} isn't the trace_id {parent.trace_id} of"
+                    " its parent"
+                )
+        elif parent_id is not None and trace_id in self._runs:
+            problems.append(f"parent run {parent_id} was never created")
+        if run_id in self._runs:
+            problems.append("run was already created")
+        start_time = _as_datetime(run.get("start_time"))
+        problems.extend(self._check_end_time(run, start_time))
+        if run_id is not None:
+            self._runs[run_id] = _SentRun(
+                run.get("name"), trace_id, dotted_order, start_time
+            )
+            self._runs.move_to_end(run_id)
+            while len(self._runs) > self.max_runs:
+                self._runs.popitem(last=False)
+        return problems
+
+    def _check_update(
+        self,
+        run: Mapping[str, Any],
+        run_id: Optional[str],
+        trace_id: Optional[str],
+        dotted_order: Optional[str],
+    ) -> List[str]:
+        created = self._runs.get(run_id) if run_id is not None else None
+        if created is None:
+            return ["run was updated before it was created"]
+        problems = []
+        if trace_id is not None and trace_id != created.trace_id:
+            problems.append(
+                f"trace_id {trace_id} isn't the trace_id {created.trace_id} it"
+                " was created with"
+            )
+        if dotted_order is not None and dotted_order != created.dotted_order:
+            problems.append(
+                f"dotted_order {dotted_order} isn't the dotted_order"
+                f" {created.dotted_order} it was created with"
+            )
+        start_time = _as_datetime(run.get("start_time")) or created.start_time
+        problems.extend(self._check_end_time(run, start_time))
+        return problems
+
+    def _check_end_time(
+        self, run: Mapping[str, Any], start_time: Optional[datetime.datetime]
+    ) -> List[str]:
+        end_time = _as_datetime(run.get("end_time"))
+        if start_time is not None and end_time is not None and end_time < start_time:
+            return [
+                f"end_time {end_time.isoformat()} is before start_time"
+                f" {start_time.isoformat()}"
+            ]
+        return []
+
+    def _path(
+        self,
+        run: Mapping[str, Any],
+        run_id: Optional[str],
+        dotted_order: Optional[str],
+    ) -> str:
+        names = []
+        if dotted_order:
+            for segment in dotted_order.split(".")[:-1]:
+                ancestor_id = segment[-_ID_LENGTH:]
+                ancestor = self._runs.get(ancestor_id)
+                names.append((ancestor and ancestor.name) or ancestor_id)
+        names.append(f"{run.get('name') or 'run'} ({run_id})")
+        return " > ".join(names)
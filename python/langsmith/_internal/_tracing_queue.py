@@ -0,0 +1,236 @@
+"""A tracing queue with a memory budget."""
+
+from __future__ import annotations
+
+import heapq
+import logging
+import os
+import tempfile
+from queue import PriorityQueue
+from typing import IO, Any, Literal, Mapping, Optional
+
+import orjson
+
+from langsmith._internal._background_thread import TracingQueueItem
+from langsmith._internal._serde import dumps_json
+from langsmith._internal._spool import TracingSpool, _decode_item, _encode_payload
+
+logger = logging.getLogger("langsmith.client")
+
+OverflowPolicy = Literal["drop_oldest", "drop_newest", "block", "spill"]
+OVERFLOW_POLICIES = ("drop_oldest", "drop_newest", "block", "spill")
+
+# What values other than strings and containers count for, e.g. numbers or ids
+_SCALAR_SIZE = 16
+# How deep to look into nested containers before counting them as a scalar
+_MAX_ESTIMATE_DEPTH = 32
+
+
+def _estimate_size(value: Any, depth: int = 0) -> int:
+    """Roughly estimate the serialized size of a payload, without serializing it.
+
+    This runs on the thread that creates the run, so it only adds up the
+    lengths of the strings and bytes in it.
+    """
+    if isinstance(value, (str, bytes, bytearray)):
+        return len(value)
+    if depth >= _MAX_ESTIMATE_DEPTH:
+        return _SCALAR_SIZE
+    if isinstance(value, Mapping):
+        return sum(
+            len(key) + _estimate_size(child, depth + 1)
+            if isinstance(key, str)
+            else _SCALAR_SIZE + _estimate_size(child, depth + 1)
+            for key, child in value.items()
+        )
+    if isinstance(value, (list, tuple)):
+        return sum(_estimate_size(child, depth + 1) for child in value)
+    return _SCALAR_SIZE
+
+
+class _Spill:
+    """Items that didn't fit in memory, kept in an anonymous temporary file."""
+
+    def __init__(self, directory: Optional[str]) -> None:
+        self.directory = directory
+        self.count = 0
+        self._file: Optional[IO[bytes]] = None
+        self._read_offset = 0
+
+    def append(self, item: TracingQueueItem) -> None:
+        record = {
+            "priority": item.priority,
+            "action": item.action,
+            "item": _encode_payload(item.item),
+            "spool_id": item.spool_id,
+            "sent_to": sorted(item.sent_to),
+        }
+        line = dumps_json(record) + b"\n"
+        if self._file is None:
+            self._file = tempfile.TemporaryFile(
+                prefix="langsmith-queue-", dir=self.directory
+            )
+        self._file.seek(0, os.SEEK_END)
+        self._file.write(line)
+        self.count += 1
+
+    def pop(self) -> TracingQueueItem:
+        assert self._file is not None
+        self._file.seek(self._read_offset)
+        line = self._file.readline()
+        self._read_offset += len(line)
+        self.count -= 1
+        if not self.count:
+            # Reclaim the disk space once everything was read back
+            self._file.truncate(0)
+            self._read_offset = 0
+        record = orjson.loads(line)
+        item = _decode_item(record, record.get("spool_id"), record["sent_to"])
+        item.size_bytes = len(line)
+        return item
+
+
+class BoundedTracingQueue(PriorityQueue):
+    """A priority queue of tracing items with an optional memory budget.
+
+    When adding an item would take the queue over ``max_items`` or
+    ``max_bytes``, the overflow policy decides what happens:
+
+    - ``drop_newest``: the new item is dropped.
+    - ``drop_oldest``: items at the head of the queue are dropped until the
+      new one fits.
+    - ``block``: the caller waits until the background thread makes room.
+    - ``spill``: the item is written to a temporary file in ``spill_dir``,
+      and read back once the items in memory were sent.
+
+    Dropped items are counted in ``dropped_items`` and ``dropped_bytes``, and
+    spilled ones in ``spilled_items``. Dropped items are also acknowledged in
+    ``spool``, if the client persisted them, so they aren't replayed later.
+
+    An item larger than ``max_bytes`` is only accepted into an empty queue.
+    Items are sized by a cheap estimate, unless they were already serialized
+    for the spool.
+    """
+
+    def __init__(
+        self,
+        *,
+        max_items: Optional[int] = None,
+        max_bytes: Optional[int] = None,
+        overflow_policy: OverflowPolicy = "drop_newest",
+        spill_dir: Optional[str] = None,
+    ) -> None:
+        """Initialize the queue. Without limits it never overflows."""
+        super().__init__()
+        self.max_items = max_items
+        self.max_bytes = max_bytes
+        self.overflow_policy = overflow_policy
+        self.size_bytes = 0
+        self.dropped_items = 0
+        self.dropped_bytes = 0
+        self.spilled_items = 0
+        self._spill = _Spill(spill_dir) if overflow_policy == "spill" else None
+        self.spool: Optional[TracingSpool] = None
+        self._warned = False
+
+    def put(
+        self, item: TracingQueueItem, block: bool = True, timeout: Any = None
+    ) -> None:
+        """Put an item on the queue, applying the overflow policy if it's full.
+
+        With the ``block`` policy, ``block`` and ``timeout`` bound the wait;
+        an item that still doesn't fit is dropped.
+        """
+        if not self.max_items and not self.max_bytes:
+            return super().put(item, block, timeout)
+        if self.max_bytes and not item.size_bytes:
+            item.size_bytes = _estimate_size(item.item)
+        with self.not_full:
+            if self._spill is not None and self._spill.count:
+                # Spilled items go back on the queue first
+                return self._spill_item(item)
+            if not self._fits(item):
+                if self.overflow_policy == "drop_oldest":
+                    while not self._fits(item):
+                        self._drop(heapq.heappop(self.queue), queued=True)
+                elif self.overflow_policy == "spill":
+                    return self._spill_item(item)
+                elif not (
+                    self.overflow_policy == "block"
+                    and block
+                    and self.not_full.wait_for(lambda: self._fits(item), timeout)
+                ):
+                    return self._drop(item)
+            self._put(item)
+            self.unfinished_tasks += 1
+            self.not_empty.notify()
+
+    def requeue(self, item: TracingQueueItem) -> None:
+        """Put an item back on the queue regardless of the limits.
+
+        Used by the background thread for items it is retrying, which must
+        neither block it nor be dropped. It reads them back from the spool a
+        batch at a time, so they don't take the queue far over its limits.
+        """
+        super().put(item)
+
+    def _fits(self, item: TracingQueueItem) -> bool:
+        if not self.queue:
+            return True
+        if self.max_items and len(self.queue) >= self.max_items:
+            return False
+        if self.max_bytes and self.size_bytes + item.size_bytes > self.max_bytes:
+            return False
+        return True
+
+    def _drop(self, item: TracingQueueItem, *, queued: bool = False) -> None:
+        if queued:
+            self.size_bytes -= item.size_bytes
+            self.unfinished_tasks -= 1
+            if not self.unfinished_tasks:
+                self.all_tasks_done.notify_all()
+        self.dropped_items += 1
+        self.dropped_bytes += item.size_bytes
+        if self.spool is not None and item.spool_id is not None:
+            self.spool.ack([item])
+        if not self._warned:
+            self._warned = True
+            logger.warning(
+                f"Tracing queue is full ({len(self.queue)} items,"
+                f" {self.size_bytes} bytes). Dropping runs with the"
+                f" {self.overflow_policy} policy until it drains."
+            )
+
+    def _spill_item(self, item: TracingQueueItem) -> None:
+        assert self._spill is not None
+        try:
+            self._spill.append(item)
+        except OSError as e:
+            logger.warning(f"Failed to spill tracing item to disk: {e!r}")
+            return self._drop(item)
+        self.spilled_items += 1
+        self.unfinished_tasks += 1
+        self.not_empty.notify()
+
+    def _qsize(self) -> int:
+        return len(self.queue) + (self._spill.count if self._spill else 0)
+
+    def _put(self, item: TracingQueueItem) -> None:
+        heapq.heappush(self.queue, item)
+        self.size_bytes += item.size_bytes
+
+    def _get(self) -> TracingQueueItem:
+        if not self.queue and self._spill is not None:
+            while self._spill.count and (
+                not self.queue
+                or (
+                    (not self.max_items or len(self.queue) < self.max_items)
+                    and (not self.max_bytes or self.size_bytes < self.max_bytes)
+                )
+            ):
+                self._put(self._spill.pop())
+        item = heapq.heappop(self.queue)
+        self.size_bytes -= item.size_bytes
+        if not self._qsize():
+            self._warned = False
+        return item
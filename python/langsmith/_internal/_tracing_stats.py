@@ -0,0 +1,193 @@
+"""Counters and histograms describing the client's tracing pipeline."""
+
+from __future__ import annotations
+
+import bisect
+import dataclasses
+import threading
+from typing import Dict, List, Sequence, Tuple
+
+# Runs per batch
+_BATCH_SIZE_BUCKETS = (1, 5, 10, 25, 50, 100, 250, 500, 1000)
+# Seconds per ingestion request
+_LATENCY_BUCKETS = (0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0)
+
+_COUNTERS = {
+    "runs_queued": "Runs and feedback put on the tracing queue.",
+    "runs_sent": "Runs and feedback in batches that were delivered.",
+    "runs_failed": "Runs and feedback in batches that failed to send.",
+    "runs_dropped": "Runs dropped because the tracing queue was full.",
+    "runs_spilled": "Runs spilled to disk because the tracing queue was full.",
+    "runs_filtered": "Runs filtered out by head or tail sampling.",
+    "runs_short_circuited": "Runs not sent because the circuit breaker was open.",
+    "batches": "Batches taken off the tracing queue.",
+    "requests": "Ingestion requests sent to the API.",
+    "request_retries": "Ingestion requests that were retries.",
+    "request_failures": "Ingestion requests that failed.",
+    "sub_threads_started": "Tracing threads started to keep up with the queue.",
+}
+_GAUGES = {
+    "queue_depth": "Items waiting on the tracing queue.",
+    "queue_bytes": "Serialized size of the items on the tracing queue.",
+    "sub_threads": "Tracing threads running besides the control thread.",
+    "circuit_breaker_open": "API URLs whose circuit breaker has paused sending.",
+}
+_HISTOGRAMS = {
+    "batch_size": "Runs and feedback per batch.",
+    "request_latency_seconds": "Duration of ingestion requests.",
+}
+
+
+@dataclasses.dataclass(frozen=True)
+class Histogram:
+    """A snapshot of a histogram.
+
+    Attributes:
+        buckets: Upper bounds and how many observations were at most that.
+        count: The number of observations.
+        sum: The sum of the observations.
+    """
+
+    buckets: Tuple[Tuple[float, int], ...]
+    count: int
+    sum: float
+
+
+@dataclasses.dataclass(frozen=True)
+class TracingStats:
+    """A snapshot of the client's tracing pipeline.
+
+    Counters are totals since the client was created.
+    """
+
+    queue_depth: int
+    queue_bytes: int
+    sub_threads: int
+    circuit_breaker_open: int
+    runs_queued: int
+    runs_sent: int
+    runs_failed: int
+    runs_dropped: int
+    runs_spilled: int
+    runs_filtered: int
+    runs_short_circuited: int
+    batches: int
+    requests: int
+    request_retries: int
+    request_failures: int
+    sub_threads_started: int
+    batch_size: Histogram
+    request_latency_seconds: Histogram
+
+    def to_prometheus(self, prefix: str = "langsmith_tracing_") -> str:
+        """Render the stats in the Prometheus text exposition format."""
+        lines: List[str] = []
+        for name, description in _COUNTERS.items():
+            metric = f"{prefix}{name}_total"
+            lines += [
+                f"# HELP {metric} {description}",
+                f"# TYPE {metric} counter",
+                f"{metric} {getattr(self, name)}",
+            ]
+        for name, description in _GAUGES.items():
+            metric = f"{prefix}{name}"
+            lines += [
+                f"# HELP {metric} {description}",
+                f"# TYPE {metric} gauge",
+                f"{metric} {getattr(self, name)}",
+            ]
+        for name, description in _HISTOGRAMS.items():
+            metric = f"{prefix}{name}"
+            histogram: Histogram = getattr(self, name)
+            lines += [
+                f"# HELP {metric} {description}",
+                f"# TYPE {metric} histogram",
+            ]
+            lines += [
+                f'{metric}_bucket{{le="{bound:g}"}} {count}'
+                for bound, count in histogram.buckets
+            ]
+            lines += [
+                f'{metric}_bucket{{le="+Inf"}} {histogram.count}',
+                f"{metric}_sum {histogram.sum:g}",
+                f"{metric}_count {histogram.count}",
+            ]
+        return "\n".join(lines) + "\n"
+
+
+@dataclasses.dataclass(frozen=True)
+class DeliveryReport:
+    """What happened to queued runs while a client flushed or shut down.
+
+    Attributes:
+        delivered: Runs and feedback that were sent.
+        failed: Runs and feedback in batches that failed to send.
+        abandoned: Runs and feedback still queued or being sent when the
+            timeout expired.
+    """
+
+    delivered: int
+    failed: int
+    abandoned: int
+
+
+class _Histogram:
+    def __init__(self, buckets: Sequence[float]) -> None:
+        self.bounds = tuple(buckets)
+        self.counts = [0] * len(self.bounds)
+        self.count = 0
+        self.sum = 0.0
+
+    def observe(self, value: float) -> None:
+        self.count += 1
+        self.sum += value
+        idx = bisect.bisect_left(self.bounds, value)
+        if idx < len(self.counts):
+            self.counts[idx] += 1
+
+    def snapshot(self) -> Histogram:
+        cumulative, buckets = 0, []
+        for bound, count in zip(self.bounds, self.counts):
+            cumulative += count
+            buckets.append((bound, cumulative))
+        return Histogram(tuple(buckets), self.count, self.sum)
+
+
+class TracingStatsRecorder:
+    """Thread-safe counters and histograms updated by the tracing pipeline."""
+
+    def __init__(self) -> None:
+        """Initialize every counter at zero."""
+        self._lock = threading.Lock()
+        self._counters: Dict[str, int] = dict.fromkeys(_COUNTERS, 0)
+        self._histograms = {
+            "batch_size": _Histogram(_BATCH_SIZE_BUCKETS),
+            "request_latency_seconds": _Histogram(_LATENCY_BUCKETS),
+        }
+        self.sub_threads = 0
+
+    def increment(self, name: str, value: int = 1) -> None:
+        """Add to a counter."""
+        with self._lock:
+            self._counters[name] += value
+
+    def observe(self, name: str, value: float) -> None:
+        """Record an observation in a histogram."""
+        with self._lock:
+            self._histograms[name].observe(value)
+
+    def snapshot(self, **values: int) -> TracingStats:
+        """Read the stats, adding the given values to gauges and counters."""
+        with self._lock:
+            stats: Dict[str, int] = {
+                **dict.fromkeys(_GAUGES, 0),
+                **self._counters,
+                "sub_threads": self.sub_threads,
+            }
+            histograms = {k: v.snapshot() for k, v in self._histograms.items()}
+        for name, value in values.items():
+            stats[name] += value
+        return TracingStats(
+            **stats,  # type: ignore[arg-type]
+            **histograms,  # type: ignore[arg-type]
+        )
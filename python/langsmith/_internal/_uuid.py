@@ -0,0 +1,62 @@
+"""Time-ordered UUIDv7 ids for runs."""
+
+from __future__ import annotations
+
+import os
+import threading
+import time
+import uuid
+from typing import Optional
+
+from langsmith import utils as ls_utils
+
+_lock = threading.Lock()
+_last_ms = 0
+_counter = 0
+_COUNTER_MAX = 0xFFF
+
+
+def uuid7(timestamp_ms: Optional[int] = None) -> uuid.UUID:
+    """Create a UUIDv7, which sorts by the millisecond it was created in.
+
+    Follows RFC 9562: a 48-bit Unix timestamp in milliseconds, then a 12-bit
+    counter and 62 random bits. The counter starts at a random value each
+    millisecond and is incremented within one, so ids created by a process
+    keep their order even when its clock is coarse or steps back.
+
+    Args:
+        timestamp_ms: The creation time to use, instead of now. Ids created
+            with one aren't ordered with the other ids of the process.
+    """
+    global _last_ms, _counter
+    rand = int.from_bytes(os.urandom(10), "big")
+    if timestamp_ms is not None:
+        ms, counter = timestamp_ms, rand >> 68
+    else:
+        with _lock:
+            ms = time.time_ns() // 1_000_000
+            if ms > _last_ms:
+                # Leave room to count up within the millisecond
+                _last_ms, _counter = ms, rand >> 69
+            elif _counter < _COUNTER_MAX:
+                _counter += 1
+            else:
+                _last_ms, _counter = _last_ms + 1, 0
+            ms, counter = _last_ms, _counter
+    value = (ms & 0xFFFF_FFFF_FFFF) << 80
+    value |= 0x7 << 76  # version
+    value |= counter << 64
+    value |= 0b10 << 62  # variant
+    value |= rand & ((1 << 62) - 1)
+    return uuid.UUID(int=value)
+
+
+def new_run_id() -> uuid.UUID:
+    """Create the id of a new run.
+
+    UUIDv7 if LANGSMITH_TRACING_UUID7 is "true", so runs sort by creation
+    time, and UUIDv4 otherwise.
+    """
+    if ls_utils.get_env_var("TRACING_UUID7") == "true":
+        return uuid7()
+    return uuid.uuid4()
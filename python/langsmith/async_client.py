@@ -4,7 +4,9 @@ from __future__ import annotations
 
 import asyncio
 import datetime
+import logging
 import uuid
+import weakref
 from typing import (
     Any,
     AsyncIterator,
@@ -20,16 +22,36 @@ from typing import (
 
 import httpx
 
+from langsmith import circuit_breaker as ls_circuit_breaker
 from langsmith import client as ls_client
+from langsmith import payload_limits as ls_payload_limits
 from langsmith import schemas as ls_schemas
 from langsmith import utils as ls_utils
 from langsmith._internal import _beta_decorator as ls_beta
+from langsmith._internal import _uuid
+from langsmith._internal._background_task import tracing_control_task_func
+from langsmith._internal._background_thread import (
+    TracingQueueItem,
+    _ensure_ingest_config,
+)
+
+logger = logging.getLogger("langsmith.client")
 
 
 class AsyncClient:
     """Async Client for interacting with the LangSmith API."""
 
-    __slots__ = ("_retry_config", "_client", "_web_url")
+    __slots__ = (
+        "__weakref__",
+        "_retry_config",
+        "_client",
+        "_web_url",
+        "_auto_batch_tracing",
+        "_tracing_queue",
+        "_tracing_task",
+        "_circuit_breaker",
+        "_payload_limit",
+    )
 
     def __init__(
         self,
@@ -42,8 +64,23 @@ class AsyncClient:
         ] = None,
         retry_config: Optional[Mapping[str, Any]] = None,
         web_url: Optional[str] = None,
+        auto_batch_tracing: bool = False,
+        circuit_breaker: Optional[ls_circuit_breaker.CircuitBreaker] = None,
+        payload_limit: Optional[ls_payload_limits.PayloadLimit] = None,
     ):
-        """Initialize the async client."""
+        """Initialize the async client.
+
+        With ``auto_batch_tracing``, runs that have a ``trace_id`` and
+        ``dotted_order`` are queued and sent in batches by a background task,
+        which is started on first use and flushed by ``aclose()``. Queued runs
+        are lost if the client isn't closed, so batching is off by default. A
+        ``circuit_breaker`` drops batches instead of sending them while the
+        API is down, and defaults to the
+        LANGSMITH_TRACING_CIRCUIT_BREAKER_THRESHOLD environment variable. A
+        ``payload_limit`` shrinks oversized inputs and outputs in multipart
+        batches, and defaults to the LANGSMITH_TRACING_PAYLOAD_MAX_BYTES
+        environment variable.
+        """
         ls_beta._warn_once("Class AsyncClient is in beta.")
         self._retry_config = retry_config or {"max_retries": 3}
         _headers = {
@@ -65,6 +102,20 @@ class AsyncClient:
             base_url=api_url, headers=_headers, timeout=timeout_
         )
         self._web_url = web_url
+        self._auto_batch_tracing = auto_batch_tracing
+        # Created on first use, so they belong to the running event loop
+        self._tracing_queue: Optional[asyncio.PriorityQueue] = None
+        self._tracing_task: Optional[asyncio.Task] = None
+        self._circuit_breaker = (
+            circuit_breaker
+            if circuit_breaker is not None
+            else ls_circuit_breaker.get_circuit_breaker_from_env()
+        )
+        self._payload_limit = (
+            payload_limit
+            if payload_limit is not None
+            else ls_payload_limits.get_payload_limit_from_env()
+        )
 
     async def __aenter__(self) -> AsyncClient:
         """Enter the async client."""
@@ -75,9 +126,24 @@ class AsyncClient:
         await self.aclose()
 
     async def aclose(self):
-        """Close the async client."""
+        """Close the async client, sending the runs still queued first."""
+        if self._tracing_task is not None:
+            await self._aflush()
+            self._tracing_task.cancel()
+            await asyncio.gather(self._tracing_task, return_exceptions=True)
+            self._tracing_task = None
         await self._client.aclose()
 
+    async def _aflush(self) -> None:
+        if self._tracing_queue is None or self._tracing_task is None:
+            return
+        join = asyncio.ensure_future(self._tracing_queue.join())
+        # Stop waiting if the background task died with runs still queued
+        await asyncio.wait(
+            [join, self._tracing_task], return_when=asyncio.FIRST_COMPLETED
+        )
+        join.cancel()
+
     @property
     def _api_url(self):
         return str(self._client.base_url)
@@ -176,13 +242,22 @@ class AsyncClient:
         """Create a run."""
         run_create = {
             "name": name,
-            "id": kwargs.get("id") or uuid.uuid4(),
+            "id": kwargs.get("id") or _uuid.new_run_id(),
             "inputs": inputs,
             "run_type": run_type,
             "session_name": project_name or ls_utils.get_tracer_project(),
             "revision_id": revision_id,
             **kwargs,
         }
+        if (
+            self._auto_batch_tracing
+            # batch ingest requires trace_id and dotted_order to be set
+            and run_create.get("trace_id") is not None
+            and run_create.get("dotted_order") is not None
+        ):
+            return self._enqueue_tracing_item(
+                TracingQueueItem(run_create["dotted_order"], "create", run_create)
+            )
         await self._arequest_with_retries(
             "POST", "/runs", content=ls_client._dumps_json(run_create)
         )
@@ -194,12 +269,92 @@ class AsyncClient:
     ) -> None:
         """Update a run."""
         data = {**kwargs, "id": ls_client._as_uuid(run_id)}
+        if (
+            self._auto_batch_tracing
+            and data.get("trace_id") is not None
+            and data.get("dotted_order") is not None
+        ):
+            return self._enqueue_tracing_item(
+                TracingQueueItem(data["dotted_order"], "update", data)
+            )
         await self._arequest_with_retries(
             "PATCH",
             f"/runs/{ls_client._as_uuid(run_id)}",
             content=ls_client._dumps_json(data),
         )
 
+    def _enqueue_tracing_item(self, item: TracingQueueItem) -> None:
+        if self._tracing_queue is None:
+            self._tracing_queue = asyncio.PriorityQueue()
+        if self._tracing_task is None:
+            self._tracing_task = asyncio.ensure_future(
+                # a weakref, so the task doesn't keep the client alive
+                tracing_control_task_func(weakref.ref(self), self._tracing_queue)
+            )
+        # Copy inputs and outputs, so later changes to them aren't sent
+        for key in ("inputs", "outputs"):
+            if item.item.get(key) is not None:
+                item.item[key] = ls_utils.snapshot(item.item[key])
+        self._tracing_queue.put_nowait(item)
+
+    async def _aget_batch_ingest_config(self) -> ls_schemas.BatchIngestConfig:
+        try:
+            response = await self._arequest_with_retries(
+                "GET", "/info", headers={"Accept": "application/json"}
+            )
+            info: Optional[ls_schemas.LangSmithInfo] = ls_schemas.LangSmithInfo(
+                **response.json()
+            )
+        except Exception as e:
+            logger.warning(f"Failed to get info from {self._api_url}: {repr(e)}")
+            info = None
+        return _ensure_ingest_config(info)
+
+    async def _abatch_ingest_runs(self, create: List[dict], update: List[dict]) -> None:
+        update = ls_client._combine_run_updates(create, update)
+        try:
+            await self._arequest_with_retries(
+                "POST",
+                "/runs/batch",
+                content=ls_client._dumps_json({"post": create, "patch": update}),
+            )
+            self._record_ingest_request()
+        except Exception as e:
+            self._record_ingest_request(e)
+            logger.warning(f"Failed to batch ingest runs: {repr(e)}")
+
+    async def _amultipart_ingest(self, create: List[dict], update: List[dict]) -> None:
+        update = ls_client._combine_run_updates(create, update)
+        all_attachments: Dict[str, ls_schemas.Attachments] = {}
+        for run in (*create, *update):
+            if attachments := run.pop("attachments", None):
+                all_attachments[run["id"]] = attachments
+        parts, context = ls_client._serialize_multipart_parts(
+            create, update, [], all_attachments, self._payload_limit
+        )
+        # Encode the body up front: retries need to send it again, and the
+        # client's default JSON content type must not replace the boundary.
+        request = httpx.Request("POST", "/runs/multipart", files=parts)
+        try:
+            await self._arequest_with_retries(
+                "POST",
+                "/runs/multipart",
+                content=request.read(),
+                headers={"Content-Type": request.headers["Content-Type"]},
+            )
+            self._record_ingest_request()
+        except Exception as e:
+            self._record_ingest_request(e)
+            logger.warning(f"Failed to multipart ingest runs: {repr(e)} {context}")
+
+    def _record_ingest_request(self, error: Optional[BaseException] = None) -> None:
+        if self._circuit_breaker is None:
+            return
+        if error is not None:
+            self._circuit_breaker.record_error(error)
+        else:
+            self._circuit_breaker.record_success()
+
     async def read_run(self, run_id: ls_client.ID_TYPE) -> ls_schemas.Run:
         """Read a run."""
         response = await self._arequest_with_retries(
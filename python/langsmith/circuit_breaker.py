@@ -0,0 +1,165 @@
+"""A circuit breaker that pauses tracing while the LangSmith API is down."""
+
+from __future__ import annotations
+
+import logging
+import threading
+import time
+from typing import Callable, Literal, Optional
+
+from langsmith import utils as ls_utils
+from langsmith._internal._delivery import is_retryable_error
+
+logger = logging.getLogger(__name__)
+
+CircuitState = Literal["closed", "open", "half_open"]
+
+
+class CircuitBreaker:
+    """Stop sending traces after repeated failures, until the API recovers.
+
+    The breaker opens after ``failure_threshold`` consecutive ingestion
+    requests fail because the API is unreachable, overloaded or erroring.
+    While it is open, batches are not sent: a client with a tracing spool
+    keeps their runs on disk to retry later, and otherwise drops them,
+    counting them in ``short_circuited_runs``. After ``reset_timeout``
+    seconds a single batch is let through as a probe. If it is delivered the
+    breaker closes, and if not it opens again.
+
+    One breaker can be shared by several clients, e.g. a Client and an
+    AsyncClient sending to the same API. A client with several write API URLs
+    uses it for the first, and a breaker configured like it for each other.
+
+    Example:
+        .. code-block:: python
+
+            from langsmith import Client
+            from langsmith.circuit_breaker import CircuitBreaker
+
+            client = Client(
+                circuit_breaker=CircuitBreaker(failure_threshold=3, reset_timeout=60)
+            )
+    """
+
+    def __init__(
+        self,
+        failure_threshold: int = 5,
+        reset_timeout: float = 30.0,
+        *,
+        clock: Callable[[], float] = time.monotonic,
+    ) -> None:
+        """Initialize a closed circuit breaker."""
+        if failure_threshold < 1:
+            raise ls_utils.LangSmithUserError(
+                "Circuit breaker failure_threshold must be at least 1."
+            )
+        self.failure_threshold = failure_threshold
+        self.reset_timeout = reset_timeout
+        self.times_opened = 0
+        self.short_circuited_runs = 0
+        self._clock = clock
+        self._lock = threading.Lock()
+        self._state: CircuitState = "closed"
+        self._failures = 0
+        self._opened_at = 0.0
+        self._probe_started: Optional[float] = None
+
+    @property
+    def state(self) -> CircuitState:
+        """Whether requests are sent ("closed"), paused ("open") or probing."""
+        with self._lock:
+            if self._state == "open" and self._probe_due():
+                return "half_open"
+            return self._state
+
+    def allow_request(self) -> bool:
+        """Whether a batch may be sent now.
+
+        Once the reset timeout has passed, returns True for a single probe
+        until its outcome is recorded, or for another one if no outcome is
+        recorded within the reset timeout.
+        """
+        with self._lock:
+            if self._state == "closed":
+                return True
+            if self._state == "open" and self._probe_due():
+                self._state = "half_open"
+            if self._state == "half_open" and (
+                self._probe_started is None
+                or self._clock() - self._probe_started >= self.reset_timeout
+            ):
+                self._probe_started = self._clock()
+                return True
+            return False
+
+    def record_success(self) -> None:
+        """Record a request that reached the API, closing the breaker."""
+        with self._lock:
+            if self._state != "closed":
+                logger.info("LangSmith API recovered, resuming tracing.")
+            self._state = "closed"
+            self._failures = 0
+            self._probe_started = None
+
+    def record_failure(self) -> None:
+        """Record a failed request, opening the breaker past the threshold."""
+        with self._lock:
+            self._failures += 1
+            self._probe_started = None
+            if self._state == "half_open" or (
+                self._state == "closed" and self._failures >= self.failure_threshold
+            ):
+                if self._state == "closed":
+                    logger.warning(
+                        f"Pausing tracing for {self.reset_timeout:g}s after "
+                        f"{self._failures} failed requests to the LangSmith API."
+                    )
+                self._state = "open"
+                self._opened_at = self._clock()
+                self.times_opened += 1
+
+    def record_error(self, error: BaseException) -> None:
+        """Record a request that raised, counting only signs of an outage.
+
+        Other errors leave the count of failures as it is.
+        """
+        if is_retryable_error(error):
+            self.record_failure()
+        else:
+            with self._lock:
+                # Let another probe through rather than wait out its timeout
+                self._probe_started = None
+
+    def record_short_circuited(self, n_runs: int) -> None:
+        """Count runs in a batch that wasn't sent because the breaker was open."""
+        with self._lock:
+            self.short_circuited_runs += n_runs
+
+    def _copy(self) -> CircuitBreaker:
+        return CircuitBreaker(
+            self.failure_threshold, self.reset_timeout, clock=self._clock
+        )
+
+    def _probe_due(self) -> bool:
+        return self._clock() - self._opened_at >= self.reset_timeout
+
+    def _reset_after_fork(self) -> None:
+        # the lock may have been held by a thread that doesn't exist in the child
+        self._lock = threading.Lock()
+        self._probe_started = None
+
+
+def get_circuit_breaker_from_env() -> Optional[CircuitBreaker]:
+    """Create a circuit breaker from the environment, if one is configured.
+
+    Reads LANGSMITH_TRACING_CIRCUIT_BREAKER_THRESHOLD, the number of
+    consecutive failures that open the breaker, and optionally
+    LANGSMITH_TRACING_CIRCUIT_BREAKER_RESET_TIMEOUT, in seconds.
+    """
+    threshold = ls_utils.get_env_var("TRACING_CIRCUIT_BREAKER_THRESHOLD")
+    if not threshold:
+        return None
+    reset_timeout = ls_utils.get_env_var("TRACING_CIRCUIT_BREAKER_RESET_TIMEOUT")
+    if reset_timeout:
+        return CircuitBreaker(int(threshold), float(reset_timeout))
+    return CircuitBreaker(int(threshold))
@@ -16,13 +16,16 @@ import atexit
 import collections
 import concurrent.futures as cf
 import contextlib
+import dataclasses
 import datetime
 import functools
 import importlib
 import importlib.metadata
 import io
+import itertools
 import json
 import logging
+import math
 import os
 import random
 import threading
@@ -33,11 +36,11 @@ import uuid
 import warnings
 import weakref
 from inspect import signature
-from queue import PriorityQueue
 from typing import (
     TYPE_CHECKING,
     Any,
     Callable,
+    Container,
     DefaultDict,
     Dict,
     Iterable,
@@ -63,9 +66,21 @@ from urllib3.poolmanager import PoolKey  # type: ignore[attr-defined]
 from urllib3.util import Retry
 
 import langsmith
+from langsmith import circuit_breaker as ls_circuit_breaker
+from langsmith import destinations as ls_destinations
 from langsmith import env as ls_env
+from langsmith import exporters as ls_exporters
+from langsmith import offline as ls_offline
+from langsmith import payload_limits as ls_payload_limits
+from langsmith import sampling as ls_sampling
 from langsmith import schemas as ls_schemas
 from langsmith import utils as ls_utils
+from langsmith._internal import _compression, _media, _native, _trace_validation, _uuid
+from langsmith._internal._attachments import (
+    AttachmentReader,
+    as_part_body,
+    read_streams,
+)
 from langsmith._internal._background_thread import (
     TracingQueueItem,
 )
@@ -78,7 +93,25 @@ from langsmith._internal._constants import (
     _BLOCKSIZE_BYTES,
     _SIZE_LIMIT_BYTES,
 )
+from langsmith._internal._delivery import (
+    BatchDelivery,
+    Outcome,
+    SendLane,
+    combine_outcomes,
+    outcome_of,
+)
 from langsmith._internal._serde import dumps_json as _dumps_json
+from langsmith._internal._spool import TracingSpool
+from langsmith._internal._tracing_queue import (
+    OVERFLOW_POLICIES,
+    BoundedTracingQueue,
+    OverflowPolicy,
+)
+from langsmith._internal._tracing_stats import (
+    DeliveryReport,
+    TracingStats,
+    TracingStatsRecorder,
+)
 
 try:
     from zoneinfo import ZoneInfo  # type: ignore[import-not-found]
@@ -101,7 +134,9 @@ X_API_KEY = "x-api-key"
 WARNED_ATTACHMENTS = False
 EMPTY_SEQ: tuple[Dict, ...] = ()
 BOUNDARY = uuid.uuid4().hex
-MultipartParts = List[Tuple[str, Tuple[None, bytes, str, Dict[str, str]]]]
+MultipartParts = List[
+    Tuple[str, Tuple[None, Union[bytes, AttachmentReader], str, Dict[str, str]]]
+]
 URLLIB3_SUPPORTS_BLOCKSIZE = "key_blocksize" in signature(PoolKey).parameters
 
 
@@ -149,6 +184,239 @@ def _is_langchain_hosted(url: str) -> bool:
         return False
 
 
+# Clients with a tracing queue, whose state is reset in forked processes
+_TRACING_CLIENTS: weakref.WeakSet[Client] = weakref.WeakSet()
+# How long a fork waits for the queued runs to be sent
+_FORK_FLUSH_TIMEOUT_S = 1.0
+
+
+def _flush_tracing_before_fork() -> None:
+    # The child starts with an empty queue, and without a batch half-sent
+    for client in list(_TRACING_CLIENTS):
+        try:
+            client.flush(timeout=_FORK_FLUSH_TIMEOUT_S)
+        except Exception as e:
+            logger.warning(f"Failed to flush tracing before fork: {e!r}")
+
+
+def _reset_tracing_after_fork() -> None:
+    for client in list(_TRACING_CLIENTS):
+        try:
+            client._reset_tracing_after_fork()
+        except Exception as e:
+            logger.warning(f"Failed to reset tracing after fork: {e!r}")
+
+
+if hasattr(os, "register_at_fork"):
+    os.register_at_fork(
+        before=_flush_tracing_before_fork, after_in_child=_reset_tracing_after_fork
+    )
+
+
+def _shutdown_tracing_at_exit(client_ref: weakref.ref[Client]) -> None:
+    client = client_ref()
+    if client is None or client._tracing_shutdown_deadline is not None:
+        return
+    timeout = ls_utils.get_env_var("TRACING_SHUTDOWN_TIMEOUT")
+    report = client.shutdown(timeout=float(timeout) if timeout else None)
+    if report.failed or report.abandoned:
+        logger.warning(
+            f"LangSmith tracing shut down with {report.failed} runs that failed"
+            f" to send and {report.abandoned} runs abandoned."
+        )
+
+
+def _register_tracing_shutdown_at_exit(client: Client) -> None:
+    # threading's hooks run before the interpreter waits for the tracing
+    # threads to finish, which lets a timeout bound how long exiting takes
+    register = getattr(threading, "_register_atexit", atexit.register)
+    try:
+        register(_shutdown_tracing_at_exit, weakref.ref(client))
+    except RuntimeError:
+        # The interpreter is already shutting down
+        pass
+
+
+def _create_tracing_queue(
+    max_items: Optional[int],
+    max_bytes: Optional[int],
+    overflow_policy: Optional[str],
+) -> BoundedTracingQueue:
+    max_items = max_items or int(
+        ls_utils.get_env_var("TRACING_QUEUE_MAX_ITEMS") or 0
+    )
+    max_bytes = max_bytes or int(
+        ls_utils.get_env_var("TRACING_QUEUE_MAX_BYTES") or 0
+    )
+    overflow_policy = (
+        overflow_policy
+        or ls_utils.get_env_var("TRACING_QUEUE_OVERFLOW_POLICY")
+        or "drop_newest"
+    )
+    if overflow_policy not in OVERFLOW_POLICIES:
+        raise ls_utils.LangSmithUserError(
+            f"Unsupported tracing queue overflow policy {overflow_policy!r}. "
+            f"Expected one of {', '.join(OVERFLOW_POLICIES)}."
+        )
+    return BoundedTracingQueue(
+        max_items=max_items,
+        max_bytes=max_bytes,
+        overflow_policy=cast(OverflowPolicy, overflow_policy),
+        spill_dir=ls_utils.get_env_var("TRACING_QUEUE_SPILL_DIR"),
+    )
+
+
+def _combine_run_updates(
+    create_dicts: Sequence[dict], update_dicts: Sequence[dict]
+) -> List[dict]:
+    """Merge updates into the creates for the same run.
+
+    Returns:
+        The updates for runs that aren't created in the same batch.
+    """
+    if not update_dicts or not create_dicts:
+        return list(update_dicts)
+    create_by_id = {run["id"]: run for run in create_dicts}
+    standalone_updates: List[dict] = []
+    for run in update_dicts:
+        if run["id"] in create_by_id:
+            for k, v in run.items():
+                if v is not None:
+                    create_by_id[run["id"]][k] = v
+        else:
+            standalone_updates.append(run)
+    return standalone_updates
+
+
+def _serialize_multipart_parts(
+    create_dicts: Sequence[dict],
+    update_dicts: Sequence[dict],
+    feedback_dicts: Sequence[dict],
+    all_attachments: Dict[str, ls_schemas.Attachments],
+    payload_limit: Optional[ls_payload_limits.PayloadLimit] = None,
+) -> Tuple[MultipartParts, str]:
+    """Encode runs and feedback as the parts of a multipart ingest request.
+
+    Inputs and outputs over the payload limit are shrunk, or moved into
+    attachments.
+
+    Returns:
+        The parts, and a description of the runs for error messages.
+    """
+    acc_context: List[str] = []
+    acc_parts: MultipartParts = []
+    for event, payloads in (
+        ("post", create_dicts),
+        ("patch", update_dicts),
+        ("feedback", feedback_dicts),
+    ):
+        for payload in payloads:
+            # collect fields to be sent as separate parts
+            fields = [
+                ("inputs", payload.pop("inputs", None)),
+                ("outputs", payload.pop("outputs", None)),
+                ("events", payload.pop("events", None)),
+                ("feedback", payload.pop("feedback", None)),
+            ]
+            # encode the main run payload
+            payloadb = _dumps_json(payload)
+            acc_parts.append(
+                (
+                    f"{event}.{payload['id']}",
+                    (
+                        None,
+                        payloadb,
+                        "application/json",
+                        {"Content-Length": str(len(payloadb))},
+                    ),
+                )
+            )
+            # encode the fields we collected
+            attachments = all_attachments.pop(payload["id"], None) or {}
+            for key, value in fields:
+                if value is None:
+                    continue
+                valb = _dumps_json(value)
+                if payload_limit is not None and len(valb) > payload_limit.max_bytes:
+                    value, offloaded = payload_limit.apply(key, value)
+                    attachments = {**attachments, **offloaded}
+                    valb = _dumps_json(value)
+                acc_parts.append(
+                    (
+                        f"{event}.{payload['id']}.{key}",
+                        (
+                            None,
+                            valb,
+                            "application/json",
+                            {"Content-Length": str(len(valb))},
+                        ),
+                    ),
+                )
+            # encode the attachments
+            if attachments:
+                for n, (ct, data) in attachments.items():
+                    # files and streams are read as the request is sent
+                    body = as_part_body(data)
+                    acc_parts.append(
+                        (
+                            f"attachment.{payload['id']}.{n}",
+                            (None, body, ct, {"Content-Length": str(len(body))}),
+                        )
+                    )
+            # compute context
+            acc_context.append(
+                f"trace={payload.get('trace_id')},id={payload.get('id')}"
+            )
+    return acc_parts, "; ".join(acc_context)
+
+
+def _chunk_multipart_parts(
+    parts: MultipartParts, size_limit_bytes: int
+) -> List[MultipartParts]:
+    """Group multipart parts into requests of at most ``size_limit_bytes``.
+
+    The parts of a run stay together, so a run larger than the limit gets a
+    request of its own.
+    """
+    chunks: List[MultipartParts] = []
+    chunk: MultipartParts = []
+    chunk_size = 0
+    # Parts are named {event}.{id}[.{field}] or attachment.{id}.{name}
+    for _, group in itertools.groupby(parts, key=lambda part: part[0].split(".")[1]):
+        run_parts = list(group)
+        size = sum(len(body) for _, (_, body, _, _) in run_parts)
+        if chunk and chunk_size + size > size_limit_bytes:
+            chunks.append(chunk)
+            chunk, chunk_size = [], 0
+        chunk.extend(run_parts)
+        chunk_size += size
+    if chunk:
+        chunks.append(chunk)
+    return chunks
+
+
+def _rewind_multipart_parts(parts: MultipartParts) -> None:
+    for _, (_, body, _, _) in parts:
+        if isinstance(body, AttachmentReader):
+            body.rewind()
+
+
+def _split_multipart_parts(
+    parts: MultipartParts,
+) -> Optional[Tuple[MultipartParts, MultipartParts]]:
+    """Split multipart parts in two, keeping the parts of each run together.
+
+    Returns None if the parts all belong to one run.
+    """
+    # Parts are named {event}.{id}[.{field}] or attachment.{id}.{name}
+    ids = [name.split(".")[1] for name, _ in parts]
+    boundaries = [i for i in range(1, len(ids)) if ids[i] != ids[i - 1]]
+    if not boundaries:
+        return None
+    middle = min(boundaries, key=lambda i: abs(2 * i - len(ids)))
+    return parts[:middle], parts[middle:]
+
+
 ID_TYPE = Union[uuid.UUID, str]
 RUN_TYPE_T = Literal[
     "tool", "chain", "llm", "retriever", "embedding", "prompt", "parser"
@@ -244,21 +512,27 @@ def _get_tracing_sampling_rate() -> float | None:
     return sampling_rate
 
 
-def _get_write_api_urls(_write_api_urls: Optional[Dict[str, str]]) -> Dict[str, str]:
+def _get_write_api_urls(
+    _write_api_urls: Optional[Mapping[str, Union[str, ls_destinations.Destination]]],
+) -> Dict[str, ls_destinations.Destination]:
     _write_api_urls = _write_api_urls or json.loads(
         os.getenv("LANGSMITH_RUNS_ENDPOINTS", "{}")
     )
     processed_write_api_urls = {}
-    for url, api_key in _write_api_urls.items():
+    for url, value in _write_api_urls.items():
         processed_url = url.strip()
         if not processed_url:
             raise ls_utils.LangSmithUserError(
                 "LangSmith runs API URL within LANGSMITH_RUNS_ENDPOINTS cannot be empty"
             )
         processed_url = processed_url.strip().strip('"').strip("'").rstrip("/")
-        processed_api_key = api_key.strip().strip('"').strip("'")
-        _validate_api_key_if_hosted(processed_url, processed_api_key)
-        processed_write_api_urls[processed_url] = processed_api_key
+        destination = ls_destinations._from_config(value)
+        if destination.api_key is not None:
+            destination = dataclasses.replace(
+                destination, api_key=destination.api_key.strip().strip('"').strip("'")
+            )
+        _validate_api_key_if_hosted(processed_url, destination.api_key)
+        processed_write_api_urls[processed_url] = destination
 
     return processed_write_api_urls
 
@@ -287,7 +561,7 @@ def _ensure_uuid(value: Optional[Union[str, uuid.UUID]], *, accept_null: bool =
     if value is None:
         if accept_null:
             return None
-        return uuid.uuid4()
+        return _uuid.new_run_id()
     return _as_uuid(value)
 
 
@@ -340,13 +614,29 @@ class Client:
         "_web_url",
         "_tenant_id",
         "tracing_sample_rate",
+        "_sampling_rules",
         "_filtered_post_uuids",
         "tracing_queue",
+        "_tracing_spool",
+        "_tail_sampler",
+        "_exporters",
+        "_compression",
+        "_compression_ratio",
+        "_tracing_stats",
+        "_tracing_thread",
+        "_tracing_thread_lock",
+        "_tracing_shutdown_deadline",
+        "_circuit_breakers",
+        "_send_lanes",
+        "_payload_limit",
+        "_extract_media",
+        "_trace_validator",
         "_anonymizer",
         "_hide_inputs",
         "_hide_outputs",
         "_info",
         "_write_api_urls",
+        "_write_destinations",
         "_settings",
     ]
 
@@ -364,7 +654,23 @@ class Client:
         hide_inputs: Optional[Union[Callable[[dict], dict], bool]] = None,
         hide_outputs: Optional[Union[Callable[[dict], dict], bool]] = None,
         info: Optional[Union[dict, ls_schemas.LangSmithInfo]] = None,
-        api_urls: Optional[Dict[str, str]] = None,
+        api_urls: Optional[
+            Mapping[str, Union[str, ls_destinations.Destination]]
+        ] = None,
+        tracing_spool_dir: Optional[str] = None,
+        tracing_spool_max_bytes: Optional[int] = None,
+        tracing_spool_max_age: Optional[float] = None,
+        tail_sampler: Optional[ls_sampling.TailSampler] = None,
+        sampling_rules: Optional[Sequence[ls_sampling.SamplingRule]] = None,
+        exporters: Optional[Sequence[ls_exporters.TraceExporter]] = None,
+        compression: Optional[Literal["gzip", "zstd"]] = None,
+        tracing_queue_max_items: Optional[int] = None,
+        tracing_queue_max_bytes: Optional[int] = None,
+        tracing_queue_overflow_policy: Optional[OverflowPolicy] = None,
+        circuit_breaker: Optional[ls_circuit_breaker.CircuitBreaker] = None,
+        payload_limit: Optional[ls_payload_limits.PayloadLimit] = None,
+        extract_media: Optional[bool] = None,
+        validate_traces: Optional[bool] = None,
     ) -> None:
         """Initialize a Client instance.
 
@@ -398,23 +704,120 @@ class Client:
         info: Optional[ls_schemas.LangSmithInfo]
             The information about the LangSmith API. If not provided, it will
             be fetched from the API.
-        api_urls: Optional[Dict[str, str]]
+        api_urls: Optional[Mapping[str, Union[str, ls_destinations.Destination]]]
             A dictionary of write API URLs and their corresponding API keys.
             Useful for multi-tenant setups. Data is only read from the first
             URL in the dictionary. However, ONLY Runs are written (POST and PATCH)
             to all URLs in the dictionary. Feedback, sessions, datasets, examples,
             annotation queues and evaluation results are only written to the first.
+            Give a URL a ls_destinations.Destination instead of an API key to
+            redact, sample or re-home the runs sent to it. Batches are sent to
+            each URL on threads of its own, so a URL that is slow or down
+            doesn't hold up the others. Once too many batches are waiting on
+            one, it fails new ones, which the tracing spool retries.
+        tracing_spool_dir: Optional[str]
+            A directory in which to persist auto-batched runs until the API has
+            accepted them. Runs that failed to send are retried, only to the API
+            URLs that didn't accept them, and runs still queued when the process
+            exited are replayed on the next start.
+            Defaults to the LANGSMITH_TRACING_SPOOL_DIR environment variable.
+            Only used when auto_batch_tracing is enabled.
+        tracing_spool_max_bytes: Optional[int]
+            The maximum size of the spool directory. Defaults to the
+            LANGSMITH_TRACING_SPOOL_MAX_BYTES environment variable, or 1GB.
+        tracing_spool_max_age: Optional[float]
+            How many seconds to keep retrying a spooled run before dropping it.
+            Runs an API URL rejects, e.g. because they are invalid, are never
+            retried. Defaults to the LANGSMITH_TRACING_SPOOL_MAX_AGE environment
+            variable, or 1 day.
+        tail_sampler: Optional[ls_sampling.TailSampler]
+            Buffer each trace until its root run ends and let a policy decide
+            whether to send it, e.g. to keep every trace that errored. Applied
+            after LANGSMITH_TRACING_SAMPLING_RATE. Requires auto_batch_tracing.
+        sampling_rules: Optional[Sequence[ls_sampling.SamplingRule]]
+            Ordered rules that set the sample rate of root runs by project, name,
+            run type, tags or metadata keys. The first matching rule wins, and
+            LANGSMITH_TRACING_SAMPLING_RATE applies when none match. Defaults to
+            the LANGSMITH_TRACING_SAMPLING_RULES environment variable.
+        exporters: Optional[Sequence[ls_exporters.TraceExporter]]
+            Where auto-batched traces are sent, e.g. a local file or the console.
+            Every batch is handed to each exporter, and with a tracing spool, only
+            retried with the exporters that failed to deliver it. Defaults to the
+            LangSmith API; include ls_exporters.LangSmithExporter() to keep
+            sending traces there alongside other exporters. Requires
+            auto_batch_tracing. If the LANGSMITH_TRACING_OFFLINE_DIR environment
+            variable is set, defaults to writing traces to files in that
+            directory, to be uploaded later with ls_offline.upload_traces.
+        compression: Optional[Literal["gzip", "zstd"]]
+            Compress the bodies of batch and multipart ingestion requests. Only
+            used if the API lists the method in its batch ingest config, falling
+            back to gzip, and size limits then apply to the compressed body.
+            zstd requires the zstandard package before Python 3.14. Defaults to
+            the LANGSMITH_TRACING_COMPRESSION environment variable.
+        tracing_queue_max_items: Optional[int]
+            The maximum number of runs held in memory by the tracing queue.
+            Defaults to the LANGSMITH_TRACING_QUEUE_MAX_ITEMS environment
+            variable, or no limit.
+        tracing_queue_max_bytes: Optional[int]
+            The maximum serialized size of the runs held in memory by the
+            tracing queue. Defaults to the LANGSMITH_TRACING_QUEUE_MAX_BYTES
+            environment variable, or no limit.
+        tracing_queue_overflow_policy: Optional[OverflowPolicy]
+            What to do with a run that doesn't fit in the tracing queue:
+            "drop_newest" (the default), "drop_oldest", "block" the caller until
+            there is room, or "spill" it to a temporary file in the
+            LANGSMITH_TRACING_QUEUE_SPILL_DIR directory, to be sent once the
+            queue drains. Drops are counted on client.tracing_queue. Defaults to
+            the LANGSMITH_TRACING_QUEUE_OVERFLOW_POLICY environment variable.
+        circuit_breaker: Optional[ls_circuit_breaker.CircuitBreaker]
+            Stop sending batches of runs to an API URL while it is down,
+            instead of retrying every batch. Runs that aren't sent are kept in
+            the tracing spool if there is one, and dropped otherwise. With
+            several write API URLs, each gets a breaker configured like this
+            one, so only the URLs that are down are paused. Defaults to the
+            LANGSMITH_TRACING_CIRCUIT_BREAKER_THRESHOLD environment variable,
+            or no circuit breaker.
+        payload_limit: Optional[ls_payload_limits.PayloadLimit]
+            Move input and output values over a size into attachments, or
+            truncate or summarize them, so oversized runs can still be ingested.
+            Defaults to the LANGSMITH_TRACING_PAYLOAD_MAX_BYTES environment
+            variable, or no limit.
+        extract_media: Optional[bool]
+            Move base64 images and audio inlined in run inputs and outputs, such
+            as the data URLs of multimodal messages, into run attachments, and
+            leave an ``attachment://`` reference in their place. Only applies to
+            runs sent to the multipart endpoint. Defaults to the
+            LANGSMITH_TRACING_EXTRACT_MEDIA environment variable, or False.
+        validate_traces: Optional[bool]
+            Check each run passed to create_run, update_run, batch_ingest_runs
+            or multipart_ingest before it is sent, against the runs of its trace
+            sent before it, and log a warning with the path of the run in its
+            trace for each mistake found, such as a missing parent, a
+            dotted_order that doesn't match the run's ancestry, or an update
+            before the create. Meant for debugging hand-built runs. Defaults to
+            the LANGSMITH_TRACING_VALIDATE environment variable, or False.
 
         Raises:
         ------
         LangSmithUserError
             If the API key is not provided when using the hosted service.
             If both api_url and api_urls are provided.
+            If tail_sampler or exporters are provided without auto_batch_tracing.
+            If compression is not a supported method.
+            If tracing_queue_overflow_policy is not a supported policy.
         """
         if api_url and api_urls:
             raise ls_utils.LangSmithUserError(
                 "You cannot provide both api_url and api_urls."
             )
+        if tail_sampler is not None and not auto_batch_tracing:
+            raise ls_utils.LangSmithUserError(
+                "Tail sampling requires auto_batch_tracing to be enabled."
+            )
+        if exporters is not None and not auto_batch_tracing:
+            raise ls_utils.LangSmithUserError(
+                "Trace exporters require auto_batch_tracing to be enabled."
+            )
 
         if (
             os.getenv("LANGSMITH_ENDPOINT") or os.getenv("LANGCHAIN_ENDPOINT")
@@ -424,19 +827,41 @@ class Client:
                 "and LANGSMITH_RUNS_ENDPOINTS."
             )
 
+        compression = compression or ls_utils.get_env_var(  # type: ignore[assignment]
+            "TRACING_COMPRESSION"
+        )
+        if compression and compression not in _compression.COMPRESSION_METHODS:
+            raise ls_utils.LangSmithUserError(
+                f"Unsupported compression method {compression!r}. Expected one of "
+                f"{', '.join(_compression.COMPRESSION_METHODS)}."
+            )
+        self._compression = compression
+        # The compressed size of recent requests relative to their raw size
+        self._compression_ratio = 1.0
         self.tracing_sample_rate = _get_tracing_sampling_rate()
-        self._filtered_post_uuids: set[uuid.UUID] = set()
-        self._write_api_urls: Mapping[str, Optional[str]] = _get_write_api_urls(
-            api_urls
+        self._sampling_rules = (
+            list(sampling_rules)
+            if sampling_rules is not None
+            else ls_sampling.get_sampling_rules_from_env()
         )
-        if self._write_api_urls:
-            self.api_url = next(iter(self._write_api_urls))
-            self.api_key: Optional[str] = self._write_api_urls[self.api_url]
+        self._filtered_post_uuids: set[uuid.UUID] = set()
+        self._write_destinations = _get_write_api_urls(api_urls)
+        if self._write_destinations:
+            self.api_url = next(iter(self._write_destinations))
+            self.api_key: Optional[str] = self._write_destinations[
+                self.api_url
+            ].api_key
         else:
             self.api_url = ls_utils.get_api_url(api_url)
             self.api_key = ls_utils.get_api_key(api_key)
             _validate_api_key_if_hosted(self.api_url, self.api_key)
-            self._write_api_urls = {self.api_url: self.api_key}
+            self._write_destinations = {
+                self.api_url: ls_destinations.Destination(api_key=self.api_key)
+            }
+        self._write_api_urls: Mapping[str, Optional[str]] = {
+            url: destination.api_key
+            for url, destination in self._write_destinations.items()
+        }
         self.retry_config = retry_config or _default_retry_config()
         self.timeout_ms = (
             (timeout_ms, timeout_ms)
@@ -456,29 +881,83 @@ class Client:
         weakref.finalize(self, close_session, self.session)
         atexit.register(close_session, session_)
         # Initialize auto batching
+        self._tracing_spool: Optional[TracingSpool] = None
+        self._tail_sampler = tail_sampler
+        self._tracing_stats = TracingStatsRecorder()
+        self._tracing_thread: Optional[threading.Thread] = None
+        self._tracing_thread_lock = threading.Lock()
+        self._tracing_shutdown_deadline: Optional[float] = None
+        if circuit_breaker is None:
+            circuit_breaker = ls_circuit_breaker.get_circuit_breaker_from_env()
+        self._circuit_breakers: Dict[str, ls_circuit_breaker.CircuitBreaker] = (
+            {
+                url: circuit_breaker if i == 0 else circuit_breaker._copy()
+                for i, url in enumerate(self._write_destinations)
+            }
+            if circuit_breaker is not None
+            else {}
+        )
+        # Batches are sent to each URL on a lane of its own
+        self._send_lanes: Dict[str, SendLane] = (
+            {url: SendLane() for url in self._write_destinations}
+            if len(self._write_destinations) > 1
+            else {}
+        )
+        self._payload_limit = (
+            payload_limit
+            if payload_limit is not None
+            else ls_payload_limits.get_payload_limit_from_env()
+        )
+        self._extract_media = (
+            extract_media
+            if extract_media is not None
+            else ls_utils.get_env_var("TRACING_EXTRACT_MEDIA") == "true"
+        )
+        if validate_traces is None:
+            validate_traces = ls_utils.get_env_var("TRACING_VALIDATE") == "true"
+        self._trace_validator = (
+            _trace_validation.TraceValidator() if validate_traces else None
+        )
+        if exporters is None and auto_batch_tracing:
+            if offline_dir := ls_utils.get_env_var("TRACING_OFFLINE_DIR"):
+                exporters = [ls_offline.OfflineExporter(offline_dir)]
+        self._exporters = list(exporters) if exporters is not None else None
+        for exporter in self._exporters or ():
+            if isinstance(exporter, ls_exporters.LangSmithExporter):
+                exporter._bind(self)
         if auto_batch_tracing:
-            self.tracing_queue: Optional[PriorityQueue] = PriorityQueue()
-
-            threading.Thread(
-                target=_tracing_control_thread_func,
-                # arg must be a weakref to self to avoid the Thread object
-                # preventing garbage collection of the Client object
-                args=(weakref.ref(self),),
-            ).start()
+            self.tracing_queue: Optional[BoundedTracingQueue] = (
+                _create_tracing_queue(
+                    tracing_queue_max_items,
+                    tracing_queue_max_bytes,
+                    tracing_queue_overflow_policy,
+                )
+            )
+            tracing_spool_dir = tracing_spool_dir or ls_utils.get_env_var(
+                "TRACING_SPOOL_DIR"
+            )
+            if tracing_spool_dir:
+                spool_max_bytes = tracing_spool_max_bytes or ls_utils.get_env_var(
+                    "TRACING_SPOOL_MAX_BYTES"
+                )
+                spool_max_age = tracing_spool_max_age or ls_utils.get_env_var(
+                    "TRACING_SPOOL_MAX_AGE"
+                )
+                spool_kwargs: Dict[str, Any] = {}
+                if spool_max_bytes:
+                    spool_kwargs["max_bytes"] = int(spool_max_bytes)
+                if spool_max_age:
+                    spool_kwargs["max_age"] = float(spool_max_age)
+                self._tracing_spool = TracingSpool(tracing_spool_dir, **spool_kwargs)
+                self.tracing_queue.spool = self._tracing_spool
+
+            _TRACING_CLIENTS.add(self)
+            _register_tracing_shutdown_at_exit(self)
+            self._start_tracing_thread()
         else:
             self.tracing_queue = None
 
-        # Mount the HTTPAdapter with the retry configuration.
-        adapter = _LangSmithHttpAdapter(
-            max_retries=self.retry_config,
-            blocksize=_BLOCKSIZE_BYTES,
-            # We need to set the pool_maxsize to a value greater than the
-            # number of threads used for batch tracing, plus 1 for other
-            # requests.
-            pool_maxsize=_AUTO_SCALE_UP_NTHREADS_LIMIT + 1,
-        )
-        self.session.mount("http://", adapter)
-        self.session.mount("https://", adapter)
+        self._mount_http_adapter()
         self._get_data_type_cached = functools.lru_cache(maxsize=10)(
             self._get_data_type
         )
@@ -496,6 +975,69 @@ class Client:
 
         self._settings: Union[ls_schemas.LangSmithSettings, None] = None
 
+    def _mount_http_adapter(self) -> None:
+        # Mount the HTTPAdapter with the retry configuration.
+        adapter = _LangSmithHttpAdapter(
+            max_retries=self.retry_config,
+            blocksize=_BLOCKSIZE_BYTES,
+            # We need to set the pool_maxsize to a value greater than the
+            # number of threads used for batch tracing, plus 1 for other
+            # requests.
+            pool_maxsize=_AUTO_SCALE_UP_NTHREADS_LIMIT + 1,
+        )
+        self.session.mount("http://", adapter)
+        self.session.mount("https://", adapter)
+
+    def _start_tracing_thread(self) -> None:
+        with self._tracing_thread_lock:
+            if self._tracing_thread is not None:
+                return
+            self._tracing_thread = threading.Thread(
+                target=_tracing_control_thread_func,
+                # arg must be a weakref to self to avoid the Thread object
+                # preventing garbage collection of the Client object
+                args=(weakref.ref(self),),
+                # shutdown() waits for it at exit, for at most the timeout
+                daemon=True,
+            )
+            self._tracing_thread.start()
+
+    def _reset_tracing_after_fork(self) -> None:
+        """Give a forked child its own tracing state.
+
+        The child inherits the parent's queue, spool and sampler buffers, but
+        none of its threads. Items queued before the fork stay with the
+        parent, which keeps sending them, so the child starts empty and only
+        starts a tracing thread once it traces something. Locks and pooled
+        connections that may have been in use at the time of the fork are
+        replaced as well.
+        """
+        queue = self.tracing_queue
+        if queue is None:
+            return
+        self.tracing_queue = _create_tracing_queue(
+            queue.max_items, queue.max_bytes, queue.overflow_policy
+        )
+        self._tracing_thread = None
+        self._tracing_thread_lock = threading.Lock()
+        self._tracing_shutdown_deadline = None
+        self._tracing_stats = TracingStatsRecorder()
+        if (spool := self._tracing_spool) is not None:
+            # The parent still owns the inherited segments, so don't close them
+            self._tracing_spool = TracingSpool(
+                spool.directory,
+                max_bytes=spool.max_bytes,
+                max_age=spool.max_age,
+                segment_bytes=spool.segment_bytes,
+                retry_interval=spool.retry_interval,
+            )
+            self.tracing_queue.spool = self._tracing_spool
+        if self._tail_sampler is not None:
+            self._tail_sampler._reset_after_fork()
+        for breaker in self._circuit_breakers.values():
+            breaker._reset_after_fork()
+        self._mount_http_adapter()
+
     def _repr_html_(self) -> str:
         """Return an HTML representation of the instance with a link to the URL.
 
@@ -612,6 +1154,7 @@ class Client:
         to_ignore: Optional[Sequence[Type[BaseException]]] = None,
         handle_response: Optional[Callable[[requests.Response, int], Any]] = None,
         _context: str = "",
+        _on_retry: Optional[Callable[[], None]] = None,
         **kwargs: Any,
     ) -> requests.Response:
         """Send a request with retries.
@@ -686,6 +1229,8 @@ class Client:
         to_ignore_: Tuple[Type[BaseException], ...] = (*(to_ignore or ()),)
         response = None
         for idx in range(stop_after_attempt):
+            if idx and _on_retry is not None:
+                _on_retry()
             try:
                 try:
                     with ls_utils.filter_logs(_urllib3_logger, logging_filters):
@@ -1066,7 +1611,8 @@ class Client:
         Args:
             run (Union[ls_schemas.Run, dict]): The run object to transform.
             update (bool, optional): Whether the payload is for an "update" event.
-            copy (bool, optional): Whether to deepcopy run inputs/outputs.
+            copy (bool, optional): Whether to snapshot run inputs/outputs, for
+                runs that are sent later.
             attachments_collector (Optional[dict[str, ls_schemas.Attachments]]):
                 A dictionary to collect attachments. If not passed, attachments
                 will be dropped.
@@ -1081,16 +1627,18 @@ class Client:
         else:
             run_create = cast(dict, run)
         if "id" not in run_create:
-            run_create["id"] = uuid.uuid4()
+            run_create["id"] = _uuid.new_run_id()
         elif isinstance(run_create["id"], str):
             run_create["id"] = uuid.UUID(run_create["id"])
+        # Hide callables and the anonymizer are handed their own copy
+        copy = copy and not self._anonymizer
         if "inputs" in run_create and run_create["inputs"] is not None:
-            if copy:
-                run_create["inputs"] = ls_utils.deepish_copy(run_create["inputs"])
+            if copy and self._hide_inputs is False:
+                run_create["inputs"] = ls_utils.snapshot(run_create["inputs"])
             run_create["inputs"] = self._hide_run_inputs(run_create["inputs"])
         if "outputs" in run_create and run_create["outputs"] is not None:
-            if copy:
-                run_create["outputs"] = ls_utils.deepish_copy(run_create["outputs"])
+            if copy and self._hide_outputs is False:
+                run_create["outputs"] = ls_utils.snapshot(run_create["outputs"])
             run_create["outputs"] = self._hide_run_outputs(run_create["outputs"])
         if not update and not run_create.get("start_time"):
             run_create["start_time"] = datetime.datetime.now(datetime.timezone.utc)
@@ -1107,6 +1655,19 @@ class Client:
                 # Drop graph
                 run_create["serialized"].pop("graph", None)
 
+        # Move inline media into attachments, if they can be sent
+        if self._extract_media and attachments_collector is not None:
+            for field in ("inputs", "outputs"):
+                if run_create.get(field):
+                    run_create[field], media = _media.extract_inline_media(
+                        field, run_create[field]
+                    )
+                    if media:
+                        run_create["attachments"] = {
+                            **(run_create.get("attachments") or {}),
+                            **media,
+                        }
+
         # Collect or drop attachments
         if attachments := run_create.pop("attachments", None):
             if attachments_collector is not None:
@@ -1168,7 +1729,7 @@ class Client:
     def _filter_for_sampling(
         self, runs: Iterable[dict], *, patch: bool = False
     ) -> list[dict]:
-        if self.tracing_sample_rate is None:
+        if self.tracing_sample_rate is None and not self._sampling_rules:
             return list(runs)
 
         if patch:
@@ -1183,16 +1744,19 @@ class Client:
         else:
             sampled = []
             for run in runs:
-                if (
-                    # Child run
-                    run["id"] != run.get("trace_id")
-                    # Whose trace is included
-                    and run.get("trace_id") not in self._filtered_post_uuids
-                    # Or a root that's randomly sampled
-                ) or random.random() < self.tracing_sample_rate:
+                if run["id"] != run.get("trace_id"):
+                    # Child runs follow the decision made for their trace
+                    keep = run.get("trace_id") not in self._filtered_post_uuids
+                else:
+                    # Root runs are randomly sampled
+                    keep = random.random() < ls_sampling.get_sample_rate(
+                        self._sampling_rules, run, self.tracing_sample_rate
+                    )
+                if keep:
                     sampled.append(run)
                 else:
                     self._filtered_post_uuids.add(_as_uuid(run["id"]))
+                    self._tracing_stats.increment("runs_filtered")
             return sampled
 
     def create_run(
@@ -1240,33 +1804,123 @@ class Client:
         }
         if not self._filter_for_sampling([run_create]):
             return
-        run_create = self._run_transform(run_create, copy=True)
-        if revision_id is not None:
-            run_create["extra"]["metadata"]["revision_id"] = revision_id
-        if (
+        enqueue = (
             self.tracing_queue is not None
             # batch ingest requires trace_id and dotted_order to be set
             and run_create.get("trace_id") is not None
             and run_create.get("dotted_order") is not None
-        ):
-            return self.tracing_queue.put(
+        )
+        # Runs sent right away are serialized before the caller can change
+        # their inputs, so only queued runs need a snapshot of them
+        run_create = self._run_transform(run_create, copy=enqueue)
+        if revision_id is not None:
+            run_create["extra"]["metadata"]["revision_id"] = revision_id
+        self._validate_runs([run_create])
+        if enqueue:
+            return self._enqueue_tracing_item(
                 TracingQueueItem(run_create["dotted_order"], "create", run_create)
             )
         self._insert_runtime_env([run_create])
         self._create_run(run_create)
 
-    def _create_run(self, run_create: dict):
-        for api_url, api_key in self._write_api_urls.items():
-            headers = {**self._headers, X_API_KEY: api_key}
-            self.request_with_retries(
-                "POST",
-                f"{api_url}/runs",
-                request_kwargs={
-                    "data": _dumps_json(run_create),
-                    "headers": headers,
-                },
-                to_ignore=(ls_utils.LangSmithConflictError,),
+    def _validate_runs(
+        self,
+        runs: Iterable[Union[ls_schemas.Run, ls_schemas.RunLikeDict, Dict]],
+        *,
+        update: bool = False,
+    ) -> None:
+        if self._trace_validator is None:
+            return
+        for run in runs:
+            run_dict = run.dict() if isinstance(run, ls_schemas.Run) else run
+            for problem in self._trace_validator.check(run_dict, update=update):
+                logger.warning(f"Invalid run {problem}")
+
+    def _enqueue_tracing_item(self, item: TracingQueueItem) -> None:
+        if self._tail_sampler is not None:
+            self._put_tracing_items(self._tail_sampler.add(item))
+        else:
+            self._put_tracing_items([item])
+
+    def _put_tracing_items(
+        self, items: Iterable[TracingQueueItem], *, requeue: bool = False
+    ) -> None:
+        tracing_queue = cast(BoundedTracingQueue, self.tracing_queue)
+        if self._tracing_thread is None:
+            # Forked processes start their tracing thread on first use
+            self._start_tracing_thread()
+        for item in items:
+            if self._tracing_spool is not None:
+                self._tracing_spool.append(item)
+            if requeue:
+                tracing_queue.requeue(item)
+            else:
+                tracing_queue.put(item)
+                self._tracing_stats.increment("runs_queued")
+
+    def _exports_to_api(self) -> bool:
+        return self._exporters is None or any(
+            isinstance(exporter, ls_exporters.LangSmithExporter)
+            for exporter in self._exporters
+        )
+
+    def _delivery_targets(self) -> List[str]:
+        """Name where auto-batched runs go: the exporters, or else the API URLs.
+
+        The names of exporters are stable across restarts, so that a tracing
+        spool can tell which ones a replayed run was already delivered to.
+        """
+        if self._exporters is not None:
+            return [
+                f"{type(exporter).__name__}:{i}"
+                for i, exporter in enumerate(self._exporters)
+            ]
+        return list(self._write_destinations)
+
+    def _export_batch(
+        self,
+        create: Sequence[dict],
+        update: Sequence[dict],
+        feedback: Sequence[Any],
+        *,
+        skip: Container[str] = (),
+    ) -> BatchDelivery:
+        """Hand a batch to every exporter but those named in ``skip``."""
+        delivery = BatchDelivery()
+        exporters = cast(List[ls_exporters.TraceExporter], self._exporters)
+        for target, exporter in zip(self._delivery_targets(), exporters):
+            if target in skip:
+                continue
+            # Ingestion pops fields off the run dicts, so each exporter gets
+            # its own copies.
+            batch = ls_exporters.TraceBatch(
+                create=[dict(run) for run in create],
+                update=[dict(run) for run in update],
+                feedback=list(feedback),
             )
+            try:
+                delivered = bool(exporter.export(batch))
+            except Exception as e:
+                delivered = False
+                logger.warning(
+                    f"Failed to export traces with {type(exporter).__name__}: {e!r}"
+                )
+            delivery.record(target, "delivered" if delivered else "retry")
+        return delivery
+
+    def _create_run(self, run_create: dict):
+        for api_url, destination in self._write_destinations.items():
+            for run in destination.apply([run_create]):
+                headers = {**self._headers, X_API_KEY: destination.api_key}
+                self.request_with_retries(
+                    "POST",
+                    f"{api_url}/runs",
+                    request_kwargs={
+                        "data": _dumps_json(run),
+                        "headers": headers,
+                    },
+                    to_ignore=(ls_utils.LangSmithConflictError,),
+                )
 
     def _hide_run_inputs(self, inputs: dict):
         if self._hide_inputs is True:
@@ -1276,7 +1930,7 @@ class Client:
             return self._anonymizer(json_inputs)
         if self._hide_inputs is False:
             return inputs
-        return self._hide_inputs(inputs)
+        return self._hide_inputs(ls_utils.snapshot(inputs))
 
     def _hide_run_outputs(self, outputs: dict):
         if self._hide_outputs is True:
@@ -1286,7 +1940,7 @@ class Client:
             return self._anonymizer(json_outputs)
         if self._hide_outputs is False:
             return outputs
-        return self._hide_outputs(outputs)
+        return self._hide_outputs(ls_utils.snapshot(outputs))
 
     def batch_ingest_runs(
         self,
@@ -1322,8 +1976,27 @@ class Client:
             - The run objects MUST contain the dotted_order and trace_id fields
                 to be accepted by the API.
         """
+        self._validate_runs(create or EMPTY_SEQ)
+        self._validate_runs(update or EMPTY_SEQ, update=True)
+        self._batch_ingest_runs(
+            create=create, update=update, pre_sampled=pre_sampled
+        ).wait()
+
+    def _batch_ingest_runs(
+        self,
+        create: Optional[
+            Sequence[Union[ls_schemas.Run, ls_schemas.RunLikeDict, Dict]]
+        ] = None,
+        update: Optional[
+            Sequence[Union[ls_schemas.Run, ls_schemas.RunLikeDict, Dict]]
+        ] = None,
+        *,
+        pre_sampled: bool = False,
+        skip: Container[str] = (),
+    ) -> BatchDelivery:
+        """Batch ingest runs to every write API URL but those named in ``skip``."""
         if not create and not update:
-            return
+            return BatchDelivery()
         # transform and convert to dicts
         create_dicts = [self._run_transform(run) for run in create or EMPTY_SEQ]
         update_dicts = [
@@ -1363,18 +2036,35 @@ class Client:
                 "patch": self._filter_for_sampling(update_dicts, patch=True),
             }
         if not raw_body["post"] and not raw_body["patch"]:
-            return
+            return BatchDelivery()
 
         self._insert_runtime_env(raw_body["post"] + raw_body["patch"])
-        info = self.info
+        return self._send_to_write_destinations(
+            raw_body["post"],
+            raw_body["patch"],
+            lambda post, patch, api_url, destination: self._send_batch_ingest_runs(
+                post, patch, api_urls={api_url: destination.api_key}
+            ),
+            skip=skip,
+        )
 
-        size_limit_bytes = (info.batch_ingest_config or {}).get(
-            "size_limit_bytes"
-        ) or _SIZE_LIMIT_BYTES
+    def _send_batch_ingest_runs(
+        self,
+        create_dicts: List[dict],
+        update_dicts: List[dict],
+        *,
+        api_urls: Mapping[str, Optional[str]],
+    ) -> Outcome:
+        raw_body = {"post": create_dicts, "patch": update_dicts}
+        size_limit_bytes = self._get_size_limit_bytes()
+        if self._get_compression():
+            # The limit applies to the compressed body, so fit as many runs in a
+            # request as the compression of recent requests allows
+            size_limit_bytes = int(size_limit_bytes / self._compression_ratio)
         # Get orjson fragments to avoid going over the max request size
         partial_body = {
-            "post": [_dumps_json(run) for run in raw_body["post"]],
-            "patch": [_dumps_json(run) for run in raw_body["patch"]],
+            "post": [self._dumps_limited_run(run) for run in raw_body["post"]],
+            "patch": [self._dumps_limited_run(run) for run in raw_body["patch"]],
         }
         ids = {
             "post": [
@@ -1387,6 +2077,7 @@ class Client:
             ],
         }
 
+        outcome: Outcome = "delivered"
         body_chunks: DefaultDict[str, list] = collections.defaultdict(list)
         context_ids: DefaultDict[str, list] = collections.defaultdict(list)
         body_size = 0
@@ -1395,9 +2086,13 @@ class Client:
             ids_ = collections.deque(ids[key])
             while body:
                 if body_size > 0 and body_size + len(body[0]) > size_limit_bytes:
-                    self._post_batch_ingest_runs(
-                        orjson.dumps(body_chunks),
-                        _context=f"\n{key}: {'; '.join(context_ids[key])}",
+                    outcome = combine_outcomes(
+                        outcome,
+                        self._post_batch_ingest_runs(
+                            orjson.dumps(body_chunks),
+                            _context=f"\n{key}: {'; '.join(context_ids[key])}",
+                            api_urls=api_urls,
+                        ),
                     )
                     body_size = 0
                     body_chunks.clear()
@@ -1407,12 +2102,124 @@ class Client:
                 context_ids[key].append(ids_.popleft())
         if body_size:
             context = "; ".join(f"{k}: {'; '.join(v)}" for k, v in context_ids.items())
-            self._post_batch_ingest_runs(
-                orjson.dumps(body_chunks), _context="\n" + context
+            outcome = combine_outcomes(
+                outcome,
+                self._post_batch_ingest_runs(
+                    orjson.dumps(body_chunks),
+                    _context="\n" + context,
+                    api_urls=api_urls,
+                ),
             )
+        return outcome
+
+    def _dumps_limited_run(self, run: dict) -> bytes:
+        """Serialize a run for the batch endpoint, shrinking oversized fields."""
+        serialized = _dumps_json(run)
+        limit = self._payload_limit
+        if limit is None or len(serialized) <= limit.max_bytes:
+            return serialized
+        run = dict(run)
+        for field in ("inputs", "outputs"):
+            if run.get(field) is not None:
+                # The batch endpoint doesn't take attachments
+                run[field], _ = limit.apply(field, run[field], can_attach=False)
+        return _dumps_json(run)
+
+    def _send_to_write_destinations(
+        self,
+        create: List[dict],
+        update: List[dict],
+        send: Callable[
+            [List[dict], List[dict], str, ls_destinations.Destination], Outcome
+        ],
+        *,
+        skip: Container[str] = (),
+    ) -> BatchDelivery:
+        """Send runs to every write API URL, applying each one's policies.
+
+        URLs named in ``skip``, and those whose circuit breaker is open, are
+        skipped. With several URLs, the runs are sent on each URL's lane, so
+        the returned delivery may still be in progress.
+        """
+
+        def send_unless_paused(
+            create: List[dict],
+            update: List[dict],
+            url: str,
+            destination: ls_destinations.Destination,
+        ) -> Outcome:
+            breaker = self._circuit_breakers.get(url)
+            if breaker is not None and not breaker.allow_request():
+                # The URL is down, so don't spend retries and threads on it
+                breaker.record_short_circuited(len(create) + len(update))
+                self._tracing_stats.increment(
+                    "runs_short_circuited", len(create) + len(update)
+                )
+                return "retry"
+            return send(create, update, url, destination)
+
+        delivery = BatchDelivery()
+        for url, destination in self._write_destinations.items():
+            if url in skip:
+                continue
+            job = functools.partial(
+                send_unless_paused,
+                destination.apply(create),
+                destination.apply(update),
+                url,
+                destination,
+            )
+            if (lane := self._send_lanes.get(url)) is None:
+                delivery.record(url, job())
+            elif (future := lane.submit(job)) is not None:
+                delivery.track(url, future)
+            else:
+                ls_utils.log_once(
+                    logging.WARNING,
+                    f"Too many batches are waiting to be sent to {url}."
+                    " Failing new ones until it catches up.",
+                )
+                delivery.record(url, "retry")
+        return delivery
+
+    def _get_size_limit_bytes(self) -> int:
+        return (self.info.batch_ingest_config or {}).get(
+            "size_limit_bytes"
+        ) or _SIZE_LIMIT_BYTES
 
-    def _post_batch_ingest_runs(self, body: bytes, *, _context: str):
-        for api_url, api_key in self._write_api_urls.items():
+    def _get_compression(self) -> Optional[str]:
+        if not self._compression:
+            return None
+        return _compression.negotiate(
+            self._compression,
+            (self.info.batch_ingest_config or {}).get("supported_compression_methods"),
+        )
+
+    def _compress_request(self, body: bytes) -> Tuple[bytes, Dict[str, str]]:
+        """Compress a request body if the API accepts compressed requests.
+
+        Returns the body to send and the headers describing its encoding.
+        """
+        method = self._get_compression()
+        if method is None or not body:
+            return body, {}
+        compressed = _compression.compress(body, method)
+        ratio = len(compressed) / len(body)
+        # Adapt quickly to batches that compress worse than earlier ones
+        self._compression_ratio = max(ratio, (self._compression_ratio + ratio) / 2)
+        return compressed, {"Content-Encoding": method}
+
+    def _post_batch_ingest_runs(
+        self,
+        body: bytes,
+        *,
+        _context: str,
+        api_urls: Optional[Mapping[str, Optional[str]]] = None,
+    ) -> Outcome:
+        outcome: Outcome = "delivered"
+        body, encoding_headers = self._compress_request(body)
+        for api_url, api_key in (api_urls or self._write_api_urls).items():
+            started = time.perf_counter()
             try:
                 self.request_with_retries(
                     "POST",
@@ -1421,20 +2228,26 @@ class Client:
                         "data": body,
                         "headers": {
                             **self._headers,
+                            **encoding_headers,
                             X_API_KEY: api_key,
                         },
                     },
                     to_ignore=(ls_utils.LangSmithConflictError,),
                     stop_after_attempt=3,
                     _context=_context,
+                    _on_retry=self._count_ingest_retry,
                 )
+                self._record_ingest_request(api_url, started)
             except Exception as e:
+                outcome = combine_outcomes(outcome, outcome_of(e))
+                self._record_ingest_request(api_url, started, error=e)
                 try:
                     exc_desc_lines = traceback.format_exception_only(type(e), e)
                     exc_desc = "".join(exc_desc_lines).rstrip()
                     logger.warning(f"Failed to batch ingest runs: {exc_desc}")
                 except Exception:
                     logger.warning(f"Failed to batch ingest runs: {repr(e)}")
+        return outcome
 
     def multipart_ingest(
         self,
@@ -1471,8 +2284,28 @@ class Client:
             - The run objects MUST contain the dotted_order and trace_id fields
                 to be accepted by the API.
         """
+        self._validate_runs(create or EMPTY_SEQ)
+        self._validate_runs(update or EMPTY_SEQ, update=True)
+        self._multipart_ingest(
+            create=create, update=update, feedback=feedback, pre_sampled=pre_sampled
+        ).wait()
+
+    def _multipart_ingest(
+        self,
+        create: Optional[
+            Sequence[Union[ls_schemas.Run, ls_schemas.RunLikeDict, Dict]]
+        ] = None,
+        update: Optional[
+            Sequence[Union[ls_schemas.Run, ls_schemas.RunLikeDict, Dict]]
+        ] = None,
+        feedback: Optional[Sequence[Union[ls_schemas.Feedback, Dict]]] = None,
+        *,
+        pre_sampled: bool = False,
+        skip: Container[str] = (),
+    ) -> BatchDelivery:
+        """Multipart ingest runs to every write API URL but those in ``skip``."""
         if not (create or update or feedback):
-            return
+            return BatchDelivery()
         # transform and convert to dicts
         all_attachments: Dict[str, ls_schemas.Attachments] = {}
         create_dicts = [
@@ -1504,105 +2337,115 @@ class Client:
             else:
                 del run
         # combine post and patch dicts where possible
-        if update_dicts and create_dicts:
-            create_by_id = {run["id"]: run for run in create_dicts}
-            standalone_updates: list[dict] = []
-            for run in update_dicts:
-                if run["id"] in create_by_id:
-                    for k, v in run.items():
-                        if v is not None:
-                            create_by_id[run["id"]][k] = v
-                else:
-                    standalone_updates.append(run)
-            else:
-                del run
-            update_dicts = standalone_updates
+        update_dicts = _combine_run_updates(create_dicts, update_dicts)
         # filter out runs that are not sampled
         if not pre_sampled:
             create_dicts = self._filter_for_sampling(create_dicts)
             update_dicts = self._filter_for_sampling(update_dicts, patch=True)
         if not create_dicts and not update_dicts and not feedback_dicts:
-            return
+            return BatchDelivery()
         # insert runtime environment
         self._insert_runtime_env(create_dicts)
         self._insert_runtime_env(update_dicts)
-        # send the runs in multipart requests
-        acc_context: List[str] = []
-        acc_parts: MultipartParts = []
-        for event, payloads in (
-            ("post", create_dicts),
-            ("patch", update_dicts),
-            ("feedback", feedback_dicts),
-        ):
-            for payload in payloads:
-                # collect fields to be sent as separate parts
-                fields = [
-                    ("inputs", payload.pop("inputs", None)),
-                    ("outputs", payload.pop("outputs", None)),
-                    ("events", payload.pop("events", None)),
-                    ("feedback", payload.pop("feedback", None)),
-                ]
-                # encode the main run payload
-                payloadb = _dumps_json(payload)
-                acc_parts.append(
-                    (
-                        f"{event}.{payload['id']}",
-                        (
-                            None,
-                            payloadb,
-                            "application/json",
-                            {"Content-Length": str(len(payloadb))},
-                        ),
+        if len(self._write_destinations) > 1:
+            # destinations are sent to concurrently, and can't share a stream
+            all_attachments = {
+                run_id: read_streams(attachments)
+                for run_id, attachments in all_attachments.items()
+            }
+
+        # send the runs in multipart requests, applying each URL's policies
+        def send(
+            create: List[dict],
+            update: List[dict],
+            api_url: str,
+            destination: ls_destinations.Destination,
+        ) -> Outcome:
+            if not create and not update and not feedback_dicts:
+                return "delivered"
+            acc_parts, acc_context = _serialize_multipart_parts(
+                create,
+                update,
+                [dict(f) for f in feedback_dicts],
+                dict(all_attachments) if destination.sends_attachments else {},
+                self._payload_limit,
+            )
+            # send the requests, keeping large attachments out of each other's
+            return combine_outcomes(
+                *[
+                    self._send_multipart_req(
+                        chunk,
+                        _context=acc_context,
+                        api_urls={api_url: destination.api_key},
                     )
-                )
-                # encode the fields we collected
-                for key, value in fields:
-                    if value is None:
-                        continue
-                    valb = _dumps_json(value)
-                    acc_parts.append(
-                        (
-                            f"{event}.{payload['id']}.{key}",
-                            (
-                                None,
-                                valb,
-                                "application/json",
-                                {"Content-Length": str(len(valb))},
-                            ),
-                        ),
+                    for chunk in _chunk_multipart_parts(
+                        acc_parts, self._get_size_limit_bytes()
                     )
-                # encode the attachments
-                if attachments := all_attachments.pop(payload["id"], None):
-                    for n, (ct, ba) in attachments.items():
-                        acc_parts.append(
-                            (
-                                f"attachment.{payload['id']}.{n}",
-                                (None, ba, ct, {"Content-Length": str(len(ba))}),
-                            )
-                        )
-                # compute context
-                acc_context.append(
-                    f"trace={payload.get('trace_id')},id={payload.get('id')}"
-                )
-        # send the request
-        self._send_multipart_req(acc_parts, _context="; ".join(acc_context))
+                ]
+            )
+
+        return self._send_to_write_destinations(
+            create_dicts, update_dicts, send, skip=skip
+        )
 
     def _send_multipart_req(
-        self, parts: MultipartParts, *, _context: str, attempts: int = 3
-    ):
-        for api_url, api_key in self._write_api_urls.items():
+        self,
+        parts: MultipartParts,
+        *,
+        _context: str,
+        attempts: int = 3,
+        api_urls: Optional[Mapping[str, Optional[str]]] = None,
+    ) -> Outcome:
+        encoder = MultipartEncoder(parts, boundary=BOUNDARY)
+        content_type = encoder.content_type
+        # The compiled core, if installed, encodes parts held in memory up front
+        body: Optional[bytes] = _native.encode_multipart(parts, BOUNDARY)
+        encoding_headers: Dict[str, str] = {}
+        if self._get_compression():
+            if body is None:
+                body = encoder.to_string()
+                _rewind_multipart_parts(parts)
+            body, encoding_headers = self._compress_request(body)
+            # Split requests whose compressed body is over the limit between runs
+            if len(body) > self._get_size_limit_bytes() and (
+                halves := _split_multipart_parts(parts)
+            ):
+                return combine_outcomes(
+                    *[
+                        self._send_multipart_req(
+                            half,
+                            _context=_context,
+                            attempts=attempts,
+                            api_urls=api_urls,
+                        )
+                        for half in halves
+                    ]
+                )
+        outcome: Outcome = "delivered"
+        for api_url, api_key in (api_urls or self._write_api_urls).items():
+            started = time.perf_counter()
+            error: Optional[Exception] = None
             for idx in range(1, attempts + 1):
+                if idx > 1:
+                    self._count_ingest_retry()
+                if body is None:
+                    _rewind_multipart_parts(parts)
                 try:
-                    encoder = MultipartEncoder(parts, boundary=BOUNDARY)
                     self.request_with_retries(
                         "POST",
                         f"{api_url}/runs/multipart",
                         request_kwargs={
-                            "data": encoder,
+                            # An encoder can only be read once, so make one per try
+                            "data": (
+                                body
+                                if body is not None
+                                else MultipartEncoder(parts, boundary=BOUNDARY)
+                            ),
                             "headers": {
                                 **self._headers,
+                                **encoding_headers,
                                 X_API_KEY: api_key,
-                                "Content-Type": encoder.content_type,
+                                "Content-Type": content_type,
                             },
                         },
                         stop_after_attempt=1,
@@ -1617,10 +2460,12 @@ class Client:
                     ls_utils.LangSmithAPIError,
                 ) as exc:
                     if idx == attempts:
+                        error = exc
                         logger.warning(f"Failed to multipart ingest runs: {exc}")
                     else:
                         continue
                 except Exception as e:
+                    error = e
                     try:
                         exc_desc_lines = traceback.format_exception_only(type(e), e)
                         exc_desc = "".join(exc_desc_lines).rstrip()
@@ -1628,7 +2473,144 @@ class Client:
                     except Exception:
                         logger.warning(f"Failed to multipart ingest runs: {repr(e)}")
                     # do not retry by default
-                    return
+                    break
+            self._record_ingest_request(api_url, started, error=error)
+            outcome = combine_outcomes(outcome, outcome_of(error))
+        return outcome
+
+    def flush(self, timeout: Optional[float] = None) -> DeliveryReport:
+        """Wait for the runs on the tracing queue to be sent.
+
+        Traces held back by a tail sampler until their root run ends aren't
+        waited for.
+
+        Args:
+            timeout (Optional[float]): The maximum number of seconds to wait.
+                By default, waits until the queue is empty.
+
+        Returns:
+            DeliveryReport: How many runs were delivered and failed while
+                waiting, and how many were still pending at the timeout.
+
+        Example:
+            .. code-block:: python
+
+                report = client.flush(timeout=5)
+                if report.abandoned:
+                    print(f"{report.abandoned} runs weren't sent in time")
+        """
+        before = self._tracing_stats.snapshot()
+        if self.tracing_queue is not None:
+            self._wait_for_tracing_queue(
+                math.inf if timeout is None else time.monotonic() + timeout
+            )
+        return self._delivery_report(before)
+
+    def shutdown(self, timeout: Optional[float] = None) -> DeliveryReport:
+        """Send the runs still queued, then stop the background tracing thread.
+
+        Traces buffered by a tail sampler are decided on first, and the
+        exporters are shut down once the queue is drained. Runs that can't be
+        sent before the timeout are abandoned; with a tracing spool they are
+        replayed by the next process. The client shouldn't be used for
+        tracing afterwards.
+
+        This is called when the interpreter exits, bounded by the
+        ``LANGSMITH_TRACING_SHUTDOWN_TIMEOUT`` environment variable (seconds).
+
+        Args:
+            timeout (Optional[float]): The maximum number of seconds to wait.
+                By default, waits until every run was sent.
+
+        Returns:
+            DeliveryReport: How many runs were delivered and failed while
+                shutting down, and how many were abandoned.
+        """
+        before = self._tracing_stats.snapshot()
+        deadline = math.inf if timeout is None else time.monotonic() + timeout
+        if self._tracing_shutdown_deadline is None:
+            self._tracing_shutdown_deadline = deadline
+        thread = self._tracing_thread
+        if thread is not None and thread is not threading.current_thread():
+            thread.join(
+                None if timeout is None else max(0, deadline - time.monotonic())
+            )
+        return self._delivery_report(before)
+
+    def _wait_for_tracing_queue(self, deadline: float) -> None:
+        queue = cast(BoundedTracingQueue, self.tracing_queue)
+        while queue.unfinished_tasks:
+            thread = self._tracing_thread
+            remaining = deadline - time.monotonic()
+            if thread is None or not thread.is_alive() or remaining <= 0:
+                # Nothing is left to drain the queue, or we're out of time
+                return
+            with queue.all_tasks_done:
+                if queue.unfinished_tasks:
+                    queue.all_tasks_done.wait(min(remaining, 0.1))
+
+    def _delivery_report(self, before: TracingStats) -> DeliveryReport:
+        after = self._tracing_stats.snapshot()
+        return DeliveryReport(
+            delivered=after.runs_sent - before.runs_sent,
+            failed=after.runs_failed - before.runs_failed,
+            abandoned=(
+                self.tracing_queue.unfinished_tasks
+                if self.tracing_queue is not None
+                else 0
+            ),
+        )
+
+    def _count_ingest_retry(self) -> None:
+        self._tracing_stats.increment("request_retries")
+
+    def _record_ingest_request(
+        self, api_url: str, started: float, *, error: Optional[BaseException] = None
+    ) -> None:
+        self._tracing_stats.increment("requests")
+        self._tracing_stats.observe(
+            "request_latency_seconds", time.perf_counter() - started
+        )
+        if error is not None:
+            self._tracing_stats.increment("request_failures")
+        if (breaker := self._circuit_breakers.get(api_url)) is not None:
+            if error is not None:
+                breaker.record_error(error)
+            else:
+                breaker.record_success()
+
+    def get_tracing_stats(self) -> TracingStats:
+        """Get counters and histograms describing the tracing pipeline.
+
+        Covers the tracing queue, the batches taken off it, the ingestion
+        requests sent for them, and the runs that were dropped or filtered
+        out along the way. Use ``TracingStats.to_prometheus()`` to expose them
+        to a Prometheus scraper.
+
+        Returns:
+            TracingStats: A snapshot of the stats. Counters are totals since
+                the client was created.
+
+        Example:
+            .. code-block:: python
+
+                stats = client.get_tracing_stats()
+                if stats.runs_failed or stats.runs_dropped:
+                    ...
+                print(stats.to_prometheus())
+        """
+        queue = self.tracing_queue
+        tail_sampler = self._tail_sampler
+        return self._tracing_stats.snapshot(
+            queue_depth=queue.qsize() if queue is not None else 0,
+            queue_bytes=queue.size_bytes if queue is not None else 0,
+            runs_dropped=queue.dropped_items if queue is not None else 0,
+            runs_spilled=queue.spilled_items if queue is not None else 0,
+            runs_filtered=tail_sampler.dropped_items if tail_sampler else 0,
+            circuit_breaker_open=sum(
+                breaker.state != "closed" for breaker in self._circuit_breakers.values()
+            ),
+        )
 
     def update_run(
         self,
@@ -1690,37 +2672,41 @@ class Client:
             data["error"] = error
         if inputs is not None:
             data["inputs"] = self._hide_run_inputs(inputs)
-        if outputs is not None:
-            outputs = ls_utils.deepish_copy(outputs)
-            data["outputs"] = self._hide_run_outputs(outputs)
-        if events is not None:
-            data["events"] = events
-        if (
+        enqueue = (
             self.tracing_queue is not None
             # batch ingest requires trace_id and dotted_order to be set
             and data["trace_id"] is not None
             and data["dotted_order"] is not None
-        ):
-            return self.tracing_queue.put(
+        )
+        if outputs is not None:
+            if enqueue and self._hide_outputs is False and not self._anonymizer:
+                outputs = ls_utils.snapshot(outputs)
+            data["outputs"] = self._hide_run_outputs(outputs)
+        if events is not None:
+            data["events"] = events
+        self._validate_runs([data], update=True)
+        if enqueue:
+            return self._enqueue_tracing_item(
                 TracingQueueItem(data["dotted_order"], "update", data)
             )
         return self._update_run(data)
 
     def _update_run(self, run_update: dict) -> None:
-        for api_url, api_key in self._write_api_urls.items():
-            headers = {
-                **self._headers,
-                X_API_KEY: api_key,
-            }
+        for api_url, destination in self._write_destinations.items():
+            for run in destination.apply([run_update]):
+                headers = {
+                    **self._headers,
+                    X_API_KEY: destination.api_key,
+                }
 
-            self.request_with_retries(
-                "PATCH",
-                f"{api_url}/runs/{run_update['id']}",
-                request_kwargs={
-                    "data": _dumps_json(run_update),
-                    "headers": headers,
-                },
-            )
+                self.request_with_retries(
+                    "PATCH",
+                    f"{api_url}/runs/{run['id']}",
+                    request_kwargs={
+                        "data": _dumps_json(run),
+                        "headers": headers,
+                    },
+                )
 
     def _load_child_runs(self, run: ls_schemas.Run) -> ls_schemas.Run:
         """Load child runs for a given run.
@@ -4346,7 +5332,7 @@ class Client:
                 and self.tracing_queue is not None
                 and feedback.trace_id is not None
             ):
-                self.tracing_queue.put(
+                self._enqueue_tracing_item(
                     TracingQueueItem(str(feedback.id), "feedback", feedback)
                 )
             else:
@@ -0,0 +1,124 @@
+"""Per-destination policies for clients that write runs to several URLs."""
+
+from __future__ import annotations
+
+import dataclasses
+import random
+from typing import Callable, Dict, List, Optional, Sequence, Union
+
+import orjson
+
+from langsmith import utils as ls_utils
+from langsmith._internal._serde import dumps_json as _dumps_json
+
+
+@dataclasses.dataclass
+class Destination:
+    """Where and how a client writes runs, as a value of ``Client(api_urls=...)``.
+
+    The policies apply on top of the client's own ``anonymizer``,
+    ``hide_inputs``, ``hide_outputs`` and sampling, just before runs are sent
+    to this destination. Runs are sent to each Destination independently and
+    concurrently, so a slow or failing destination doesn't hold up the
+    others.
+
+    Example:
+        .. code-block:: python
+
+            from langsmith import Client
+            from langsmith.destinations import Destination
+
+            client = Client(
+                api_urls={
+                    "https://langsmith.internal.example.com": "internal-key",
+                    "https://api.smith.langchain.com": Destination(
+                        api_key="shared-key",
+                        hide_inputs=True,
+                        sample_rate=0.1,
+                        project_name="shared-traces",
+                    ),
+                }
+            )
+
+    Attributes:
+        api_key: The API key for this URL.
+        anonymizer: Applied to the inputs and outputs sent to this URL.
+        hide_inputs: If True, sends no inputs. If a function, applied to the
+            inputs sent to this URL.
+        hide_outputs: If True, sends no outputs. If a function, applied to the
+            outputs sent to this URL.
+        sample_rate: The fraction of traces to send. Every run of a trace
+            gets the same decision.
+        project_name: Send runs to this project instead of the run's own.
+
+    Attachments are only sent to destinations that don't transform inputs or
+    outputs, since they may hold the same data.
+    """
+
+    api_key: Optional[str] = None
+    anonymizer: Optional[Callable[[dict], dict]] = None
+    hide_inputs: Optional[Union[Callable[[dict], dict], bool]] = None
+    hide_outputs: Optional[Union[Callable[[dict], dict], bool]] = None
+    sample_rate: Optional[float] = None
+    project_name: Optional[str] = None
+
+    def __post_init__(self) -> None:
+        """Check the sample rate."""
+        if self.sample_rate is not None and not 0 <= self.sample_rate <= 1:
+            raise ls_utils.LangSmithUserError(
+                f"Destination sample_rate must be between 0 and 1, "
+                f"got {self.sample_rate}."
+            )
+
+    @property
+    def sends_attachments(self) -> bool:
+        """Whether attachments are sent to this destination."""
+        return not (self.anonymizer or self.hide_inputs or self.hide_outputs)
+
+    def apply(self, runs: Sequence[dict]) -> List[dict]:
+        """Sample and transform runs for this destination.
+
+        Returns new run dicts, leaving the given ones unchanged.
+        """
+        return [self._transform(run) for run in runs if self._is_sampled(run)]
+
+    def _is_sampled(self, run: dict) -> bool:
+        if self.sample_rate is None:
+            return True
+        trace_id = run.get("trace_id") or run.get("id")
+        # Seeded by the trace, so every run of a trace gets the same decision
+        return random.Random(str(trace_id)).random() < self.sample_rate
+
+    def _transform(self, run: dict) -> dict:
+        run = dict(run)
+        if run.get("inputs") is not None:
+            run["inputs"] = self._hide(run["inputs"], self.hide_inputs)
+        if run.get("outputs") is not None:
+            run["outputs"] = self._hide(run["outputs"], self.hide_outputs)
+        if self.project_name is not None:
+            run["session_name"] = self.project_name
+            run.pop("session_id", None)
+        return run
+
+    def _hide(
+        self, values: dict, hide: Optional[Union[Callable[[dict], dict], bool]]
+    ) -> dict:
+        if hide is True:
+            return {}
+        if self.anonymizer:
+            values = self.anonymizer(orjson.loads(_dumps_json(values)))
+        elif callable(hide):
+            # The other destinations may be sending the same values concurrently
+            values = ls_utils.snapshot(values)
+        if callable(hide):
+            return hide(values)
+        return values
+
+
+def _from_config(value: Union[str, Dict, Destination]) -> Destination:
+    if isinstance(value, Destination):
+        return value
+    if isinstance(value, str):
+        return Destination(api_key=value)
+    # JSON from LANGSMITH_RUNS_ENDPOINTS
+    return Destination(**value)
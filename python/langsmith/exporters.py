@@ -0,0 +1,333 @@
+"""Exporters that receive the batches of traces drained by the client."""
+
+from __future__ import annotations
+
+import collections
+import dataclasses
+import logging
+import os
+import sys
+import threading
+import weakref
+from typing import (
+    IO,
+    TYPE_CHECKING,
+    Any,
+    Dict,
+    Iterable,
+    List,
+    Optional,
+    Protocol,
+    TextIO,
+    Union,
+)
+
+from langsmith._internal._attachments import encode_attachments
+from langsmith._internal._serde import dumps_json
+from langsmith.sampling import _parse_time
+
+if TYPE_CHECKING:
+    from langsmith.client import Client
+
+logger = logging.getLogger(__name__)
+
+
+@dataclasses.dataclass
+class TraceBatch:
+    """A batch of runs and feedback drained from the client's tracing queue.
+
+    Runs are the dicts the client would send to the API, after ``hide_inputs``,
+    ``hide_outputs`` and the anonymizer were applied. The create and update
+    events of a run may arrive in different batches.
+    """
+
+    create: List[dict]
+    """Runs to create, in the shape of ``Client.create_run`` arguments."""
+    update: List[dict]
+    """Updates to runs created in this or an earlier batch."""
+    feedback: List[Any]
+    """Feedback logged against traced runs."""
+
+
+class TraceExporter(Protocol):
+    """Receives batches of traces from a client's background thread.
+
+    Each exporter is handed its own shallow copy of every run dict, but nested
+    values are shared, so exporters must not mutate them. ``export`` may be
+    called from several threads at once.
+    """
+
+    def export(self, batch: TraceBatch) -> bool:
+        """Export a batch, returning whether it was delivered."""
+
+    def shutdown(self) -> None:
+        """Flush and release resources when the client's tracing thread exits."""
+
+
+def _feedback_dict(feedback: Any) -> dict:
+    return feedback if isinstance(feedback, dict) else feedback.dict()
+
+
+def _inline_attachments(run: dict) -> dict:
+    if not run.get("attachments"):
+        return run
+    # The file may be gone by the time the export is read
+    return {
+        **run,
+        "attachments": encode_attachments(run["attachments"], inline_files=True),
+    }
+
+
+class LangSmithExporter:
+    """Send batches to the LangSmith API of the client the exporter is passed to.
+
+    This is what a client does when no exporters are given. Include it to keep
+    sending traces to LangSmith alongside other exporters.
+
+    Example:
+        .. code-block:: python
+
+            from langsmith import Client
+            from langsmith.exporters import JSONLFileExporter, LangSmithExporter
+
+            client = Client(
+                exporters=[LangSmithExporter(), JSONLFileExporter("traces.jsonl")]
+            )
+    """
+
+    def __init__(self) -> None:
+        """Initialize the exporter."""
+        self._client_ref: Optional[weakref.ref[Client]] = None
+
+    def _bind(self, client: Client) -> None:
+        # A weakref, so that the exporter doesn't keep the client alive.
+        self._client_ref = weakref.ref(client)
+
+    def export(self, batch: TraceBatch) -> bool:
+        """Send the batch to the LangSmith API.
+
+        Returns False if any URL failed in a way that a retry could fix.
+        """
+        client = self._client_ref() if self._client_ref is not None else None
+        if client is None:
+            logger.warning("LangSmithExporter is not attached to a client.")
+            return False
+        batch_ingest_config = client.info.batch_ingest_config or {}
+        if batch_ingest_config.get("use_multipart_endpoint", False):
+            delivery = client._multipart_ingest(
+                create=batch.create,
+                update=batch.update,
+                feedback=batch.feedback,
+                pre_sampled=True,
+            )
+        else:
+            delivery = client._batch_ingest_runs(
+                create=batch.create, update=batch.update, pre_sampled=True
+            )
+        delivery.wait()
+        return not delivery.to_retry(client._write_destinations)
+
+    def shutdown(self) -> None:
+        """Do nothing, the client owns the connection."""
+
+
+class JSONLFileExporter:
+    """Append every run and feedback event to a JSON Lines file.
+
+    Each line is an object such as ``{"event": "post", "payload": {...}}``,
+    where the event is ``"post"``, ``"patch"`` or ``"feedback"``. Attachment
+    contents are base64-encoded.
+    """
+
+    def __init__(self, path: Union[str, os.PathLike]) -> None:
+        """Initialize the exporter. The file is created on the first export."""
+        self.path = os.fspath(path)
+        self._lock = threading.Lock()
+        self._file: Optional[IO[bytes]] = None
+
+    def export(self, batch: TraceBatch) -> bool:
+        """Append the batch to the file."""
+        lines = [
+            dumps_json({"event": event, "payload": payload}) + b"\n"
+            for event, payloads in (
+                ("post", [_inline_attachments(run) for run in batch.create]),
+                ("patch", [_inline_attachments(run) for run in batch.update]),
+                ("feedback", [_feedback_dict(fb) for fb in batch.feedback]),
+            )
+            for payload in payloads
+        ]
+        with self._lock:
+            if self._file is None:
+                directory = os.path.dirname(os.path.abspath(self.path))
+                os.makedirs(directory, exist_ok=True)
+                self._file = open(self.path, "ab")
+            self._file.write(b"".join(lines))
+            self._file.flush()
+        return True
+
+    def shutdown(self) -> None:
+        """Close the file."""
+        with self._lock:
+            if self._file is not None:
+                self._file.close()
+                self._file = None
+
+
+def _merge_runs(events: Iterable[dict], runs: Dict[str, dict]) -> None:
+    for event in events:
+        run = runs.setdefault(str(event["id"]), {})
+        run.update({k: v for k, v in event.items() if v is not None})
+
+
+class ConsoleExporter:
+    """Print each trace as a tree once its root run ends.
+
+    Example output::
+
+        agent (chain) 2.31s
+        ├── retrieve (retriever) 0.42s
+        └── ChatOpenAI (llm) 1.80s [error: RateLimitError]
+    """
+
+    def __init__(self, stream: Optional[TextIO] = None) -> None:
+        """Initialize the exporter.
+
+        Args:
+            stream: Where to print traces. Defaults to ``sys.stdout``.
+        """
+        self.stream = stream
+        self._lock = threading.Lock()
+        self._traces: Dict[str, Dict[str, dict]] = collections.defaultdict(dict)
+
+    def export(self, batch: TraceBatch) -> bool:
+        """Buffer the runs and print the traces that ended."""
+        finished: List[Dict[str, dict]] = []
+        with self._lock:
+            touched = set()
+            for run in batch.create + batch.update:
+                trace_id = str(run.get("trace_id") or run["id"])
+                _merge_runs([run], self._traces[trace_id])
+                touched.add(trace_id)
+            for trace_id in touched:
+                root = self._traces[trace_id].get(trace_id)
+                if root is not None and root.get("end_time") is not None:
+                    finished.append(self._traces.pop(trace_id))
+            for runs in finished:
+                self._print(runs)
+        return True
+
+    def shutdown(self) -> None:
+        """Print the traces that never finished."""
+        with self._lock:
+            while self._traces:
+                _, runs = self._traces.popitem()
+                self._print(runs, incomplete=True)
+
+    def _print(self, runs: Dict[str, dict], incomplete: bool = False) -> None:
+        children: Dict[Optional[str], List[dict]] = collections.defaultdict(list)
+        for run in sorted(runs.values(), key=lambda r: r.get("dotted_order") or ""):
+            parent_id = run.get("parent_run_id")
+            parent = str(parent_id) if parent_id is not None else None
+            # Runs whose parent wasn't seen are printed at the top level
+            children[parent if parent in runs else None].append(run)
+        lines: List[str] = []
+        for run in children[None]:
+            lines.append(_describe_run(run) + (" (incomplete)" if incomplete else ""))
+            self._render_children(str(run["id"]), children, "", lines)
+        stream = self.stream or sys.stdout
+        stream.write("\n".join(lines) + "\n")
+        stream.flush()
+
+    def _render_children(
+        self,
+        run_id: str,
+        children: Dict[Optional[str], List[dict]],
+        prefix: str,
+        lines: List[str],
+    ) -> None:
+        runs = children.get(run_id, [])
+        for i, run in enumerate(runs):
+            last = i == len(runs) - 1
+            branch, indent = ("└── ", "    ") if last else ("├── ", "│   ")
+            lines.append(prefix + branch + _describe_run(run))
+            self._render_children(str(run["id"]), children, prefix + indent, lines)
+
+
+def _describe_run(run: dict) -> str:
+    description = f"{run.get('name', 'Unnamed')} ({run.get('run_type', 'chain')})"
+    start = _parse_time(run.get("start_time"))
+    end = _parse_time(run.get("end_time"))
+    if start is not None and end is not None:
+        description += f" {(end - start).total_seconds():.2f}s"
+    if run.get("error"):
+        error = str(run["error"]).strip().splitlines()[0]
+        description += f" [error: {error}]"
+    return description
+
+
+class InMemoryExporter:
+    """Keep every batch in memory, e.g. to assert on traces in tests.
+
+    Example:
+        .. code-block:: python
+
+            from langsmith import Client, traceable
+            from langsmith.exporters import InMemoryExporter
+
+            exporter = InMemoryExporter()
+            client = Client(exporters=[exporter])
+
+
+            @traceable(client=client)
+            def my_function(): ...
+
+
+            my_function()
+            client.tracing_queue.join()
+            assert exporter.runs[0]["name"] == "my_function"
+    """
+
+    def __init__(self) -> None:
+        """Initialize the exporter."""
+        self.batches: List[TraceBatch] = []
+        self._lock = threading.Lock()
+
+    def export(self, batch: TraceBatch) -> bool:
+        """Store the batch."""
+        with self._lock:
+            self.batches.append(batch)
+        return True
+
+    @property
+    def runs(self) -> List[dict]:
+        """Every run exported so far, with updates merged into their creates."""
+        runs: Dict[str, dict] = {}
+        with self._lock:
+            for batch in self.batches:
+                _merge_runs(batch.create, runs)
+                _merge_runs(batch.update, runs)
+        return sorted(runs.values(), key=lambda r: r.get("dotted_order") or "")
+
+    @property
+    def feedback(self) -> List[dict]:
+        """Every feedback exported so far."""
+        with self._lock:
+            return [_feedback_dict(fb) for b in self.batches for fb in b.feedback]
+
+    def clear(self) -> None:
+        """Forget every stored batch."""
+        with self._lock:
+            self.batches.clear()
+
+    def shutdown(self) -> None:
+        """Do nothing, the batches stay available."""
+
+
+__all__ = [
+    "TraceBatch",
+    "TraceExporter",
+    "LangSmithExporter",
+    "JSONLFileExporter",
+    "ConsoleExporter",
+    "InMemoryExporter",
+]
@@ -0,0 +1,205 @@
+"""Capture traces to local files and upload them to LangSmith later.
+
+Set the ``LANGSMITH_TRACING_OFFLINE_DIR`` environment variable (or pass an
+:class:`OfflineExporter` to the client) to write every traced run to JSON Lines
+files instead of sending it. Once the machine has network access, replay the
+files into a project:
+
+.. code-block:: python
+
+    from langsmith.offline import upload_traces
+
+    result = upload_traces("./traces", project_name="nightly-eval")
+    print(f"Uploaded {result.runs} runs in {result.traces} traces")
+"""
+
+from __future__ import annotations
+
+import dataclasses
+import glob
+import logging
+import os
+import time
+import uuid
+from typing import (
+    TYPE_CHECKING,
+    Dict,
+    Iterable,
+    List,
+    Optional,
+    Sequence,
+    Set,
+    Union,
+)
+
+import orjson
+
+from langsmith._internal._attachments import decode_attachments
+from langsmith.exporters import JSONLFileExporter, LangSmithExporter, TraceBatch
+
+if TYPE_CHECKING:
+    from langsmith.client import Client
+
+logger = logging.getLogger(__name__)
+
+
+class OfflineExporter(JSONLFileExporter):
+    """Write traces to a new JSON Lines file in a directory.
+
+    Each client process writes its own file, named so that files sort in the
+    order they were created. Lines hold the same ``post`` and ``patch``
+    payloads the client would send to the multipart ingest endpoint.
+    """
+
+    def __init__(self, directory: Union[str, os.PathLike]) -> None:
+        """Initialize the exporter. The directory is created on the first export."""
+        self.directory = os.fspath(directory)
+        name = f"{time.time_ns():020d}-{os.getpid()}-{uuid.uuid4().hex[:8]}.jsonl"
+        super().__init__(os.path.join(self.directory, name))
+
+
+@dataclasses.dataclass
+class UploadResult:
+    """A summary of an upload."""
+
+    files: List[str] = dataclasses.field(default_factory=list)
+    """The files that were read."""
+    traces: int = 0
+    """The number of traces uploaded."""
+    runs: int = 0
+    """The number of runs uploaded."""
+    feedback: int = 0
+    """The number of feedback entries uploaded."""
+    failed_traces: int = 0
+    """The number of traces that the API did not accept."""
+    skipped_lines: int = 0
+    """Lines that could not be parsed, or runs missing a trace ID or dotted order."""
+
+
+def _list_files(path: Union[str, os.PathLike, Sequence[str]]) -> List[str]:
+    if isinstance(path, (str, os.PathLike)):
+        path = os.fspath(path)
+        if os.path.isdir(path):
+            return sorted(glob.glob(os.path.join(path, "*.jsonl")))
+        return [path]
+    return list(path)
+
+
+def _restore_attachments(payload: dict) -> None:
+    if attachments := payload.get("attachments"):
+        payload["attachments"] = decode_attachments(attachments)
+
+
+def _chunk_traces(
+    traces: Dict[str, List[dict]], batch_size: int
+) -> Iterable[List[List[dict]]]:
+    chunk: List[List[dict]] = []
+    size = 0
+    for runs in traces.values():
+        if chunk and size + len(runs) > batch_size:
+            yield chunk
+            chunk, size = [], 0
+        chunk.append(runs)
+        size += len(runs)
+    if chunk:
+        yield chunk
+
+
+def upload_traces(
+    path: Union[str, os.PathLike, Sequence[str]],
+    *,
+    client: Optional[Client] = None,
+    project_name: Optional[str] = None,
+    batch_size: int = 100,
+    delete: bool = False,
+) -> UploadResult:
+    """Upload traces captured by an :class:`OfflineExporter`.
+
+    The patches of each run are merged into its post. Runs are grouped by
+    ``trace_id`` and sent in ``dotted_order``, so that parents are created
+    before their children and a trace is never split across requests.
+
+    Args:
+        path: A directory of ``.jsonl`` files, a single file, or a list of files.
+        client: The client to upload with. Defaults to a new client configured
+            from the environment.
+        project_name: Upload every run into this project instead of the
+            project it was traced to.
+        batch_size: The maximum number of runs per request. Larger traces are
+            sent in a request of their own.
+        delete: Whether to delete the files once every trace was accepted.
+
+    Returns:
+        A summary of what was uploaded.
+    """
+    if client is None:
+        from langsmith.client import Client
+
+        client = Client()
+    exporter = LangSmithExporter()
+    exporter._bind(client)
+
+    result = UploadResult(files=_list_files(path))
+    runs: Dict[str, dict] = {}
+    posted: Set[str] = set()
+    feedback: List[dict] = []
+    for file in result.files:
+        with open(file, "rb") as f:
+            for line in f:
+                try:
+                    record = orjson.loads(line)
+                    event, payload = record["event"], record["payload"]
+                except (orjson.JSONDecodeError, KeyError, TypeError):
+                    # e.g. a partially written line from a crash
+                    result.skipped_lines += 1
+                    continue
+                if event == "feedback":
+                    feedback.append(payload)
+                    continue
+                if event == "post":
+                    posted.add(payload["id"])
+                run = runs.setdefault(payload["id"], {})
+                run.update({k: v for k, v in payload.items() if v is not None})
+
+    traces: Dict[str, List[dict]] = {}
+    for run in sorted(runs.values(), key=lambda r: r.get("dotted_order") or ""):
+        if not run.get("trace_id") or not run.get("dotted_order"):
+            result.skipped_lines += 1
+            continue
+        _restore_attachments(run)
+        if project_name is not None:
+            run["session_name"] = project_name
+            run.pop("session_id", None)
+        traces.setdefault(run["trace_id"], []).append(run)
+
+    for chunk in _chunk_traces(traces, batch_size):
+        chunk_runs = [run for trace in chunk for run in trace]
+        batch = TraceBatch(
+            # Patches whose post was lost, e.g. in a crash, are sent as is
+            create=[run for run in chunk_runs if run["id"] in posted],
+            update=[run for run in chunk_runs if run["id"] not in posted],
+            feedback=[],
+        )
+        if exporter.export(batch):
+            result.traces += len(chunk)
+            result.runs += len(chunk_runs)
+        else:
+            result.failed_traces += len(chunk)
+    if feedback:
+        if exporter.export(TraceBatch(create=[], update=[], feedback=feedback)):
+            result.feedback = len(feedback)
+        else:
+            logger.warning(f"Failed to upload {len(feedback)} feedback entries.")
+
+    if result.failed_traces:
+        logger.warning(
+            f"{result.failed_traces} traces were not accepted by the API."
+            " Run the upload again to retry them."
+        )
+    elif delete:
+        for file in result.files:
+            os.remove(file)
+    return result
+
+
+__all__ = ["OfflineExporter", "UploadResult", "upload_traces"]
@@ -0,0 +1,906 @@
+"""Bridge OpenTelemetry and LangSmith traces.
+
+:class:`OTLPExporter` sends the runs a client traces to an OpenTelemetry
+collector. In the other direction, :class:`LangSmithSpanExporter` converts
+finished spans into runs, mapping the
+`GenAI semantic conventions <https://opentelemetry.io/docs/specs/semconv/gen-ai/>`_
+onto LLM, embedding and tool runs, and sends them through the client's
+background batching. The latter implements the OpenTelemetry SDK ``SpanExporter``
+interface, so that the ``opentelemetry-sdk`` package only needs to be installed
+by the application that uses it:
+
+.. code-block:: python
+
+    from opentelemetry import trace
+    from opentelemetry.sdk.trace import TracerProvider
+    from opentelemetry.sdk.trace.export import BatchSpanProcessor
+
+    from langsmith.otel import LangSmithSpanExporter
+
+    provider = TracerProvider()
+    provider.add_span_processor(BatchSpanProcessor(LangSmithSpanExporter()))
+    trace.set_tracer_provider(provider)
+
+Run IDs are derived from span IDs the same way as the JS SDK's
+``AISDKExporter``, so the same span always maps onto the same run. Spans are
+held back until their parent span has been exported, since the dotted order of
+a run depends on its parent's. The following span attributes customize the
+runs:
+
+- ``langsmith.span.kind``: The run type, e.g. ``"retriever"``.
+- ``langsmith.span.tags``: A comma-separated string or list of tags.
+- ``langsmith.metadata.<key>``: A metadata value.
+- ``langsmith.trace.session_name``: The project of the trace, set on its root span.
+"""
+
+from __future__ import annotations
+
+import collections
+import json
+import logging
+import os
+import struct
+import threading
+import urllib.parse
+import uuid
+from datetime import datetime, timezone
+from typing import (
+    TYPE_CHECKING,
+    Any,
+    Dict,
+    List,
+    Literal,
+    Mapping,
+    Optional,
+    Sequence,
+    Tuple,
+    Union,
+)
+
+import requests
+
+from langsmith import run_trees
+from langsmith import utils as ls_utils
+from langsmith._internal._serde import dumps_json
+from langsmith.exporters import TraceBatch, _merge_runs
+from langsmith.sampling import _parse_time
+
+if TYPE_CHECKING:
+    from opentelemetry.sdk.trace import ReadableSpan
+    from opentelemetry.sdk.trace.export import SpanExportResult
+
+    from langsmith.client import Client
+
+logger = logging.getLogger(__name__)
+
+# Shared with the JS SDK, so that both derive the same run IDs from span IDs.
+RUN_ID_NAMESPACE = uuid.UUID("5c718b20-9078-11ef-9a3d-325096b39f47")
+
+# How long shutdown() waits for queued runs, so it doesn't hold up exit
+_SHUTDOWN_TIMEOUT_MS = 5_000
+
+_LLM_OPERATIONS = {"chat", "text_completion", "generate_content"}
+_RUN_TYPES_BY_OPERATION = {
+    "embeddings": "embedding",
+    "execute_tool": "tool",
+    "create_agent": "chain",
+    "invoke_agent": "chain",
+}
+# Attributes that hold inputs and outputs, which are left out of the metadata.
+_CONTENT_PREFIXES = (
+    "gen_ai.prompt",
+    "gen_ai.completion",
+    "gen_ai.input.messages",
+    "gen_ai.output.messages",
+    "gen_ai.system_instructions",
+    "gen_ai.tool.call.arguments",
+    "gen_ai.tool.call.result",
+    "langsmith.",
+)
+_MESSAGE_EVENTS = {
+    "gen_ai.system.message": "system",
+    "gen_ai.user.message": "user",
+    "gen_ai.assistant.message": "assistant",
+    "gen_ai.tool.message": "tool",
+}
+
+
+def span_id_to_run_id(span_id: int) -> uuid.UUID:
+    """Return the run ID of an OpenTelemetry span ID."""
+    return uuid.uuid5(RUN_ID_NAMESPACE, format(span_id, "016x"))
+
+
+def _to_datetime(time_ns: int) -> datetime:
+    seconds, nanoseconds = divmod(time_ns, 1_000_000_000)
+    return datetime.fromtimestamp(seconds, timezone.utc).replace(
+        microsecond=nanoseconds // 1000
+    )
+
+
+def _try_json(value: Any) -> Any:
+    if not isinstance(value, str):
+        return value
+    try:
+        return json.loads(value)
+    except ValueError:
+        return value
+
+
+def _attribute_value(value: Any) -> Any:
+    # Sequence attributes are tuples
+    return list(value) if isinstance(value, tuple) else value
+
+
+def _indexed_messages(attributes: Mapping[str, Any], prefix: str) -> List[dict]:
+    """Collect ``{prefix}.{i}.{field}`` attributes, as written by OpenLLMetry."""
+    messages: Dict[int, dict] = {}
+    for key, value in attributes.items():
+        index, _, field = key[len(prefix) + 1 :].partition(".")
+        if key.startswith(prefix + ".") and index.isdigit() and field:
+            messages.setdefault(int(index), {})[field] = _attribute_value(value)
+    return [messages[i] for i in sorted(messages)]
+
+
+def _convert_message(message: Any) -> Any:
+    """Convert a semantic-convention message with ``parts`` to the OpenAI format."""
+    if not isinstance(message, dict) or "parts" not in message:
+        return message
+    converted: Dict[str, Any] = {"role": message.get("role")}
+    content: List[Any] = []
+    tool_calls: List[dict] = []
+    for part in message["parts"]:
+        part_type = part.get("type")
+        if part_type == "text":
+            content.append({"type": "text", "text": part.get("content")})
+        elif part_type == "tool_call":
+            tool_calls.append(
+                {
+                    "id": part.get("id"),
+                    "type": "function",
+                    "function": {
+                        "name": part.get("name"),
+                        "arguments": json.dumps(part.get("arguments")),
+                    },
+                }
+            )
+        elif part_type == "tool_call_response":
+            converted["role"] = "tool"
+            converted["tool_call_id"] = part.get("id")
+            content.append(part.get("response"))
+        else:
+            content.append(part)
+    if len(content) == 1 and isinstance(content[0], dict):
+        converted["content"] = content[0].get("text", content[0])
+    else:
+        converted["content"] = content
+    if tool_calls:
+        converted["tool_calls"] = tool_calls
+    if message.get("finish_reason"):
+        converted["finish_reason"] = message["finish_reason"]
+    return converted
+
+
+def _event_message(name: str, attributes: Mapping[str, Any]) -> dict:
+    if name == "gen_ai.choice":
+        message = _try_json(attributes.get("message"))
+        if not isinstance(message, dict):
+            message = {
+                "role": attributes.get("message.role", "assistant"),
+                "content": attributes.get("message.content", message),
+            }
+        if attributes.get("finish_reason"):
+            message["finish_reason"] = attributes["finish_reason"]
+        return message
+    message = {"role": attributes.get("role", _MESSAGE_EVENTS[name])}
+    if "content" in attributes:
+        message["content"] = _try_json(attributes["content"])
+    if "tool_calls" in attributes:
+        message["tool_calls"] = _try_json(attributes["tool_calls"])
+    if name == "gen_ai.tool.message" and "id" in attributes:
+        message["tool_call_id"] = attributes["id"]
+    return message
+
+
+def _messages(
+    attributes: Mapping[str, Any],
+    events: Sequence[Tuple[str, Mapping[str, Any]]],
+    *,
+    output: bool,
+) -> Optional[List[Any]]:
+    key = "gen_ai.output.messages" if output else "gen_ai.input.messages"
+    if key in attributes:
+        messages = _try_json(attributes[key])
+        if isinstance(messages, list):
+            return [_convert_message(m) for m in messages]
+    legacy = "gen_ai.completion" if output else "gen_ai.prompt"
+    if legacy in attributes:
+        messages = _try_json(attributes[legacy])
+        if isinstance(messages, list):
+            return [_convert_message(m) for m in messages]
+        return [{"role": "assistant" if output else "user", "content": messages}]
+    if indexed := _indexed_messages(attributes, legacy):
+        return indexed
+    from_events = [
+        _event_message(name, event_attributes)
+        for name, event_attributes in events
+        if (name == "gen_ai.choice") == output
+        and (name in _MESSAGE_EVENTS or name == "gen_ai.choice")
+    ]
+    if not output and "gen_ai.system_instructions" in attributes:
+        instructions = _try_json(attributes["gen_ai.system_instructions"])
+        from_events.insert(0, {"role": "system", "content": instructions})
+    return from_events or None
+
+
+def _usage_metadata(attributes: Mapping[str, Any]) -> Optional[dict]:
+    input_tokens = attributes.get(
+        "gen_ai.usage.input_tokens", attributes.get("gen_ai.usage.prompt_tokens")
+    )
+    output_tokens = attributes.get(
+        "gen_ai.usage.output_tokens",
+        attributes.get("gen_ai.usage.completion_tokens"),
+    )
+    if input_tokens is None and output_tokens is None:
+        return None
+    return {
+        "input_tokens": input_tokens or 0,
+        "output_tokens": output_tokens or 0,
+        "total_tokens": (input_tokens or 0) + (output_tokens or 0),
+    }
+
+
+def _error(span: ReadableSpan) -> Optional[str]:
+    if span.status is None or span.status.status_code.name != "ERROR":
+        return None
+    for event in span.events:
+        if event.name == "exception":
+            attributes = event.attributes or {}
+            if attributes.get("exception.stacktrace"):
+                return str(attributes["exception.stacktrace"])
+            return f"{attributes.get('exception.type')}: " + str(
+                attributes.get("exception.message")
+            )
+    return span.status.description or "Error"
+
+
+def _run_type(attributes: Mapping[str, Any]) -> str:
+    if kind := attributes.get("langsmith.span.kind"):
+        return str(kind).lower()
+    operation = attributes.get("gen_ai.operation.name")
+    if operation in _LLM_OPERATIONS:
+        return "llm"
+    if operation in _RUN_TYPES_BY_OPERATION:
+        return _RUN_TYPES_BY_OPERATION[operation]
+    if attributes.get("gen_ai.tool.name"):
+        return "tool"
+    if attributes.get("gen_ai.system") or attributes.get("gen_ai.provider.name"):
+        return "llm"
+    return "chain"
+
+
+def _convert_span(span: ReadableSpan) -> Dict[str, Any]:
+    """Map a span onto the fields of a run, except its place in the trace."""
+    attributes = {k: _attribute_value(v) for k, v in (span.attributes or {}).items()}
+    events = [(event.name, event.attributes or {}) for event in span.events]
+    run_type = _run_type(attributes)
+    name = span.name
+    inputs: Dict[str, Any] = {}
+    outputs: Dict[str, Any] = {}
+
+    metadata: Dict[str, Any] = {
+        key: value
+        for key, value in attributes.items()
+        if not key.startswith(_CONTENT_PREFIXES)
+    }
+    metadata.update(
+        {
+            key[len("langsmith.metadata.") :]: value
+            for key, value in attributes.items()
+            if key.startswith("langsmith.metadata.")
+        }
+    )
+    resource = getattr(span, "resource", None)
+    if resource is not None and "service.name" in resource.attributes:
+        metadata["service.name"] = resource.attributes["service.name"]
+    metadata["otel_trace_id"] = format(span.context.trace_id, "032x")
+    metadata["otel_span_id"] = format(span.context.span_id, "016x")
+
+    if run_type == "tool":
+        name = attributes.get("gen_ai.tool.name") or name
+        args = _try_json(attributes.get("gen_ai.tool.call.arguments"))
+        if args is not None:
+            inputs = args if isinstance(args, dict) else {"input": args}
+        result = _try_json(attributes.get("gen_ai.tool.call.result"))
+        if result is not None:
+            outputs = result if isinstance(result, dict) else {"output": result}
+        if attributes.get("gen_ai.tool.call.id"):
+            metadata["tool_call_id"] = attributes["gen_ai.tool.call.id"]
+    elif attributes.get("gen_ai.operation.name") or run_type in ("llm", "embedding"):
+        provider = attributes.get("gen_ai.provider.name") or attributes.get(
+            "gen_ai.system"
+        )
+        model = attributes.get("gen_ai.request.model") or attributes.get(
+            "gen_ai.response.model"
+        )
+        ls_params = {
+            "ls_provider": provider,
+            "ls_model_name": model,
+            "ls_model_type": (
+                "chat"
+                if attributes.get("gen_ai.operation.name", "chat") == "chat"
+                else "llm"
+            ),
+            "ls_temperature": attributes.get("gen_ai.request.temperature"),
+            "ls_max_tokens": attributes.get("gen_ai.request.max_tokens"),
+            "ls_stop": attributes.get("gen_ai.request.stop_sequences"),
+        }
+        if run_type == "llm":
+            metadata.update({k: v for k, v in ls_params.items() if v is not None})
+        if (input_messages := _messages(attributes, events, output=False)) is not None:
+            inputs["messages"] = input_messages
+        output_messages = _messages(attributes, events, output=True)
+        if output_messages is not None:
+            outputs["messages"] = output_messages
+        if usage_metadata := _usage_metadata(attributes):
+            outputs["usage_metadata"] = usage_metadata
+
+    run_events = [
+        {
+            "name": event.name,
+            "time": _to_datetime(event.timestamp).isoformat(),
+            "kwargs": {k: _attribute_value(v) for k, v in event_attributes.items()},
+        }
+        for event, (_, event_attributes) in zip(span.events, events)
+        if event.name not in _MESSAGE_EVENTS
+        and event.name not in ("gen_ai.choice", "exception")
+    ]
+    tags = attributes.get("langsmith.span.tags") or []
+    if isinstance(tags, str):
+        tags = [tag.strip() for tag in tags.split(",") if tag.strip()]
+
+    start_time = _to_datetime(span.start_time or 0)
+    return {
+        "name": name,
+        "run_type": run_type,
+        "inputs": inputs,
+        "outputs": outputs,
+        "error": _error(span),
+        "start_time": start_time,
+        "end_time": _to_datetime(span.end_time) if span.end_time else start_time,
+        "events": run_events,
+        "tags": list(tags),
+        "extra": {"metadata": metadata},
+    }
+
+
+class LangSmithSpanExporter:
+    """An OpenTelemetry ``SpanExporter`` that sends spans to LangSmith as runs.
+
+    A span whose parent is remote, i.e. it was propagated from another service,
+    starts a new trace.
+    """
+
+    def __init__(
+        self,
+        client: Optional[Client] = None,
+        *,
+        project_name: Optional[str] = None,
+        max_buffered_spans: int = 10_000,
+    ) -> None:
+        """Initialize the exporter.
+
+        Args:
+            client: The client to send runs with. Defaults to the client used by
+                ``@traceable``.
+            project_name: The project to send traces to. Defaults to the project
+                configured in the environment.
+            max_buffered_spans: The maximum number of spans to hold back while
+                waiting for their parent. The oldest are dropped beyond that.
+        """
+        self.client = client or run_trees.get_cached_client()
+        self.project_name = project_name
+        self.max_buffered_spans = max_buffered_spans
+        self._lock = threading.Lock()
+        # Runs that were sent, by span ID, to place late children under them
+        self._sent: collections.OrderedDict[int, run_trees.RunTree] = (
+            collections.OrderedDict()
+        )
+        # Spans waiting for their parent, by the parent's span ID
+        self._pending: Dict[int, List[ReadableSpan]] = collections.defaultdict(list)
+        self._num_pending = 0
+
+    def export(self, spans: Sequence[ReadableSpan]) -> SpanExportResult:
+        """Convert the spans into runs and queue them for sending."""
+        from opentelemetry.sdk.trace.export import SpanExportResult
+
+        try:
+            with self._lock:
+                for span in sorted(spans, key=lambda s: s.start_time or 0):
+                    self._add(span)
+                self._evict()
+        except Exception as e:
+            logger.warning(f"Failed to export spans to LangSmith: {e!r}")
+            return SpanExportResult.FAILURE
+        return SpanExportResult.SUCCESS
+
+    def _add(self, span: ReadableSpan) -> None:
+        parent = span.parent
+        if parent is None or parent.is_remote:
+            self._send(span, None)
+        elif parent.span_id in self._sent:
+            self._send(span, self._sent[parent.span_id])
+        else:
+            self._pending[parent.span_id].append(span)
+            self._num_pending += 1
+
+    def _send(self, span: ReadableSpan, parent: Optional[run_trees.RunTree]) -> None:
+        run_id = span_id_to_run_id(span.context.span_id)
+        fields = _convert_span(span)
+        if parent is None:
+            project_name = (
+                (span.attributes or {}).get("langsmith.trace.session_name")
+                or self.project_name
+                or ls_utils.get_tracer_project()
+            )
+            run = run_trees.RunTree(
+                id=run_id, project_name=project_name, ls_client=self.client, **fields
+            )
+        else:
+            run = run_trees.RunTree(
+                id=run_id,
+                trace_id=parent.trace_id,
+                parent_run_id=parent.id,
+                dotted_order=parent.dotted_order
+                + "."
+                + run_trees._create_current_dotted_order(fields["start_time"], run_id),
+                project_name=parent.session_name,
+                ls_client=self.client,
+                **fields,
+            )
+        run.post()
+        self._sent[span.context.span_id] = run
+        if len(self._sent) > self.max_buffered_spans:
+            self._sent.popitem(last=False)
+        children = self._pending.pop(span.context.span_id, [])
+        self._num_pending -= len(children)
+        for child in children:
+            self._send(child, run)
+
+    def _evict(self) -> None:
+        dropped = 0
+        while self._num_pending > self.max_buffered_spans:
+            parent_id = next(iter(self._pending))
+            dropped += len(self._pending[parent_id])
+            self._num_pending -= len(self._pending.pop(parent_id))
+        if dropped:
+            logger.warning(
+                f"Dropped {dropped} spans whose parent span was never exported."
+            )
+
+    def force_flush(self, timeout_millis: int = 30_000) -> bool:
+        """Wait for the queued runs to be sent, returning whether they were."""
+        report = self.client.flush(timeout=timeout_millis / 1000)
+        return not report.abandoned
+
+    def shutdown(self) -> None:
+        """Send the queued runs, warning about spans whose parent never ended."""
+        with self._lock:
+            if self._num_pending:
+                logger.warning(
+                    f"{self._num_pending} spans were not sent to LangSmith because"
+                    " their parent span never ended."
+                )
+            self._pending.clear()
+            self._num_pending = 0
+        self.force_flush(_SHUTDOWN_TIMEOUT_MS)
+
+
+def run_id_to_span_id(run_id: Union[str, uuid.UUID]) -> bytes:
+    """Return the 8-byte OpenTelemetry span ID of a run ID.
+
+    It is the last 64 bits of the UUID, which are random in both UUIDv4 and
+    UUIDv7 run IDs. This is also the parent ID of the ``traceparent`` header
+    from :meth:`~langsmith.run_trees.RunTree.to_headers`.
+    """
+    return uuid.UUID(str(run_id)).bytes[8:]
+
+
+def _time_ns(value: Any) -> Optional[int]:
+    time = _parse_time(value)
+    if time is None:
+        return None
+    if time.tzinfo is None:
+        time = time.replace(tzinfo=timezone.utc)
+    seconds = int(time.timestamp())
+    return seconds * 1_000_000_000 + time.microsecond * 1000
+
+
+def _otel_attribute(value: Any) -> Any:
+    """Coerce a value to a type OpenTelemetry attributes can hold."""
+    if isinstance(value, (str, bool, int, float)):
+        return value
+    if isinstance(value, (list, tuple)) and all(isinstance(v, str) for v in value):
+        return list(value)
+    return dumps_json(value).decode("utf-8")
+
+
+def _usage(run: dict) -> Tuple[Optional[int], Optional[int]]:
+    outputs = run.get("outputs") or {}
+    if isinstance(usage := outputs.get("usage_metadata"), dict):
+        return usage.get("input_tokens"), usage.get("output_tokens")
+    llm_output = outputs.get("llm_output")
+    if isinstance(llm_output, dict) and isinstance(
+        usage := llm_output.get("token_usage"), dict
+    ):
+        return usage.get("prompt_tokens"), usage.get("completion_tokens")
+    return None, None
+
+
+def _run_to_span(run: dict, include_content: bool) -> Dict[str, Any]:
+    """Map a run onto the fields of an OTLP span."""
+    run_type = run.get("run_type") or "chain"
+    metadata = (run.get("extra") or {}).get("metadata") or {}
+    attributes: Dict[str, Any] = {
+        "langsmith.run.id": str(run["id"]),
+        "langsmith.trace.id": str(run["trace_id"]),
+        "langsmith.span.kind": run_type,
+    }
+    if run.get("dotted_order"):
+        attributes["langsmith.span.dotted_order"] = run["dotted_order"]
+    if run.get("parent_run_id"):
+        attributes["langsmith.run.parent_id"] = str(run["parent_run_id"])
+    if run.get("session_name"):
+        attributes["langsmith.trace.session_name"] = run["session_name"]
+    if run.get("session_id"):
+        attributes["langsmith.trace.session_id"] = str(run["session_id"])
+    if run.get("tags"):
+        attributes["langsmith.span.tags"] = [str(tag) for tag in run["tags"]]
+    for key, value in metadata.items():
+        if value is not None:
+            attributes[f"langsmith.metadata.{key}"] = _otel_attribute(value)
+    if run_type == "llm":
+        if metadata.get("ls_provider"):
+            attributes["gen_ai.system"] = metadata["ls_provider"]
+        if metadata.get("ls_model_name"):
+            attributes["gen_ai.request.model"] = metadata["ls_model_name"]
+        attributes["gen_ai.operation.name"] = (
+            "text_completion" if metadata.get("ls_model_type") == "llm" else "chat"
+        )
+    input_tokens, output_tokens = _usage(run)
+    if input_tokens is not None:
+        attributes["gen_ai.usage.input_tokens"] = input_tokens
+    if output_tokens is not None:
+        attributes["gen_ai.usage.output_tokens"] = output_tokens
+    if include_content:
+        if run.get("inputs"):
+            attributes["langsmith.inputs"] = _otel_attribute(run["inputs"])
+        if run.get("outputs"):
+            attributes["langsmith.outputs"] = _otel_attribute(run["outputs"])
+
+    start = _time_ns(run.get("start_time")) or 0
+    events = []
+    for event in run.get("events") or []:
+        kwargs = event.get("kwargs") or {}
+        events.append(
+            {
+                "name": str(event.get("name", "event")),
+                "time": _time_ns(event.get("time")) or start,
+                "attributes": {
+                    str(k): _otel_attribute(v)
+                    for k, v in kwargs.items()
+                    if v is not None
+                },
+            }
+        )
+    error = run.get("error")
+    return {
+        "trace_id": uuid.UUID(str(run["trace_id"])).bytes,
+        "span_id": run_id_to_span_id(run["id"]),
+        "parent_span_id": (
+            run_id_to_span_id(run["parent_run_id"]) if run.get("parent_run_id") else b""
+        ),
+        "name": str(run.get("name") or "Unnamed"),
+        # CLIENT for calls to models, INTERNAL otherwise
+        "kind": 3 if run_type in ("llm", "embedding") else 1,
+        "start_time": start,
+        "end_time": _time_ns(run.get("end_time")) or start,
+        "attributes": attributes,
+        "events": events,
+        "status": (2, str(error)) if error else (0, ""),
+    }
+
+
+# A minimal encoder of the protobuf wire format, for the OTLP trace messages in
+# opentelemetry/proto/collector/trace/v1/trace_service.proto
+
+
+def _varint(value: int) -> bytes:
+    value &= (1 << 64) - 1  # negative int64 values are two's complement
+    out = bytearray()
+    while True:
+        byte, value = value & 0x7F, value >> 7
+        if value:
+            out.append(byte | 0x80)
+        else:
+            out.append(byte)
+            return bytes(out)
+
+
+def _pb_varint(number: int, value: int) -> bytes:
+    return _varint(number << 3) + _varint(value)
+
+
+def _pb_fixed64(number: int, value: int) -> bytes:
+    return _varint(number << 3 | 1) + struct.pack("<Q", value)
+
+
+def _pb_double(number: int, value: float) -> bytes:
+    return _varint(number << 3 | 1) + struct.pack("<d", value)
+
+
+def _pb_bytes(number: int, value: Union[bytes, str]) -> bytes:
+    if isinstance(value, str):
+        value = value.encode("utf-8")
+    return _varint(number << 3 | 2) + _varint(len(value)) + value
+
+
+def _pb_any_value(value: Any) -> bytes:
+    if isinstance(value, bool):
+        return _pb_varint(2, int(value))
+    if isinstance(value, int):
+        return _pb_varint(3, value)
+    if isinstance(value, float):
+        return _pb_double(4, value)
+    if isinstance(value, list):
+        values = b"".join(_pb_bytes(1, _pb_any_value(v)) for v in value)
+        return _pb_bytes(5, values)
+    return _pb_bytes(1, str(value))
+
+
+def _pb_attributes(number: int, attributes: Mapping[str, Any]) -> bytes:
+    return b"".join(
+        _pb_bytes(number, _pb_bytes(1, key) + _pb_bytes(2, _pb_any_value(value)))
+        for key, value in attributes.items()
+    )
+
+
+def _encode_protobuf(
+    spans: Sequence[Dict[str, Any]], resource: Mapping[str, Any]
+) -> bytes:
+    encoded_spans = b""
+    for span in spans:
+        status_code, status_message = span["status"]
+        encoded = (
+            _pb_bytes(1, span["trace_id"])
+            + _pb_bytes(2, span["span_id"])
+            + (_pb_bytes(4, span["parent_span_id"]) if span["parent_span_id"] else b"")
+            + _pb_bytes(5, span["name"])
+            + _pb_varint(6, span["kind"])
+            + _pb_fixed64(7, span["start_time"])
+            + _pb_fixed64(8, span["end_time"])
+            + _pb_attributes(9, span["attributes"])
+            + b"".join(
+                _pb_bytes(
+                    11,
+                    _pb_fixed64(1, event["time"])
+                    + _pb_bytes(2, event["name"])
+                    + _pb_attributes(3, event["attributes"]),
+                )
+                for event in span["events"]
+            )
+            + _pb_bytes(15, _pb_bytes(2, status_message) + _pb_varint(3, status_code))
+        )
+        encoded_spans += _pb_bytes(2, encoded)
+    scope_spans = _pb_bytes(1, _pb_bytes(1, "langsmith")) + encoded_spans
+    resource_spans = _pb_bytes(1, _pb_attributes(1, resource))
+    resource_spans += _pb_bytes(2, scope_spans)
+    return _pb_bytes(1, resource_spans)
+
+
+def _json_any_value(value: Any) -> dict:
+    if isinstance(value, bool):
+        return {"boolValue": value}
+    if isinstance(value, int):
+        # int64 values are strings in the JSON encoding
+        return {"intValue": str(value)}
+    if isinstance(value, float):
+        return {"doubleValue": value}
+    if isinstance(value, list):
+        return {"arrayValue": {"values": [_json_any_value(v) for v in value]}}
+    return {"stringValue": str(value)}
+
+
+def _json_attributes(attributes: Mapping[str, Any]) -> List[dict]:
+    return [
+        {"key": key, "value": _json_any_value(value)}
+        for key, value in attributes.items()
+    ]
+
+
+def _encode_json(spans: Sequence[Dict[str, Any]], resource: Mapping[str, Any]) -> bytes:
+    encoded_spans = []
+    for span in spans:
+        status_code, status_message = span["status"]
+        encoded: Dict[str, Any] = {
+            # IDs are hex in the JSON encoding, rather than base64
+            "traceId": span["trace_id"].hex(),
+            "spanId": span["span_id"].hex(),
+            "name": span["name"],
+            "kind": span["kind"],
+            "startTimeUnixNano": str(span["start_time"]),
+            "endTimeUnixNano": str(span["end_time"]),
+            "attributes": _json_attributes(span["attributes"]),
+            "events": [
+                {
+                    "timeUnixNano": str(event["time"]),
+                    "name": event["name"],
+                    "attributes": _json_attributes(event["attributes"]),
+                }
+                for event in span["events"]
+            ],
+            "status": {"code": status_code, "message": status_message},
+        }
+        if span["parent_span_id"]:
+            encoded["parentSpanId"] = span["parent_span_id"].hex()
+        encoded_spans.append(encoded)
+    request = {
+        "resourceSpans": [
+            {
+                "resource": {"attributes": _json_attributes(resource)},
+                "scopeSpans": [
+                    {"scope": {"name": "langsmith"}, "spans": encoded_spans}
+                ],
+            }
+        ]
+    }
+    return json.dumps(request).encode("utf-8")
+
+
+def _parse_otlp_headers(value: Optional[str]) -> Dict[str, str]:
+    headers = {}
+    for pair in (value or "").split(","):
+        key, sep, header = pair.partition("=")
+        if sep:
+            headers[key.strip()] = urllib.parse.unquote(header.strip())
+    return headers
+
+
+class OTLPExporter:
+    """A :class:`~langsmith.exporters.TraceExporter` that sends runs as OTLP spans.
+
+    Runs are posted to an OpenTelemetry collector over OTLP/HTTP once they end,
+    encoded as protobuf or JSON. A run becomes a span with the trace ID of its
+    trace's UUID and a span ID made of the last 64 bits of its own UUID (see
+    :func:`run_id_to_span_id`). Its IDs, project, tags, metadata and token usage
+    are set as span attributes. The attribute names are the ones
+    :class:`LangSmithSpanExporter` reads, so the spans can be turned back into
+    runs.
+
+    The endpoint and headers default to the standard
+    ``OTEL_EXPORTER_OTLP_TRACES_ENDPOINT``, ``OTEL_EXPORTER_OTLP_ENDPOINT`` and
+    ``OTEL_EXPORTER_OTLP_HEADERS`` environment variables.
+
+    Example:
+        .. code-block:: python
+
+            from langsmith import Client
+            from langsmith.exporters import LangSmithExporter
+            from langsmith.otel import OTLPExporter
+
+            client = Client(
+                exporters=[
+                    LangSmithExporter(),
+                    OTLPExporter("http://otel-collector:4318/v1/traces"),
+                ]
+            )
+    """
+
+    def __init__(
+        self,
+        endpoint: Optional[str] = None,
+        *,
+        encoding: Literal["protobuf", "json"] = "protobuf",
+        headers: Optional[Mapping[str, str]] = None,
+        service_name: Optional[str] = None,
+        include_content: bool = True,
+        timeout: float = 10.0,
+        max_buffered_runs: int = 10_000,
+        session: Optional[requests.Session] = None,
+    ) -> None:
+        """Initialize the exporter.
+
+        Args:
+            endpoint: The URL of the collector's OTLP/HTTP traces endpoint.
+                Defaults to ``http://localhost:4318/v1/traces``.
+            encoding: Whether to send protobuf or JSON requests.
+            headers: Headers to send with every request, e.g. for authentication.
+            service_name: The ``service.name`` of the resource. Defaults to the
+                ``OTEL_SERVICE_NAME`` environment variable, or ``"langsmith"``.
+            include_content: Whether to set the inputs and outputs of runs as
+                the ``langsmith.inputs`` and ``langsmith.outputs`` attributes.
+            timeout: The timeout of each request, in seconds.
+            max_buffered_runs: The maximum number of runs to hold while waiting
+                for them to end. The oldest are dropped beyond that.
+            session: The session to send requests with.
+        """
+        if endpoint is None:
+            endpoint = os.environ.get("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT")
+        if endpoint is None:
+            base = os.environ.get("OTEL_EXPORTER_OTLP_ENDPOINT")
+            endpoint = (base or "http://localhost:4318").rstrip("/") + "/v1/traces"
+        self.endpoint = endpoint
+        self.encoding = encoding
+        self.headers = {
+            **_parse_otlp_headers(os.environ.get("OTEL_EXPORTER_OTLP_HEADERS")),
+            **(headers or {}),
+            "Content-Type": (
+                "application/json" if encoding == "json" else "application/x-protobuf"
+            ),
+        }
+        self.resource = {
+            "service.name": service_name
+            or os.environ.get("OTEL_SERVICE_NAME")
+            or "langsmith"
+        }
+        self.include_content = include_content
+        self.timeout = timeout
+        self.max_buffered_runs = max_buffered_runs
+        self.session = session or requests.Session()
+        self._lock = threading.Lock()
+        self._runs: collections.OrderedDict[str, dict] = collections.OrderedDict()
+
+    def export(self, batch: TraceBatch) -> bool:
+        """Send the runs of the batch that have ended."""
+        finished = []
+        with self._lock:
+            for run in batch.create + batch.update:
+                run_id = str(run["id"])
+                _merge_runs([run], self._runs)
+                if self._runs[run_id].get("end_time") is not None:
+                    finished.append(self._runs.pop(run_id))
+            if len(self._runs) > self.max_buffered_runs:
+                logger.warning(
+                    f"Dropping {len(self._runs) - self.max_buffered_runs} runs"
+                    " that did not end before the OTLP exporter's buffer filled."
+                )
+                while len(self._runs) > self.max_buffered_runs:
+                    self._runs.popitem(last=False)
+        spans = [
+            _run_to_span(run, self.include_content)
+            for run in finished
+            if run.get("trace_id")
+        ]
+        if not spans:
+            return True
+        if self.encoding == "protobuf":
+            body = _encode_protobuf(spans, self.resource)
+        else:
+            body = _encode_json(spans, self.resource)
+        try:
+            response = self.session.post(
+                self.endpoint, data=body, headers=self.headers, timeout=self.timeout
+            )
+            response.raise_for_status()
+        except requests.RequestException as e:
+            logger.warning(f"Failed to send {len(spans)} spans to {self.endpoint}: {e}")
+            return False
+        return True
+
+    def shutdown(self) -> None:
+        """Close the session, warning about runs that never ended."""
+        with self._lock:
+            if self._runs:
+                logger.warning(
+                    f"{len(self._runs)} runs were not sent to {self.endpoint}"
+                    " because they never ended."
+                )
+            self._runs.clear()
+        self.session.close()
+
+
+__all__ = [
+    "LangSmithSpanExporter",
+    "OTLPExporter",
+    "run_id_to_span_id",
+    "span_id_to_run_id",
+]
@@ -0,0 +1,116 @@
+"""Limits on the size of the run inputs and outputs sent to LangSmith."""
+
+from __future__ import annotations
+
+import dataclasses
+from typing import Any, Callable, Dict, Literal, Optional, Tuple
+
+from langsmith import schemas as ls_schemas
+from langsmith import utils as ls_utils
+from langsmith._internal._serde import dumps_json as _dumps_json
+from langsmith._internal._serde import truncate_text as _truncate_text
+
+PayloadLimitAction = Literal["attach", "truncate", "summarize"]
+
+_ACTIONS = ("attach", "truncate", "summarize")
+
+
+@dataclasses.dataclass
+class PayloadLimit:
+    """What to do with input and output fields too large to send as they are.
+
+    Applies to each top-level key of a run's inputs and outputs whose
+    serialized value is over ``max_bytes``, instead of letting the whole run
+    fail to ingest:
+
+    - "attach" moves the value into a JSON attachment of the run named
+      ``{inputs|outputs}-{key}``. Attachments need the multipart endpoint,
+      so values are truncated when it isn't available.
+    - "truncate" cuts the value down to ``max_bytes``, ending it with a marker.
+    - "summarize" replaces the value with ``summarizer(key, value)``, which is
+      truncated if it's still too large.
+
+    Example:
+        .. code-block:: python
+
+            from langsmith import Client
+            from langsmith.payload_limits import PayloadLimit
+
+            client = Client(payload_limit=PayloadLimit(max_bytes=1_000_000))
+    """
+
+    max_bytes: int
+    action: PayloadLimitAction = "attach"
+    summarizer: Optional[Callable[[str, Any], Any]] = None
+
+    def __post_init__(self) -> None:
+        """Check the action has what it needs."""
+        if self.action not in _ACTIONS:
+            raise ls_utils.LangSmithUserError(
+                f"Unsupported payload limit action {self.action!r}. Expected one "
+                f"of {', '.join(_ACTIONS)}."
+            )
+        if self.action == "summarize" and self.summarizer is None:
+            raise ls_utils.LangSmithUserError(
+                'The "summarize" payload limit action requires a summarizer.'
+            )
+
+    def apply(
+        self, field: str, values: Any, *, can_attach: bool = True
+    ) -> Tuple[Any, ls_schemas.Attachments]:
+        """Shrink the oversized values of a run's inputs or outputs.
+
+        Args:
+            field: "inputs" or "outputs".
+            values: The run's inputs or outputs.
+            can_attach: Whether values can be moved to attachments.
+
+        Returns:
+            The values to send, and the attachments to send with them.
+        """
+        attachments: ls_schemas.Attachments = {}
+        if not isinstance(values, dict):
+            return values, attachments
+        limited: Optional[Dict[str, Any]] = None
+        for key, value in values.items():
+            serialized = _dumps_json(value)
+            if len(serialized) <= self.max_bytes:
+                continue
+            if limited is None:
+                limited = dict(values)
+            if self.action == "attach" and can_attach:
+                name = f"{field}-{key}"
+                attachments[name] = ("application/json", serialized)
+                limited[key] = (
+                    f"[{len(serialized)} bytes moved to attachment {name!r}]"
+                )
+            elif self.action == "summarize":
+                assert self.summarizer is not None
+                summary = self.summarizer(key, value)
+                summary_bytes = _dumps_json(summary)
+                limited[key] = (
+                    summary
+                    if len(summary_bytes) <= self.max_bytes
+                    else self._truncate(summary, summary_bytes)
+                )
+            else:
+                limited[key] = self._truncate(value, serialized)
+        return (values if limited is None else limited), attachments
+
+    def _truncate(self, value: Any, serialized: bytes) -> str:
+        # Keep strings as they are, and anything else as JSON text
+        text = value.encode("utf-8") if isinstance(value, str) else serialized
+        return _truncate_text(text, self.max_bytes)
+
+
+def get_payload_limit_from_env() -> Optional[PayloadLimit]:
+    """Create a payload limit from the environment, if one is configured.
+
+    Reads LANGSMITH_TRACING_PAYLOAD_MAX_BYTES and optionally
+    LANGSMITH_TRACING_PAYLOAD_LIMIT_ACTION, "attach" or "truncate".
+    """
+    max_bytes = ls_utils.get_env_var("TRACING_PAYLOAD_MAX_BYTES")
+    if not max_bytes:
+        return None
+    action = ls_utils.get_env_var("TRACING_PAYLOAD_LIMIT_ACTION") or "attach"
+    return PayloadLimit(int(max_bytes), action)  # type: ignore[arg-type]
@@ -9,7 +9,6 @@ import datetime
 import functools
 import inspect
 import logging
-import uuid
 import warnings
 from contextvars import copy_context
 from typing import (
@@ -43,6 +42,7 @@ from typing_extensions import ParamSpec, TypeGuard
 from langsmith import client as ls_client
 from langsmith import run_trees, utils
 from langsmith._internal import _aiter as aitertools
+from langsmith._internal import _uuid
 from langsmith.env import _runtime_env
 
 if TYPE_CHECKING:
@@ -1333,7 +1333,7 @@ def _setup_run(
             on_end=langsmith_extra.get("on_end"),
             context=copy_context(),
         )
-    id_ = id_ or str(uuid.uuid4())
+    id_ = id_ or str(_uuid.new_run_id())
     signature = inspect.signature(func)
     name_ = name or utils._get_function_name(func)
     docstring = func.__doc__
@@ -5,9 +5,9 @@ from __future__ import annotations
 import json
 import logging
 import sys
-from datetime import datetime, timezone
+from datetime import datetime, timedelta, timezone
 from typing import Any, Dict, List, Mapping, Optional, Sequence, Tuple, Union, cast
-from uuid import UUID, uuid4
+from uuid import UUID
 
 try:
     from pydantic.v1 import Field, root_validator  # type: ignore[import]
@@ -17,11 +17,14 @@ except ImportError:
         root_validator,
     )
 
+import base64
+import struct
 import threading
 import urllib.parse
 
 from langsmith import schemas as ls_schemas
 from langsmith import utils
+from langsmith._internal import _uuid
 from langsmith.client import ID_TYPE, RUN_TYPE_T, Client, _dumps_json, _ensure_uuid
 
 logger = logging.getLogger(__name__)
@@ -32,6 +35,9 @@ LANGSMITH_DOTTED_ORDER_BYTES = LANGSMITH_DOTTED_ORDER.encode("utf-8")
 LANGSMITH_METADATA = sys.intern(f"{LANGSMITH_PREFIX}metadata")
 LANGSMITH_TAGS = sys.intern(f"{LANGSMITH_PREFIX}tags")
 LANGSMITH_PROJECT = sys.intern(f"{LANGSMITH_PREFIX}project")
+# W3C trace context headers, see https://www.w3.org/TR/trace-context/
+W3C_TRACEPARENT = sys.intern("traceparent")
+W3C_TRACESTATE = sys.intern("tracestate")
 _CLIENT: Optional[Client] = None
 _LOCK = threading.Lock()  # Keeping around for a while for backwards compat
 
@@ -51,7 +57,7 @@ class RunTree(ls_schemas.RunBase):
     """Run Schema with back-references for posting runs."""
 
     name: str
-    id: UUID = Field(default_factory=uuid4)
+    id: UUID = Field(default_factory=_uuid.new_run_id)
     run_type: str = Field(default="chain")
     start_time: datetime = Field(default_factory=lambda: datetime.now(timezone.utc))
     parent_run: Optional[RunTree] = Field(default=None, exclude=True)
@@ -101,7 +107,7 @@ class RunTree(ls_schemas.RunBase):
         if values.get("parent_run") is not None:
             values["parent_run_id"] = values["parent_run"].id
         if "id" not in values:
-            values["id"] = uuid4()
+            values["id"] = _uuid.new_run_id()
         if "trace_id" not in values:
             if "parent_run" in values:
                 values["trace_id"] = values["parent_run"].trace_id
@@ -122,15 +128,20 @@ class RunTree(ls_schemas.RunBase):
         current_dotted_order = values.get("dotted_order")
         if current_dotted_order and current_dotted_order.strip():
             return values
-        current_dotted_order = _create_current_dotted_order(
-            values["start_time"], values["id"]
+        parent_run = values["parent_run"]
+        values["dotted_order"] = _create_dotted_order(
+            values["start_time"],
+            values["id"],
+            parent_run.dotted_order if parent_run else None,
         )
-        if values["parent_run"]:
-            values["dotted_order"] = (
-                values["parent_run"].dotted_order + "." + current_dotted_order
+        if parent_run and values["start_time"] is not None:
+            # A child that seems to start before its parent takes its parent's
+            # start time in the dotted order, so keep its start time the same
+            segment = values["dotted_order"].rsplit(".", 1)[-1]
+            ((start_time, _),) = _parse_dotted_order(segment)
+            values["start_time"] = start_time.replace(
+                tzinfo=values["start_time"].tzinfo
             )
-        else:
-            values["dotted_order"] = current_dotted_order
         return values
 
     @property
@@ -286,11 +297,9 @@ class RunTree(ls_schemas.RunBase):
             exclude={"child_runs", "inputs", "outputs"}, exclude_none=True
         )
         if self.inputs is not None:
-            # shallow copy. deep copying will occur in the client
-            self_dict["inputs"] = self.inputs.copy()
+            self_dict["inputs"] = utils.snapshot(self.inputs)
         if self.outputs is not None:
-            # shallow copy; deep copying will occur in the client
-            self_dict["outputs"] = self.outputs.copy()
+            self_dict["outputs"] = utils.snapshot(self.outputs)
         return self_dict
 
     def post(self, exclude_child_runs: bool = True) -> None:
@@ -417,7 +426,9 @@ class RunTree(ls_schemas.RunBase):
 
         Extracts parent span information from the headers and creates a new span.
         Metadata and tags are extracted from the baggage header.
-        The dotted order and trace id are extracted from the trace header.
+        The dotted order and trace id are extracted from the trace header, or
+        from the W3C ``traceparent`` and ``tracestate`` headers if it is missing.
+        See :meth:`to_headers` for how they map onto each other.
 
         Returns:
             Optional[RunTree]: The new span or None if
@@ -430,9 +441,16 @@ class RunTree(ls_schemas.RunBase):
             langsmith_trace_bytes = cast(
                 Optional[bytes], headers.get(LANGSMITH_DOTTED_ORDER_BYTES)
             )
-            if not langsmith_trace_bytes:
+            if langsmith_trace_bytes:
+                langsmith_trace = langsmith_trace_bytes.decode("utf-8")
+            else:
+                # e.g. a gateway that only forwards standard tracing headers
+                langsmith_trace = _dotted_order_from_trace_context(
+                    _get_header(headers, W3C_TRACEPARENT),
+                    _get_header(headers, W3C_TRACESTATE),
+                )
+            if not langsmith_trace:
                 return  # type: ignore[return-value]
-            langsmith_trace = langsmith_trace_bytes.decode("utf-8")
 
         parent_dotted_order = langsmith_trace.strip()
         parsed_dotted_order = _parse_dotted_order(parent_dotted_order)
@@ -467,10 +485,30 @@ class RunTree(ls_schemas.RunBase):
         return RunTree(**init_args)
 
     def to_headers(self) -> Dict[str, str]:
-        """Return the RunTree as a dictionary of headers."""
+        """Return the RunTree as a dictionary of headers.
+
+        Besides the ``langsmith-trace`` and ``baggage`` headers, the run is
+        propagated with the W3C ``traceparent`` and ``tracestate`` headers, which
+        proxies and service meshes forward even when they drop other headers:
+
+        - The trace ID is the 128 bits of the LangSmith trace ID, i.e. of the ID
+          of the root run.
+        - The parent ID is the last 64 bits of the run ID.
+        - The ``langsmith`` entry of ``tracestate`` holds the dotted order. Each
+          of its segments is encoded as the big-endian microseconds since the
+          epoch (8 bytes) followed by the run ID (16 bytes), in unpadded
+          URL-safe base64, and segments are joined with ``.``.
+
+        ``tracestate`` entries are limited to 256 characters, so the entry is
+        left out for runs more than 7 levels deep, counting the root. Services
+        then need the ``langsmith-trace`` header to continue the trace.
+        """
         headers = {}
         if self.trace_id:
             headers[f"{LANGSMITH_DOTTED_ORDER}"] = self.dotted_order
+            headers[W3C_TRACEPARENT] = _to_traceparent(self.trace_id, self.id)
+            if tracestate := _dotted_order_to_tracestate(self.dotted_order):
+                headers[W3C_TRACESTATE] = f"langsmith={tracestate}"
         baggage = _Baggage(
             metadata=self.extra.get("metadata", {}),
             tags=self.tags,
@@ -554,13 +592,134 @@ def _parse_dotted_order(dotted_order: str) -> List[Tuple[datetime, UUID]]:
     ]
 
 
+def _get_header(
+    headers: Mapping[Union[str, bytes], Union[str, bytes]], name: str
+) -> Optional[str]:
+    value = headers.get(name)
+    if value is None:
+        value = headers.get(name.encode("utf-8"))
+    if isinstance(value, bytes):
+        return value.decode("utf-8")
+    return value
+
+
+def _to_traceparent(trace_id: UUID, run_id: UUID) -> str:
+    return f"00-{trace_id.hex}-{run_id.hex[16:]}-01"
+
+
+def _dotted_order_to_tracestate(dotted_order: str) -> Optional[str]:
+    """Encode a dotted order as the value of a ``tracestate`` entry."""
+    segments = []
+    for start_time, run_id in _parse_dotted_order(dotted_order):
+        timestamp = start_time.replace(tzinfo=timezone.utc).timestamp()
+        micros = int(timestamp) * 1_000_000 + start_time.microsecond
+        encoded = base64.urlsafe_b64encode(struct.pack(">q", micros) + run_id.bytes)
+        segments.append(encoded.decode("ascii"))
+    value = ".".join(segments)
+    return value if len(value) <= 256 else None
+
+
+def _dotted_order_from_trace_context(
+    traceparent: Optional[str], tracestate: Optional[str]
+) -> Optional[str]:
+    """Return the dotted order from the ``langsmith`` entry of ``tracestate``.
+
+    The entry is only used if it belongs to the trace in ``traceparent``.
+    """
+    if not traceparent or not tracestate:
+        return None
+    parts = traceparent.strip().split("-")
+    if len(parts) < 4 or len(parts[1]) != 32:
+        return None
+    for member in tracestate.split(","):
+        key, _, value = member.strip().partition("=")
+        if key != "langsmith":
+            continue
+        try:
+            segments = []
+            for segment in value.split("."):
+                decoded = base64.urlsafe_b64decode(segment)
+                (micros,) = struct.unpack(">q", decoded[:8])
+                start_time = datetime(1970, 1, 1) + timedelta(microseconds=micros)
+                segments.append(
+                    _create_current_dotted_order(start_time, UUID(bytes=decoded[8:]))
+                )
+        except (ValueError, struct.error) as e:
+            logger.warning(f"Error parsing tracestate header: {e}")
+            return None
+        if UUID(segments[0][-36:]).hex != parts[1].lower():
+            return None
+        return ".".join(segments)
+    return None
+
+
 def _create_current_dotted_order(
     start_time: Optional[datetime], run_id: Optional[UUID]
 ) -> str:
     """Create the current dotted order."""
     st = start_time or datetime.now(timezone.utc)
-    id_ = run_id or uuid4()
+    id_ = run_id or _uuid.new_run_id()
     return st.strftime("%Y%m%dT%H%M%S%fZ") + str(id_)
 
 
-__all__ = ["RunTree", "RunTree"]
+def _create_dotted_order(
+    start_time: Optional[datetime],
+    run_id: Optional[UUID],
+    parent_dotted_order: Optional[str] = None,
+) -> str:
+    """Create the dotted order of a run, never ordered before its parent.
+
+    The clocks of the services in a distributed trace can disagree, so a
+    child may seem to start before its parent. Its segment then takes its
+    parent's start time, and its id breaks the tie.
+    """
+    current = _create_current_dotted_order(start_time, run_id)
+    if not parent_dotted_order:
+        return current
+    parent_time = parent_dotted_order.rsplit(".", 1)[-1][:-36]
+    if current[:-36] < parent_time:
+        current = parent_time + current[-36:]
+    return f"{parent_dotted_order}.{current}"
+
+
+def validate_dotted_order(dotted_order: str) -> List[str]:
+    """Describe what's wrong with a dotted order, if anything.
+
+    Each segment must be a start time and a run id, and start no earlier
+    than the segment before it.
+
+    Returns:
+        List[str]: The problems found, empty if the dotted order is valid.
+    """
+    problems = []
+    previous: Optional[datetime] = None
+    for i, segment in enumerate(dotted_order.split(".")):
+        try:
+            ((start_time, _),) = _parse_dotted_order(segment)
+        except ValueError:
+            problems.append(f"Segment {i} is malformed: {segment!r}")
+            continue
+        if previous is not None and start_time < previous:
+            problems.append(
+                f"Segment {i} starts at {start_time.isoformat()}, before its"
+                f" parent at {previous.isoformat()}"
+            )
+        previous = max(start_time, previous or start_time)
+    return problems
+
+
+def repair_dotted_order(dotted_order: str) -> str:
+    """Move each segment of a dotted order up to its parent's start time.
+
+    Only segments that start before their parent change, so repairing the
+    dotted orders of a parent and a child keeps one the prefix of the other.
+    """
+    segments = dotted_order.split(".")
+    for i in range(1, len(segments)):
+        parent_time = segments[i - 1][:-36]
+        if segments[i][:-36] < parent_time:
+            segments[i] = parent_time + segments[i][-36:]
+    return ".".join(segments)
+
+
+__all__ = ["RunTree", "validate_dotted_order", "repair_dotted_order"]
@@ -0,0 +1,411 @@
+"""Sampling policies for traces sent to LangSmith."""
+
+from __future__ import annotations
+
+import collections
+import dataclasses
+import datetime
+import fnmatch
+import json
+import logging
+import random
+import threading
+import time
+from typing import Any, Callable, Dict, List, Mapping, Optional, Sequence, Set
+
+from langsmith import utils as ls_utils
+from langsmith._internal._background_thread import TracingQueueItem
+
+logger = logging.getLogger(__name__)
+
+
+@dataclasses.dataclass
+class SamplingRule:
+    """Set the sample rate for root runs matching every given condition.
+
+    String conditions are glob patterns, e.g. ``name="retriever*"``. Rules are
+    evaluated in order against each root run and the first match decides
+    whether the trace is sent. Child runs follow their trace's decision.
+
+    Example:
+        .. code-block:: python
+
+            from langsmith import Client
+            from langsmith.sampling import SamplingRule
+
+            client = Client(
+                sampling_rules=[
+                    SamplingRule(sample_rate=1.0, run_type="chain", tags=["agent"]),
+                    SamplingRule(sample_rate=0.01, name="retriever*"),
+                ]
+            )
+    """
+
+    sample_rate: float
+    """The fraction of matching traces to send."""
+    session_name: Optional[str] = None
+    """Match the project the run is logged to."""
+    name: Optional[str] = None
+    """Match the name of the run."""
+    run_type: Optional[str] = None
+    """Match the run type, e.g. "llm" or "retriever"."""
+    tags: Optional[Sequence[str]] = None
+    """Match runs carrying any of these tags."""
+    metadata_keys: Optional[Sequence[str]] = None
+    """Match runs whose metadata has all of these keys."""
+
+    def __post_init__(self) -> None:
+        """Validate the sample rate."""
+        if self.sample_rate < 0 or self.sample_rate > 1:
+            raise ls_utils.LangSmithUserError(
+                f"Sample rate must be between 0 and 1. Got: {self.sample_rate}"
+            )
+
+    def matches(self, run: Mapping[str, Any]) -> bool:
+        """Check whether the rule applies to a run dict."""
+        for pattern, value in (
+            (self.session_name, run.get("session_name")),
+            (self.name, run.get("name")),
+            (self.run_type, run.get("run_type")),
+        ):
+            if pattern is not None and (
+                value is None or not fnmatch.fnmatchcase(str(value), pattern)
+            ):
+                return False
+        if self.tags is not None:
+            run_tags = run.get("tags") or ()
+            if not any(
+                fnmatch.fnmatchcase(tag, pattern)
+                for tag in run_tags
+                for pattern in self.tags
+            ):
+                return False
+        if self.metadata_keys is not None:
+            metadata = (run.get("extra") or {}).get("metadata") or {}
+            if not all(key in metadata for key in self.metadata_keys):
+                return False
+        return True
+
+
+def get_sample_rate(
+    rules: Sequence[SamplingRule],
+    run: Mapping[str, Any],
+    default: Optional[float] = None,
+) -> float:
+    """Get the sample rate of the first rule matching a run.
+
+    Args:
+        rules: The rules to evaluate, in order.
+        run: The root run dict.
+        default: The rate used when no rule matches. Defaults to 1.
+
+    Returns:
+        The sample rate for the run's trace.
+    """
+    for rule in rules:
+        if rule.matches(run):
+            return rule.sample_rate
+    return 1.0 if default is None else default
+
+
+def get_sampling_rules_from_env() -> List[SamplingRule]:
+    """Load rules from the LANGSMITH_TRACING_SAMPLING_RULES environment variable.
+
+    The variable holds a JSON list of rule objects, e.g.
+    ``[{"name": "retriever*", "sample_rate": 0.01}]``.
+    """
+    rules_str = ls_utils.get_env_var("TRACING_SAMPLING_RULES")
+    if not rules_str:
+        return []
+    try:
+        return [SamplingRule(**rule) for rule in json.loads(rules_str)]
+    except (TypeError, ValueError) as e:
+        raise ls_utils.LangSmithUserError(
+            f"Invalid LANGSMITH_TRACING_SAMPLING_RULES: {e}"
+        ) from e
+
+
+def _parse_time(value: Any) -> Optional[datetime.datetime]:
+    if isinstance(value, datetime.datetime):
+        return value
+    if isinstance(value, str):
+        try:
+            return datetime.datetime.fromisoformat(value.replace("Z", "+00:00"))
+        except ValueError:
+            return None
+    return None
+
+
+def _item_trace_id(item: TracingQueueItem) -> Optional[str]:
+    payload = item.item
+    trace_id = (
+        payload.get("trace_id")
+        if isinstance(payload, dict)
+        else getattr(payload, "trace_id", None)
+    )
+    return str(trace_id) if trace_id is not None else None
+
+
+@dataclasses.dataclass
+class CompletedTrace:
+    """A buffered trace handed to a tail sampling policy.
+
+    Each run merges its create and update payloads. Runs are sorted by
+    ``dotted_order``, so the root run comes first.
+    """
+
+    trace_id: str
+    """The ID of the trace (and of its root run)."""
+    runs: List[dict]
+    """The runs in the trace."""
+    feedback: List[dict]
+    """Feedback logged against runs in the trace while it was buffered."""
+    complete: bool = True
+    """False if the trace was evicted before its root run ended."""
+
+    @property
+    def root(self) -> Optional[dict]:
+        """The root run of the trace, if it was seen."""
+        return next(
+            (run for run in self.runs if str(run.get("id")) == self.trace_id), None
+        )
+
+    @property
+    def has_error(self) -> bool:
+        """Whether any run in the trace errored."""
+        return any(run.get("error") for run in self.runs)
+
+    @property
+    def latency(self) -> Optional[float]:
+        """The duration of the root run in seconds."""
+        root = self.root
+        if root is None:
+            return None
+        start = _parse_time(root.get("start_time"))
+        end = _parse_time(root.get("end_time"))
+        if start is None or end is None:
+            return None
+        return (end - start).total_seconds()
+
+    @property
+    def tags(self) -> Set[str]:
+        """The tags of every run in the trace."""
+        return {tag for run in self.runs for tag in run.get("tags") or ()}
+
+
+TailSamplingPolicy = Callable[[CompletedTrace], bool]
+"""Decide whether to send a trace once its root run has ended."""
+
+
+def keep_errors() -> TailSamplingPolicy:
+    """Keep traces in which any run errored."""
+    return lambda trace: trace.has_error
+
+
+def keep_slower_than(seconds: float) -> TailSamplingPolicy:
+    """Keep traces whose root run took longer than ``seconds``."""
+
+    def policy(trace: CompletedTrace) -> bool:
+        latency = trace.latency
+        return latency is not None and latency > seconds
+
+    return policy
+
+
+def keep_tagged(*tags: str) -> TailSamplingPolicy:
+    """Keep traces in which any run carries one of ``tags``."""
+    tags_ = set(tags)
+    return lambda trace: bool(trace.tags & tags_)
+
+
+def keep_with_feedback(*keys: str) -> TailSamplingPolicy:
+    """Keep traces with feedback, optionally restricted to the given keys."""
+
+    def policy(trace: CompletedTrace) -> bool:
+        return any(not keys or fb.get("key") in keys for fb in trace.feedback)
+
+    return policy
+
+
+def sample_rate(rate: float) -> TailSamplingPolicy:
+    """Keep a random fraction of traces."""
+    if rate < 0 or rate > 1:
+        raise ValueError(f"Sample rate must be between 0 and 1. Got: {rate}")
+    return lambda trace: random.random() < rate
+
+
+def any_of(*policies: TailSamplingPolicy) -> TailSamplingPolicy:
+    """Keep a trace if any of the policies keeps it.
+
+    Example:
+        .. code-block:: python
+
+            from langsmith import Client
+            from langsmith.sampling import TailSampler, any_of, keep_errors, sample_rate
+
+            client = Client(
+                tail_sampler=TailSampler(any_of(keep_errors(), sample_rate(0.05)))
+            )
+    """
+    return lambda trace: any(policy(trace) for policy in policies)
+
+
+@dataclasses.dataclass
+class _BufferedTrace:
+    items: List[TracingQueueItem] = dataclasses.field(default_factory=list)
+    created_at: float = dataclasses.field(default_factory=time.monotonic)
+
+
+class TailSampler:
+    """Buffer whole traces and decide whether to send them after they end.
+
+    Runs and feedback are held in memory, keyed by trace ID, until the root
+    run ends. The policy is then called with the complete trace. Traces whose
+    root run does not end within ``trace_timeout`` seconds, or that are
+    evicted because more than ``max_traces`` traces are buffered, are handed
+    to the policy as they are, with ``complete=False``.
+
+    Decisions are remembered for a while, so runs that arrive after their
+    root run ended follow the decision made for their trace. Items discarded
+    by a decision are counted in ``dropped_items``.
+    """
+
+    def __init__(
+        self,
+        policy: TailSamplingPolicy,
+        *,
+        max_traces: int = 1_000,
+        trace_timeout: float = 600.0,
+        max_decisions: int = 10_000,
+    ) -> None:
+        """Initialize the tail sampler."""
+        self.policy = policy
+        self.max_traces = max_traces
+        self.trace_timeout = trace_timeout
+        self.max_decisions = max_decisions
+        self.dropped_items = 0
+        self._lock = threading.Lock()
+        self._buffer: collections.OrderedDict[str, _BufferedTrace] = (
+            collections.OrderedDict()
+        )
+        self._decisions: collections.OrderedDict[str, bool] = (
+            collections.OrderedDict()
+        )
+
+    @property
+    def buffered_traces(self) -> int:
+        """The number of traces waiting for a decision."""
+        return len(self._buffer)
+
+    def add(self, item: TracingQueueItem) -> List[TracingQueueItem]:
+        """Buffer an item, returning the items that should now be sent."""
+        trace_id = _item_trace_id(item)
+        if trace_id is None:
+            return [item]
+        released: List[TracingQueueItem] = []
+        with self._lock:
+            decision = self._decisions.get(trace_id)
+            if decision is not None:
+                if not decision:
+                    self.dropped_items += 1
+                return [item] if decision else []
+            buffered = self._buffer.get(trace_id)
+            if buffered is None:
+                buffered = self._buffer[trace_id] = _BufferedTrace()
+            buffered.items.append(item)
+            if self._is_root_end(item, trace_id):
+                released.extend(self._decide(trace_id, complete=True))
+            released.extend(self._evict())
+        return released
+
+    def release_expired(self) -> List[TracingQueueItem]:
+        """Decide on traces that exceeded the timeout."""
+        with self._lock:
+            return self._evict()
+
+    def release_all(self) -> List[TracingQueueItem]:
+        """Decide on every buffered trace, e.g. before the process exits."""
+        released: List[TracingQueueItem] = []
+        with self._lock:
+            for trace_id in list(self._buffer):
+                released.extend(self._decide(trace_id, complete=False))
+        return released
+
+    def _reset_after_fork(self) -> None:
+        # Traces buffered before a fork are decided by the parent process
+        self._lock = threading.Lock()
+        self._buffer.clear()
+
+    @staticmethod
+    def _is_root_end(item: TracingQueueItem, trace_id: str) -> bool:
+        payload = item.item
+        return (
+            isinstance(payload, dict)
+            and item.action in ("create", "update")
+            and str(payload.get("id")) == trace_id
+            and payload.get("end_time") is not None
+        )
+
+    def _evict(self) -> List[TracingQueueItem]:
+        released: List[TracingQueueItem] = []
+        now = time.monotonic()
+        while self._buffer:
+            trace_id, buffered = next(iter(self._buffer.items()))
+            if (
+                len(self._buffer) <= self.max_traces
+                and now - buffered.created_at < self.trace_timeout
+            ):
+                break
+            released.extend(self._decide(trace_id, complete=False))
+        return released
+
+    def _decide(self, trace_id: str, *, complete: bool) -> List[TracingQueueItem]:
+        buffered = self._buffer.pop(trace_id)
+        trace = _to_completed_trace(trace_id, buffered.items, complete=complete)
+        try:
+            keep = bool(self.policy(trace))
+        except Exception as e:
+            logger.warning(f"Tail sampling policy failed, keeping trace: {e!r}")
+            keep = True
+        self._decisions[trace_id] = keep
+        while len(self._decisions) > self.max_decisions:
+            self._decisions.popitem(last=False)
+        if not keep:
+            self.dropped_items += len(buffered.items)
+        return buffered.items if keep else []
+
+
+def _to_completed_trace(
+    trace_id: str, items: Sequence[TracingQueueItem], *, complete: bool
+) -> CompletedTrace:
+    runs: Dict[str, dict] = {}
+    feedback: List[dict] = []
+    for item in items:
+        payload = item.item
+        if item.action == "feedback":
+            feedback.append(payload if isinstance(payload, dict) else payload.dict())
+            continue
+        run = runs.setdefault(str(payload["id"]), {})
+        run.update({k: v for k, v in payload.items() if v is not None})
+    return CompletedTrace(
+        trace_id=trace_id,
+        runs=sorted(runs.values(), key=lambda r: r.get("dotted_order") or ""),
+        feedback=feedback,
+        complete=complete,
+    )
+
+
+__all__ = [
+    "SamplingRule",
+    "get_sample_rate",
+    "get_sampling_rules_from_env",
+    "CompletedTrace",
+    "TailSampler",
+    "TailSamplingPolicy",
+    "any_of",
+    "keep_errors",
+    "keep_slower_than",
+    "keep_tagged",
+    "keep_with_feedback",
+    "sample_rate",
+]
@@ -5,6 +5,8 @@ from __future__ import annotations
 from datetime import datetime, timedelta, timezone
 from decimal import Decimal
 from enum import Enum
+from io import IOBase
+from pathlib import Path
 from typing import (
     Any,
     Dict,
@@ -43,8 +45,14 @@ from typing_extensions import Literal
 SCORE_TYPE = Union[StrictBool, StrictInt, StrictFloat, None]
 VALUE_TYPE = Union[Dict, str, None]
 
-Attachments = Dict[str, Tuple[str, bytes]]
-"""Attachments associated with the run. Each entry is a tuple of (mime_type, bytes)."""
+Attachments = Dict[str, Tuple[str, Union[bytes, Path, IOBase]]]
+"""Attachments associated with the run. Each entry is a tuple of (mime_type, data).
+
+The data is either the bytes of the attachment, the path of a file, or a binary
+stream. Files and streams are read as the run is sent rather than held in memory,
+so files must exist until then, and streams should be seekable so requests can be
+retried. A stream is read from the position it had when the run was sent.
+"""
 
 
 class ExampleBase(BaseModel):
@@ -259,7 +267,12 @@ class RunBase(BaseModel):
 
     attachments: Attachments = Field(default_factory=dict)
     """Attachments associated with the run.
-    Each entry is a tuple of (mime_type, bytes)."""
+    Each entry is a tuple of (mime_type, data)."""
+
+    class Config:
+        """Configuration class for the schema."""
+
+        arbitrary_types_allowed = True
 
     @property
     def metadata(self) -> dict[str, Any]:
@@ -659,6 +672,8 @@ class BatchIngestConfig(TypedDict, total=False):
     """The maximum size limit for the batch."""
     size_limit_bytes: Optional[int]
     """The maximum size limit in bytes for the batch."""
+    supported_compression_methods: List[str]
+    """The compression methods accepted for request bodies, e.g. gzip or zstd."""
 
 
 class LangSmithInfo(BaseModel):
@@ -0,0 +1,68 @@
+"""Custom JSON encoders for the objects in traced inputs and outputs."""
+
+from __future__ import annotations
+
+import enum
+import uuid
+from typing import Any, Callable, Optional, Type, TypeVar
+
+from langsmith import utils as ls_utils
+from langsmith._internal import _serde
+
+T = TypeVar("T")
+
+# orjson always serializes these itself
+_BUILTIN_TYPES = (str, int, float, bool, type(None), dict, list, tuple)
+
+
+def register_serializer(
+    cls: Type[T],
+    encoder: Callable[[T], Any],
+    *,
+    max_bytes: Optional[int] = None,
+) -> None:
+    """Serialize instances of a type with an encoder when tracing.
+
+    Applies to instances of ``cls`` and its subclasses wherever LangSmith
+    serializes run data, in place of the built-in handling, which falls back
+    to ``str(obj)`` for types it doesn't know. The encoder returns any
+    JSON-serializable value. If it raises, the object is serialized as if
+    no encoder was registered. Registering a type again replaces its encoder.
+
+    Args:
+        cls: The type to encode. Can't be a JSON builtin such as ``dict``, an
+            enum or ``uuid.UUID``, but can be a subclass of ``dict``,
+            ``list``, ``str`` or ``int``, a dataclass, a date or time type, or
+            a numpy type.
+        encoder: Converts an instance to a JSON-serializable value.
+        max_bytes: The most bytes an encoded instance takes when serialized.
+            Larger encodings are replaced with their JSON text, truncated to
+            ``max_bytes`` and ending with a marker.
+
+    Example:
+        .. code-block:: python
+
+            import numpy as np
+            from langsmith.serializers import register_serializer
+
+            register_serializer(
+                np.ndarray,
+                lambda a: {"shape": a.shape, "data": a.tolist()},
+                max_bytes=10_000,
+            )
+    """
+    if not isinstance(cls, type):
+        raise ls_utils.LangSmithUserError(f"Expected a type, got {cls!r}.")
+    if cls in _BUILTIN_TYPES or issubclass(cls, (enum.Enum, uuid.UUID)):
+        raise ls_utils.LangSmithUserError(
+            f"Can't register a serializer for {cls.__name__}, which is always "
+            "serialized as JSON directly."
+        )
+    if max_bytes is not None and max_bytes <= 0:
+        raise ls_utils.LangSmithUserError("max_bytes must be positive.")
+    _serde._set_serializer(cls, _serde._Serializer(encoder, max_bytes))
+
+
+def unregister_serializer(cls: type) -> None:
+    """Go back to the built-in serialization of a type."""
+    _serde._set_serializer(cls, None)
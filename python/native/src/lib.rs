@@ -0,0 +1,19 @@
+//! Optional compiled core of the LangSmith SDK.
+//!
+//! Every function produces the same bytes as the pure-Python code it speeds
+//! up, and returns `None` for inputs it can't, so the caller falls back to
+//! the pure-Python path.
+
+mod multipart;
+mod serialize;
+mod surrogates;
+
+use pyo3::prelude::*;
+
+#[pymodule]
+fn langsmith_native(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(serialize::dumps, m)?)?;
+    m.add_function(wrap_pyfunction!(surrogates::elide_surrogates, m)?)?;
+    m.add_function(wrap_pyfunction!(multipart::encode_multipart, m)?)?;
+    Ok(())
+}
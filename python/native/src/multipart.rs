@@ -0,0 +1,83 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyList, PyString, PyTuple};
+
+// Headers urllib3 renders first, and sets from the part tuple itself
+const SORTED_HEADERS: [&str; 3] = ["Content-Disposition", "Content-Type", "Content-Location"];
+
+/// Quote a header parameter value as urllib3's `format_multipart_header_param`.
+fn push_param(out: &mut Vec<u8>, value: &str) {
+    out.push(b'"');
+    for byte in value.bytes() {
+        match byte {
+            b'\n' => out.extend_from_slice(b"%0A"),
+            b'\r' => out.extend_from_slice(b"%0D"),
+            b'"' => out.extend_from_slice(b"%22"),
+            _ => out.push(byte),
+        }
+    }
+    out.push(b'"');
+}
+
+fn push_header(out: &mut Vec<u8>, name: &str, value: &str) {
+    out.extend_from_slice(name.as_bytes());
+    out.extend_from_slice(b": ");
+    out.extend_from_slice(value.as_bytes());
+    out.extend_from_slice(b"\r\n");
+}
+
+/// Encode one `(name, (None, body, content_type, headers))` part, or return
+/// `None` for a part with a file name or a body that isn't bytes.
+fn push_part(out: &mut Vec<u8>, boundary: &str, part: &Bound<'_, PyAny>) -> Option<()> {
+    let part = part.downcast::<PyTuple>().ok()?;
+    let (name, field) = (part.get_item(0).ok()?, part.get_item(1).ok()?);
+    let name = name.downcast::<PyString>().ok()?.to_str().ok()?;
+    let field = field.downcast::<PyTuple>().ok()?;
+    if part.len() != 2 || field.len() != 4 || !field.get_item(0).ok()?.is_none() {
+        return None;
+    }
+    let body = field.get_item(1).ok()?;
+    let body = body.downcast_exact::<PyBytes>().ok()?.as_bytes();
+    let content_type = field.get_item(2).ok()?;
+    let headers = field.get_item(3).ok()?;
+    let headers = headers.downcast::<PyDict>().ok()?;
+
+    out.extend_from_slice(b"--");
+    out.extend_from_slice(boundary.as_bytes());
+    out.extend_from_slice(b"\r\n");
+    out.extend_from_slice(b"Content-Disposition: form-data; name=");
+    push_param(out, name);
+    out.extend_from_slice(b"\r\n");
+    if content_type.is_truthy().ok()? {
+        let content_type = content_type.downcast::<PyString>().ok()?.to_str().ok()?;
+        push_header(out, "Content-Type", content_type);
+    }
+    for (key, value) in headers.iter() {
+        let key = key.downcast::<PyString>().ok()?.to_str().ok()?;
+        if SORTED_HEADERS.contains(&key) || !value.is_truthy().ok()? {
+            continue;
+        }
+        push_header(out, key, value.downcast::<PyString>().ok()?.to_str().ok()?);
+    }
+    out.extend_from_slice(b"\r\n");
+    out.extend_from_slice(body);
+    out.extend_from_slice(b"\r\n");
+    Some(())
+}
+
+/// Encode multipart parts as the body `MultipartEncoder(parts, boundary)`
+/// streams, or return `None` if any part isn't plain bytes.
+#[pyfunction]
+pub fn encode_multipart<'py>(
+    py: Python<'py>,
+    parts: &Bound<'py, PyList>,
+    boundary: &str,
+) -> Option<Bound<'py, PyBytes>> {
+    let mut out = Vec::new();
+    for part in parts.iter() {
+        push_part(&mut out, boundary, &part)?;
+    }
+    out.extend_from_slice(b"--");
+    out.extend_from_slice(boundary.as_bytes());
+    out.extend_from_slice(b"--\r\n");
+    Some(PyBytes::new(py, &out))
+}
@@ -0,0 +1,224 @@
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple, PyType};
+
+// Deeper objects are left to orjson, which has its own recursion limit
+const MAX_DEPTH: usize = 128;
+
+static UUID: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static DATE: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static DATETIME: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static TIME: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static TIMEZONE: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static ENUM: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+/// An object this serializer can't write exactly as orjson would.
+struct Unsupported;
+
+type Result<T = ()> = std::result::Result<T, Unsupported>;
+
+impl From<PyErr> for Unsupported {
+    fn from(_: PyErr) -> Self {
+        Unsupported
+    }
+}
+
+struct Serializer<'py> {
+    py: Python<'py>,
+    default: Bound<'py, PyAny>,
+    out: Vec<u8>,
+}
+
+impl<'py> Serializer<'py> {
+    fn write(&mut self, obj: &Bound<'py, PyAny>, depth: usize) -> Result {
+        if depth > MAX_DEPTH {
+            return Err(Unsupported);
+        }
+        if obj.is_none() {
+            self.out.extend_from_slice(b"null");
+        } else if let Ok(value) = obj.downcast_exact::<PyString>() {
+            self.write_str(value.to_str()?);
+        } else if let Ok(value) = obj.downcast_exact::<PyBool>() {
+            let literal: &[u8] = if value.is_true() { b"true" } else { b"false" };
+            self.out.extend_from_slice(literal);
+        } else if obj.is_exact_instance_of::<PyInt>() {
+            self.write_int(obj)?;
+        } else if let Ok(value) = obj.downcast_exact::<PyFloat>() {
+            self.write_float(value.value());
+        } else if let Ok(dict) = obj.downcast_exact::<PyDict>() {
+            self.out.push(b'{');
+            for (i, (key, value)) in dict.iter().enumerate() {
+                if i > 0 {
+                    self.out.push(b',');
+                }
+                // orjson converts other keys with OPT_NON_STR_KEYS
+                let key = key.downcast_exact::<PyString>().map_err(|_| Unsupported)?;
+                self.write_str(key.to_str()?);
+                self.out.push(b':');
+                self.write(&value, depth + 1)?;
+            }
+            self.out.push(b'}');
+        } else if let Ok(list) = obj.downcast_exact::<PyList>() {
+            self.write_items(list.iter(), depth)?;
+        } else if let Ok(tuple) = obj.downcast_exact::<PyTuple>() {
+            self.write_items(tuple.iter(), depth)?;
+        } else if obj.get_type().is(UUID.import(self.py, "uuid", "UUID")?) {
+            self.write_uuid(obj)?;
+        } else if obj
+            .get_type()
+            .is(DATETIME.import(self.py, "datetime", "datetime")?)
+        {
+            self.write_datetime(obj)?;
+        } else if self.is_serialized_by_orjson(obj)? {
+            return Err(Unsupported);
+        } else {
+            let value = self.default.call1((obj,))?;
+            self.write(&value, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    fn write_items(
+        &mut self,
+        items: impl Iterator<Item = Bound<'py, PyAny>>,
+        depth: usize,
+    ) -> Result {
+        self.out.push(b'[');
+        for (i, item) in items.enumerate() {
+            if i > 0 {
+                self.out.push(b',');
+            }
+            self.write(&item, depth + 1)?;
+        }
+        self.out.push(b']');
+        Ok(())
+    }
+
+    fn write_str(&mut self, value: &str) {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+        self.out.push(b'"');
+        let bytes = value.as_bytes();
+        let mut start = 0;
+        for (i, &byte) in bytes.iter().enumerate() {
+            let escape: &[u8] = match byte {
+                b'"' => b"\\\"",
+                b'\\' => b"\\\\",
+                b'\x08' => b"\\b",
+                b'\t' => b"\\t",
+                b'\n' => b"\\n",
+                b'\x0c' => b"\\f",
+                b'\r' => b"\\r",
+                0x00..=0x1f => &[],
+                _ => continue,
+            };
+            self.out.extend_from_slice(&bytes[start..i]);
+            if escape.is_empty() {
+                let code = [HEX[(byte >> 4) as usize], HEX[(byte & 0xf) as usize]];
+                self.out.extend_from_slice(b"\\u00");
+                self.out.extend_from_slice(&code);
+            } else {
+                self.out.extend_from_slice(escape);
+            }
+            start = i + 1;
+        }
+        self.out.extend_from_slice(&bytes[start..]);
+        self.out.push(b'"');
+    }
+
+    fn write_int(&mut self, obj: &Bound<'py, PyAny>) -> Result {
+        let mut buffer = itoa::Buffer::new();
+        let digits = match obj.extract::<i64>() {
+            Ok(value) => buffer.format(value),
+            // orjson rejects integers outside of the 64-bit range
+            Err(_) => buffer.format(obj.extract::<u64>()?),
+        };
+        self.out.extend_from_slice(digits.as_bytes());
+        Ok(())
+    }
+
+    fn write_float(&mut self, value: f64) {
+        if value.is_finite() {
+            let mut buffer = ryu::Buffer::new();
+            self.out
+                .extend_from_slice(buffer.format_finite(value).as_bytes());
+        } else {
+            self.out.extend_from_slice(b"null");
+        }
+    }
+
+    fn write_uuid(&mut self, obj: &Bound<'py, PyAny>) -> Result {
+        let hex = format!("{:032x}", obj.getattr("int")?.extract::<u128>()?);
+        let groups = [
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..],
+        ];
+        self.write_str(&groups.join("-"));
+        Ok(())
+    }
+
+    fn write_datetime(&mut self, obj: &Bound<'py, PyAny>) -> Result {
+        let tzinfo = obj.getattr("tzinfo")?;
+        if !tzinfo.is_none() {
+            // Offsets in seconds are formatted differently from isoformat()
+            let timezone = TIMEZONE.import(self.py, "datetime", "timezone")?;
+            let offset = obj.call_method0("utcoffset")?;
+            let seconds: i64 = offset.getattr("seconds")?.extract()?;
+            let microseconds: i64 = offset.getattr("microseconds")?.extract()?;
+            if !tzinfo.get_type().is(timezone) || seconds % 60 != 0 || microseconds != 0 {
+                return Err(Unsupported);
+            }
+        }
+        let isoformat = obj.call_method0("isoformat")?;
+        self.write_str(
+            isoformat
+                .downcast::<PyString>()
+                .map_err(|_| Unsupported)?
+                .to_str()?,
+        );
+        Ok(())
+    }
+
+    /// Whether orjson serializes the object itself rather than calling default.
+    fn is_serialized_by_orjson(&self, obj: &Bound<'py, PyAny>) -> Result<bool> {
+        let py = self.py;
+        let object_type = obj.get_type();
+        let module: String = object_type
+            .getattr("__module__")?
+            .extract()
+            .unwrap_or_default();
+        Ok(obj.is_instance_of::<PyString>()
+            || obj.is_instance_of::<PyInt>()
+            || obj.is_instance_of::<PyFloat>()
+            || obj.is_instance_of::<PyDict>()
+            || obj.is_instance_of::<PyList>()
+            || obj.is_instance_of::<PyTuple>()
+            || obj.is_instance(UUID.import(py, "uuid", "UUID")?)?
+            || obj.is_instance(DATE.import(py, "datetime", "date")?)?
+            || obj.is_instance(TIME.import(py, "datetime", "time")?)?
+            || obj.is_instance(ENUM.import(py, "enum", "Enum")?)?
+            || object_type.hasattr("__dataclass_fields__")?
+            || module == "numpy"
+            || module.starts_with("numpy.")
+            || module == "orjson")
+    }
+}
+
+/// Serialize an object as `orjson.dumps` does with the options and default of
+/// `langsmith._internal._serde`, or return `None` for objects it can't.
+#[pyfunction]
+pub fn dumps<'py>(
+    py: Python<'py>,
+    obj: &Bound<'py, PyAny>,
+    default: Bound<'py, PyAny>,
+) -> Option<Bound<'py, PyBytes>> {
+    let mut serializer = Serializer {
+        py,
+        default,
+        out: Vec::with_capacity(1024),
+    };
+    serializer.write(obj, 0).ok()?;
+    Some(PyBytes::new(py, &serializer.out))
+}
@@ -0,0 +1,28 @@
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+/// Whether `escape` is a `\uDXXX` surrogate escape, in any case.
+fn is_surrogate_escape(escape: &[u8]) -> bool {
+    matches!(escape, [b'\\', b'u' | b'U', b'd' | b'D', third, fourth, fifth]
+        if matches!(third.to_ascii_lowercase(), b'8' | b'9' | b'a'..=b'f')
+            && fourth.is_ascii_hexdigit()
+            && fifth.is_ascii_hexdigit())
+}
+
+/// Remove the `\uDXXX` surrogate escapes from JSON.
+///
+/// Matches `re.compile(rb"\\ud[89a-f][0-9a-f]{2}", re.IGNORECASE).sub(b"", data)`.
+#[pyfunction]
+pub fn elide_surrogates<'py>(py: Python<'py>, data: &[u8]) -> Bound<'py, PyBytes> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        if data[i] == b'\\' && data.len() - i >= 6 && is_surrogate_escape(&data[i..i + 6]) {
+            i += 6;
+        } else {
+            out.push(data[i]);
+            i += 1;
+        }
+    }
+    PyBytes::new(py, &out)
+}
@@ -0,0 +1,14 @@
+import pytest
+
+from langsmith._internal import _native
+
+
+@pytest.fixture(autouse=True)
+def _pure_python_core(request: pytest.FixtureRequest, monkeypatch) -> None:
+    """Run the tests on the pure-Python path, even if the native core is installed.
+
+    test_native.py checks the native core against it.
+    """
+    if request.module.__name__.split(".")[-1] == "test_native":
+        return
+    monkeypatch.setattr(_native, "_core", None)
@@ -0,0 +1,151 @@
+"""Test the async client's background batching."""
+
+import asyncio
+import email
+import json
+import uuid
+from datetime import datetime, timezone
+from typing import Dict, List
+
+import httpx
+
+from langsmith import run_trees
+from langsmith.async_client import AsyncClient
+
+
+def _client(requests: List[httpx.Request], use_multipart: bool = True) -> AsyncClient:
+    def handler(request: httpx.Request) -> httpx.Response:
+        if request.url.path == "/info":
+            config = {
+                "use_multipart_endpoint": use_multipart,
+                "size_limit": 100,
+                "scale_up_nthreads_limit": 16,
+                "scale_up_qsize_trigger": 1000,
+                "scale_down_nempty_trigger": 4,
+            }
+            return httpx.Response(200, json={"batch_ingest_config": config})
+        requests.append(request)
+        return httpx.Response(202)
+
+    client = AsyncClient(
+        api_url="http://localhost:1984", api_key="123", auto_batch_tracing=True
+    )
+    client._client = httpx.AsyncClient(
+        base_url="http://localhost:1984",
+        headers={"Content-Type": "application/json"},
+        transport=httpx.MockTransport(handler),
+    )
+    return client
+
+
+def _run_kwargs() -> dict:
+    id_ = uuid.uuid4()
+    return {
+        "id": id_,
+        "trace_id": id_,
+        "dotted_order": run_trees._create_current_dotted_order(
+            datetime.now(timezone.utc), id_
+        ),
+    }
+
+
+def _parts(request: httpx.Request) -> Dict[str, bytes]:
+    message = email.message_from_bytes(
+        f"Content-Type: {request.headers['Content-Type']}\r\n\r\n".encode()
+        + request.content
+    )
+    return {
+        part.get_param("name", header="content-disposition"): part.get_payload(
+            decode=True
+        )
+        for part in message.get_payload()
+    }
+
+
+async def test_runs_are_batched_and_flushed_on_close() -> None:
+    requests: List[httpx.Request] = []
+    client = _client(requests)
+    first, second = _run_kwargs(), _run_kwargs()
+    await client.create_run("first", inputs={"a": 1}, run_type="chain", **first)
+    await client.create_run("second", inputs={"b": 2}, run_type="chain", **second)
+    await client.update_run(
+        first["id"],
+        outputs={"c": 3},
+        trace_id=first["trace_id"],
+        dotted_order=first["dotted_order"],
+    )
+    assert requests == []
+    await client.aclose()
+
+    assert [request.url.path for request in requests] == ["/runs/multipart"]
+    parts = _parts(requests[0])
+    assert set(parts) == {
+        f"post.{first['id']}",
+        f"post.{first['id']}.inputs",
+        f"post.{first['id']}.outputs",
+        f"post.{second['id']}",
+        f"post.{second['id']}.inputs",
+    }
+    assert json.loads(parts[f"post.{first['id']}.outputs"]) == {"c": 3}
+
+
+async def test_batch_endpoint_without_multipart() -> None:
+    requests: List[httpx.Request] = []
+    client = _client(requests, use_multipart=False)
+    run = _run_kwargs()
+    await client.create_run("run", inputs={"a": 1}, run_type="chain", **run)
+    # The background task sends batches without waiting for aclose()
+    for _ in range(50):
+        if requests:
+            break
+        await asyncio.sleep(0.05)
+    assert [request.url.path for request in requests] == ["/runs/batch"]
+    body = json.loads(requests[0].content)
+    assert [r["id"] for r in body["post"]] == [str(run["id"])]
+    await client.aclose()
+
+
+async def test_runs_without_dotted_order_are_sent_directly() -> None:
+    requests: List[httpx.Request] = []
+    client = _client(requests)
+    await client.create_run("run", inputs={}, run_type="chain")
+    assert [request.url.path for request in requests] == ["/runs"]
+    assert client._tracing_task is None
+    await client.aclose()
+
+
+async def test_queued_runs_are_copied() -> None:
+    requests: List[httpx.Request] = []
+    client = _client(requests)
+    run = _run_kwargs()
+    inputs = {"messages": ["hi"]}
+    await client.create_run("run", inputs=inputs, run_type="chain", **run)
+    inputs["messages"].append("changed later")
+    await client.aclose()
+
+    parts = _parts(requests[0])
+    assert json.loads(parts[f"post.{run['id']}.inputs"]) == {"messages": ["hi"]}
+
+
+async def test_attachments_are_sent_as_parts() -> None:
+    requests: List[httpx.Request] = []
+    client = _client(requests)
+    run = _run_kwargs()
+    await client.create_run(
+        "run",
+        inputs={},
+        run_type="chain",
+        attachments={"notes": ("text/plain", b"some notes")},
+        **run,
+    )
+    await client.aclose()
+
+    parts = _parts(requests[0])
+    assert parts[f"attachment.{run['id']}.notes"] == b"some notes"
+    assert "attachments" not in json.loads(parts[f"post.{run['id']}"])
+
+
+async def test_batching_is_off_by_default() -> None:
+    client = AsyncClient(api_url="http://localhost:1984", api_key="123")
+    assert not client._auto_batch_tracing
+    await client.aclose()
@@ -0,0 +1,143 @@
+"""Test attachments backed by files and streams."""
+
+import email
+import io
+import pathlib
+import uuid
+from datetime import datetime, timezone
+from typing import Dict, List
+from unittest import mock
+
+import orjson
+
+from langsmith import run_trees
+from langsmith import schemas as ls_schemas
+from langsmith import utils as ls_utils
+from langsmith._internal._attachments import AttachmentReader
+from langsmith._internal._background_thread import TracingQueueItem
+from langsmith._internal._spool import _decode_item, _encode_item
+from langsmith.client import (
+    Client,
+    _chunk_multipart_parts,
+    _serialize_multipart_parts,
+)
+
+
+def _run(attachments: ls_schemas.Attachments) -> dict:
+    id_ = uuid.uuid4()
+    return {
+        "id": id_,
+        "trace_id": id_,
+        "dotted_order": run_trees._create_current_dotted_order(
+            datetime.now(timezone.utc), id_
+        ),
+        "name": "run",
+        "run_type": "chain",
+        "inputs": {"question": "hi"},
+        "attachments": attachments,
+    }
+
+
+def _parts(data: bytes, content_type: str) -> Dict[str, bytes]:
+    message = email.message_from_bytes(
+        f"Content-Type: {content_type}\r\n\r\n".encode() + data
+    )
+    return {
+        part.get_param("name", header="content-disposition"): part.get_payload(
+            decode=True
+        )
+        for part in message.get_payload()
+    }
+
+
+def test_file_attachments_are_read_as_they_are_sent(tmp_path: pathlib.Path) -> None:
+    path = tmp_path / "audio.wav"
+    path.write_bytes(b"RIFF" * 1000)
+    run = _run({"audio": ("audio/wav", path), "text": ("text/plain", b"hi")})
+    parts, _ = _serialize_multipart_parts(
+        [run], [], [], {run["id"]: run.pop("attachments")}
+    )
+    by_name = {name: part for name, part in parts}
+    _, body, content_type, headers = by_name[f"attachment.{run['id']}.audio"]
+    assert isinstance(body, AttachmentReader)
+    assert content_type == "audio/wav"
+    assert headers == {"Content-Length": "4000"}
+    assert body.read(10) == b"RIFFRIFFRI"
+    assert len(body) == 3990
+    assert body.read() == (b"RIFF" * 1000)[10:]
+    assert len(body) == 0
+    body.rewind()
+    assert body.read() == b"RIFF" * 1000
+    assert by_name[f"attachment.{run['id']}.text"][1] == b"hi"
+
+
+def test_streams_are_sent_again_on_retry() -> None:
+    client = Client(
+        api_url="http://localhost:1984",
+        api_key="123",
+        session=mock.Mock(),
+        info=ls_schemas.LangSmithInfo(),
+    )
+    stream = io.BytesIO(b"header" + b"frame" * 100)
+    stream.seek(6)
+    bodies: List[Dict[str, bytes]] = []
+
+    def request(method: str, url: str, request_kwargs: dict, **kwargs) -> None:
+        bodies.append(
+            _parts(
+                request_kwargs["data"].to_string(),
+                request_kwargs["headers"]["Content-Type"],
+            )
+        )
+        if len(bodies) == 1:
+            raise ls_utils.LangSmithConnectionError("connection reset")
+
+    run = _run({"video": ("video/mp4", stream)})
+    with mock.patch.object(Client, "request_with_retries", side_effect=request):
+        client.multipart_ingest(create=[run])
+    assert len(bodies) == 2
+    for body in bodies:
+        assert body[f"attachment.{run['id']}.video"] == b"frame" * 100
+    # The stream belongs to the caller, so it's left open
+    assert not stream.closed
+
+
+def test_large_attachments_are_split_between_requests(
+    tmp_path: pathlib.Path,
+) -> None:
+    runs = []
+    for i in range(3):
+        path = tmp_path / f"{i}.bin"
+        path.write_bytes(b"x" * 600)
+        runs.append(_run({"file": ("application/octet-stream", path)}))
+    parts, _ = _serialize_multipart_parts(
+        runs, [], [], {run["id"]: run.pop("attachments") for run in runs}
+    )
+    chunks = _chunk_multipart_parts(parts, 1000)
+    # Each run's parts stay together, and no request takes two of the files
+    assert [{name.split(".")[1] for name, _ in chunk} for chunk in chunks] == [
+        {str(run["id"])} for run in runs
+    ]
+    assert _chunk_multipart_parts(parts, 10_000) == [parts]
+
+
+def test_attachments_survive_the_spool(tmp_path: pathlib.Path) -> None:
+    path = tmp_path / "image.png"
+    path.write_bytes(b"png")
+    stream = io.BytesIO(b"jpeg")
+    run = _run(
+        {
+            "file": ("image/png", path),
+            "stream": ("image/jpeg", stream),
+            "bytes": ("text/plain", b"text"),
+        }
+    )
+    record = orjson.loads(_encode_item(TracingQueueItem("", "create", run)))
+    restored = _decode_item(record, None).item["attachments"]
+    # Files are referenced by path, and streams are read
+    assert restored == {
+        "file": ("image/png", path),
+        "stream": ("image/jpeg", b"jpeg"),
+        "bytes": ("text/plain", b"text"),
+    }
+    assert stream.tell() == 0
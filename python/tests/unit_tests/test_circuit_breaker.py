@@ -0,0 +1,223 @@
+"""Test pausing tracing while the API is down."""
+
+import uuid
+from datetime import datetime, timezone
+from typing import List
+from unittest import mock
+
+import httpx
+import requests
+
+from langsmith import run_trees
+from langsmith import schemas as ls_schemas
+from langsmith import utils as ls_utils
+from langsmith._internal._tracing_stats import DeliveryReport
+from langsmith.async_client import AsyncClient
+from langsmith.circuit_breaker import CircuitBreaker
+from langsmith.client import Client
+
+
+class _Clock:
+    def __init__(self) -> None:
+        self.now = 0.0
+
+    def __call__(self) -> float:
+        return self.now
+
+
+def _run_kwargs() -> dict:
+    id_ = uuid.uuid4()
+    return {
+        "id": id_,
+        "trace_id": id_,
+        "dotted_order": run_trees._create_current_dotted_order(
+            datetime.now(timezone.utc), id_
+        ),
+    }
+
+
+def test_circuit_breaker_opens_and_probes() -> None:
+    clock = _Clock()
+    breaker = CircuitBreaker(failure_threshold=2, reset_timeout=10, clock=clock)
+    breaker.record_error(ls_utils.LangSmithConnectionError("down"))
+    # Errors that don't suggest an outage leave the count as it is
+    breaker.record_error(ls_utils.LangSmithUserError("bad request"))
+    assert breaker.state == "closed"
+    breaker.record_error(ls_utils.LangSmithAPIError("500"))
+    assert breaker.state == "open"
+    assert not breaker.allow_request()
+
+    clock.now = 10
+    assert breaker.state == "half_open"
+    assert breaker.allow_request()
+    # Only one probe at a time
+    assert not breaker.allow_request()
+    breaker.record_failure()
+    assert breaker.state == "open"
+    assert breaker.times_opened == 2
+
+    clock.now = 20
+    assert breaker.allow_request()
+    breaker.record_success()
+    assert breaker.state == "closed"
+    assert breaker.allow_request()
+
+
+def _http_error(status: int) -> BaseException:
+    response = requests.Response()
+    response.status_code = status
+    try:
+        raise requests.HTTPError(response=response)
+    except requests.HTTPError:
+        try:
+            raise ls_utils.LangSmithError(f"{status}")
+        except ls_utils.LangSmithError as e:
+            return e
+
+
+def test_circuit_breaker_classifies_by_status() -> None:
+    breaker = CircuitBreaker(failure_threshold=1)
+    for status in (400, 403, 404, 422):
+        breaker.record_error(_http_error(status))
+    assert breaker.state == "closed"
+    for status in (408, 429, 500, 503):
+        breaker = CircuitBreaker(failure_threshold=1)
+        breaker.record_error(_http_error(status))
+        assert breaker.state == "open"
+
+
+def _client(breaker: CircuitBreaker, session: mock.Mock, **kwargs) -> Client:
+    kwargs = kwargs or {"api_url": "http://localhost:1984", "api_key": "123"}
+    return Client(
+        session=session,
+        info=ls_schemas.LangSmithInfo(
+            batch_ingest_config=ls_schemas.BatchIngestConfig(
+                use_multipart_endpoint=True,
+                size_limit_bytes=None,
+                size_limit=100,
+                scale_up_nthreads_limit=16,
+                scale_up_qsize_trigger=1000,
+                scale_down_nempty_trigger=4,
+            )
+        ),
+        circuit_breaker=breaker,
+        **kwargs,
+    )
+
+
+def test_client_opens_on_server_errors() -> None:
+    response = requests.Response()
+    response.status_code = 503
+    response._content = b"unavailable"
+    session = mock.Mock()
+    session.request.return_value = response
+    breaker = CircuitBreaker(failure_threshold=1)
+    client = _client(breaker, session)
+    client.create_run("my_run", inputs={}, run_type="chain", **_run_kwargs())
+    assert client.flush(timeout=5) == DeliveryReport(0, 1, 0)
+    assert session.request.called
+    assert breaker.state == "open"
+
+
+def test_client_stops_sending_while_open() -> None:
+    clock = _Clock()
+    breaker = CircuitBreaker(failure_threshold=1, reset_timeout=30, clock=clock)
+    client = _client(breaker, mock.Mock())
+
+    def create_run() -> None:
+        client.create_run("my_run", inputs={}, run_type="chain", **_run_kwargs())
+
+    down = ls_utils.LangSmithConnectionError("down")
+    with mock.patch.object(Client, "request_with_retries", side_effect=down) as req:
+        create_run()
+        assert client.flush(timeout=5) == DeliveryReport(0, 1, 0)
+        assert breaker.state == "open"
+        create_run()
+        create_run()
+        assert client.flush(timeout=5) == DeliveryReport(0, 2, 0)
+        # Only the first batch was tried
+        assert req.call_count == 3
+    stats = client.get_tracing_stats()
+    assert (stats.runs_short_circuited, stats.circuit_breaker_open) == (2, 1)
+    assert breaker.short_circuited_runs == 2
+
+    clock.now = 30
+    with mock.patch.object(Client, "request_with_retries") as req:
+        create_run()
+        assert client.flush(timeout=5) == DeliveryReport(1, 0, 0)
+        assert req.call_count == 1
+    assert breaker.state == "closed"
+    assert client.get_tracing_stats().circuit_breaker_open == 0
+
+
+def test_client_pauses_only_the_urls_that_are_down() -> None:
+    breaker = CircuitBreaker(failure_threshold=1)
+    client = _client(
+        breaker,
+        mock.Mock(),
+        api_urls={"http://down:1984": "123", "http://up:1984": "456"},
+    )
+    sent: List[str] = []
+
+    def request(method: str, url: str, **kwargs) -> None:
+        if url.startswith("http://down:1984"):
+            raise ls_utils.LangSmithConnectionError("down")
+        sent.append(url)
+
+    with mock.patch.object(Client, "request_with_retries", side_effect=request):
+        for _ in range(2):
+            client.create_run("my_run", inputs={}, run_type="chain", **_run_kwargs())
+            client.flush(timeout=5)
+    assert sent == ["http://up:1984/runs/multipart"] * 2
+    down, up = client._circuit_breakers.values()
+    assert down is breaker
+    assert (down.state, up.state) == ("open", "closed")
+    assert down.short_circuited_runs == 1
+    assert client.get_tracing_stats().circuit_breaker_open == 1
+
+async def test_async_client_drops_batches_while_open() -> None:
+    requests: List[httpx.Request] = []
+
+    def handler(request: httpx.Request) -> httpx.Response:
+        if request.url.path == "/info":
+            return httpx.Response(200, json={})
+        requests.append(request)
+        return httpx.Response(202)
+
+    breaker = CircuitBreaker(failure_threshold=1)
+    breaker.record_failure()
+    client = AsyncClient(
+        api_url="http://localhost:1984",
+        api_key="123",
+        auto_batch_tracing=True,
+        circuit_breaker=breaker,
+    )
+    client._client = httpx.AsyncClient(
+        base_url="http://localhost:1984", transport=httpx.MockTransport(handler)
+    )
+    await client.create_run("run", inputs={}, run_type="chain", **_run_kwargs())
+    await client.aclose()
+    assert requests == []
+    assert breaker.short_circuited_runs == 1
+
+
+async def test_async_client_ignores_bad_requests() -> None:
+    def handler(request: httpx.Request) -> httpx.Response:
+        if request.url.path == "/info":
+            return httpx.Response(200, json={})
+        return httpx.Response(400, json={"detail": "bad run"})
+
+    breaker = CircuitBreaker(failure_threshold=1)
+    client = AsyncClient(
+        api_url="http://localhost:1984",
+        api_key="123",
+        auto_batch_tracing=True,
+        circuit_breaker=breaker,
+    )
+    client._client = httpx.AsyncClient(
+        base_url="http://localhost:1984", transport=httpx.MockTransport(handler)
+    )
+    with mock.patch("langsmith.async_client.asyncio.sleep", new=mock.AsyncMock()):
+        await client.create_run("run", inputs={}, run_type="chain", **_run_kwargs())
+        await client.aclose()
+    assert breaker.state == "closed"
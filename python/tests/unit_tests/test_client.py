@@ -3,9 +3,11 @@
 import asyncio
 import dataclasses
 import gc
+import gzip
 import itertools
 import json
 import math
+import os
 import sys
 import time
 import uuid
@@ -585,6 +587,33 @@ def test_create_run_with_filters(auto_batch_tracing: bool) -> None:
     assert all([exp in all_posted for exp in expected])
 
 
+@pytest.mark.parametrize("auto_batch_tracing", [True, False])
+def test_filters_dont_change_the_callers_values(auto_batch_tracing: bool) -> None:
+    session = mock.MagicMock(spec=requests.Session)
+
+    def hide_secret(values: dict) -> dict:
+        values["nested"].pop("secret", None)
+        return values
+
+    client = Client(
+        api_url="http://localhost:1984",
+        api_key="123",
+        auto_batch_tracing=auto_batch_tracing,
+        session=session,
+        hide_inputs=hide_secret,
+        hide_outputs=hide_secret,
+    )
+    inputs = {"nested": {"secret": "a"}}
+    outputs = {"nested": {"secret": "b"}}
+    id_ = uuid.uuid4()
+    client.create_run("my_run", inputs=inputs, run_type="llm", id=id_)
+    client.update_run(id_, end_time=datetime.now(), inputs=inputs, outputs=outputs)
+    client.flush()
+
+    assert inputs == {"nested": {"secret": "a"}}
+    assert outputs == {"nested": {"secret": "b"}}
+
+
 def test_client_gc_after_autoscale() -> None:
     session = mock.MagicMock(spec=requests.Session)
     client = Client(
@@ -1136,6 +1165,100 @@ def test_batch_ingest_run_splits_large_batches(
         assert len(request_bodies) == len(set([body["id"] for body in request_bodies]))
 
 
+def _compression_client(
+    supported: Optional[List[str]], size_limit_bytes: Optional[int] = None
+) -> Client:
+    session = MagicMock()
+    session.request.return_value = MagicMock(status_code=200)
+    return Client(
+        api_key="test",
+        session=session,
+        auto_batch_tracing=False,
+        compression="gzip",
+        info=ls_schemas.LangSmithInfo(
+            batch_ingest_config=ls_schemas.BatchIngestConfig(
+                use_multipart_endpoint=True,
+                size_limit_bytes=size_limit_bytes,
+                supported_compression_methods=supported,  # type: ignore[typeddict-item]
+            )
+        ),
+    )
+
+
+def _multipart_requests(client: Client) -> List[tuple]:
+    return [
+        (call[1]["headers"], call[1]["data"])
+        for call in client.session.request.call_args_list  # type: ignore
+        if call[0][0] == "POST" and call[0][1].endswith("runs/multipart")
+    ]
+
+
+def _trace(run_id: str, inputs: dict) -> dict:
+    return {
+        "name": "test",
+        "id": run_id,
+        "trace_id": run_id,
+        "dotted_order": run_id,
+        "inputs": inputs,
+        "start_time": "2021-01-01T00:00:00Z",
+    }
+
+
+@pytest.mark.parametrize("supported", (["zstd", "gzip"], None))
+def test_multipart_ingest_compression(supported: Optional[List[str]]) -> None:
+    client = _compression_client(supported)
+    run_id = str(uuid.uuid4())
+    client.multipart_ingest(create=[_trace(run_id, {"x": "a" * 10_000})])
+
+    ((headers, data),) = _multipart_requests(client)
+    if supported is None:
+        # The server doesn't accept compressed requests
+        assert "Content-Encoding" not in headers
+        assert isinstance(data, MultipartEncoder)
+        return
+    assert headers["Content-Encoding"] == "gzip"
+    assert len(data) < 10_000
+    boundary = parse_options_header(headers["Content-Type"])[1]["boundary"]
+    parts = list(MultipartParser(BytesIO(gzip.decompress(data)), boundary).parts())
+    assert [p.name for p in parts] == [f"post.{run_id}", f"post.{run_id}.inputs"]
+    assert json.loads(parts[1].value) == {"x": "a" * 10_000}
+
+
+def test_multipart_ingest_splits_on_compressed_size() -> None:
+    client = _compression_client(["gzip"], size_limit_bytes=2_000)
+    # Repetitive inputs fit in one request once compressed, random ones don't
+    run_ids = [str(uuid.uuid4()) for _ in range(3)]
+    client.multipart_ingest(
+        create=[_trace(run_id, {"x": "a" * 3_000}) for run_id in run_ids]
+    )
+    assert len(_multipart_requests(client)) == 1
+
+    client = _compression_client(["gzip"], size_limit_bytes=2_000)
+    client.multipart_ingest(
+        create=[_trace(run_id, {"x": os.urandom(1_000).hex()}) for run_id in run_ids]
+    )
+    requests_ = _multipart_requests(client)
+    assert len(requests_) == 3
+    for run_id, (headers, data) in zip(run_ids, requests_):
+        boundary = parse_options_header(headers["Content-Type"])[1]["boundary"]
+        parts = MultipartParser(BytesIO(gzip.decompress(data)), boundary).parts()
+        assert {p.name.split(".")[1] for p in parts} == {run_id}
+
+
+def test_compression_method_validation(monkeypatch: pytest.MonkeyPatch) -> None:
+    _clear_env_cache()
+    monkeypatch.setenv("LANGSMITH_TRACING_COMPRESSION", "gzip")
+    client = Client(api_key="test", auto_batch_tracing=False)
+    assert client._compression == "gzip"
+    with pytest.raises(ls_utils.LangSmithUserError, match="brotli"):
+        Client(
+            api_key="test",
+            auto_batch_tracing=False,
+            compression="brotli",  # type: ignore[arg-type]
+        )
+    _clear_env_cache()
+
+
 @mock.patch("langsmith.client.requests.Session")
 def test_select_eval_results(mock_session_cls: mock.Mock):
     expected = EvaluationResult(
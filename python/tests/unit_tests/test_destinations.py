@@ -0,0 +1,226 @@
+"""Test per-destination policies for clients writing to several URLs."""
+
+import email
+import json
+import threading
+import time
+import uuid
+from datetime import datetime, timezone
+from pathlib import Path
+from typing import Dict, List
+from unittest import mock
+
+from langsmith import run_trees
+from langsmith import schemas as ls_schemas
+from langsmith import utils as ls_utils
+from langsmith.client import Client
+from langsmith.destinations import Destination
+
+
+def _run(**kwargs) -> dict:
+    id_ = uuid.uuid4()
+    return {
+        "id": id_,
+        "trace_id": id_,
+        "dotted_order": run_trees._create_current_dotted_order(
+            datetime.now(timezone.utc), id_
+        ),
+        "name": "run",
+        "run_type": "chain",
+        "session_name": "default",
+        **kwargs,
+    }
+
+
+def _parts(request_kwargs: dict) -> Dict[str, bytes]:
+    data = request_kwargs["data"]
+    content_type = request_kwargs["headers"]["Content-Type"]
+    message = email.message_from_bytes(
+        f"Content-Type: {content_type}\r\n\r\n".encode() + data.to_string()
+    )
+    return {
+        part.get_param("name", header="content-disposition"): part.get_payload(
+            decode=True
+        )
+        for part in message.get_payload()
+    }
+
+
+def test_destination_applies_policies() -> None:
+    destination = Destination(
+        anonymizer=lambda values: {k: "***" for k in values},
+        hide_outputs=True,
+        project_name="shared",
+    )
+    run = _run(inputs={"secret": "value"}, outputs={"answer": 42}, session_id="1")
+    [applied] = destination.apply([run])
+    assert applied["inputs"] == {"secret": "***"}
+    assert applied["outputs"] == {}
+    assert applied["session_name"] == "shared"
+    assert "session_id" not in applied
+    # The original run is left as it was
+    assert run["inputs"] == {"secret": "value"}
+    assert not destination.sends_attachments
+
+
+def test_destination_hide_functions_get_their_own_copy() -> None:
+    def hide_secret(values: dict) -> dict:
+        values["nested"].pop("secret")
+        return values
+
+    run = _run(inputs={"nested": {"secret": "value"}})
+    [applied] = Destination(hide_inputs=hide_secret).apply([run])
+    assert applied["inputs"] == {"nested": {}}
+    # Another destination may be sending the same run
+    assert run["inputs"] == {"nested": {"secret": "value"}}
+
+
+def test_client_leaves_the_given_destinations_unchanged() -> None:
+    destination = Destination(api_key=' "shared-key" ')
+    client = Client(
+        api_urls={"http://shared:1984": destination},
+        session=mock.Mock(),
+        info=ls_schemas.LangSmithInfo(),
+    )
+    assert client._write_api_urls == {"http://shared:1984": "shared-key"}
+    assert destination.api_key == ' "shared-key" '
+
+
+def test_destination_samples_whole_traces() -> None:
+    assert Destination(sample_rate=0).apply([_run()]) == []
+    assert len(Destination(sample_rate=1).apply([_run()])) == 1
+    sampled = Destination(sample_rate=0.5)
+    root = _run()
+    children = [_run(trace_id=root["trace_id"]) for _ in range(20)]
+    assert len(sampled.apply([root, *children])) in (0, 21)
+
+
+def test_client_sends_each_destination_its_own_runs() -> None:
+    client = Client(
+        api_urls={
+            "http://internal:1984": "internal-key",
+            "http://shared:1984": Destination(
+                api_key="shared-key", hide_inputs=True, project_name="shared"
+            ),
+        },
+        session=mock.Mock(),
+        info=ls_schemas.LangSmithInfo(),
+    )
+    assert client._write_api_urls == {
+        "http://internal:1984": "internal-key",
+        "http://shared:1984": "shared-key",
+    }
+    internal_called = threading.Event()
+    sent: Dict[str, Dict[str, bytes]] = {}
+
+    def request(method: str, url: str, request_kwargs: dict, **kwargs) -> None:
+        if url.startswith("http://shared"):
+            # Destinations are sent to concurrently, so a slow one doesn't
+            # hold up the others
+            assert internal_called.wait(timeout=5)
+        else:
+            internal_called.set()
+        sent[url] = _parts(request_kwargs)
+
+    run = _run(inputs={"question": "hi"})
+    with mock.patch.object(Client, "request_with_retries", side_effect=request):
+        client.multipart_ingest(
+            create=[{**run, "attachments": {"file": ("text/plain", b"data")}}]
+        )
+    internal = sent["http://internal:1984/runs/multipart"]
+    shared = sent["http://shared:1984/runs/multipart"]
+    run_id = run["id"]
+    assert json.loads(internal[f"post.{run_id}.inputs"]) == {"question": "hi"}
+    assert f"attachment.{run_id}.file" in internal
+    assert json.loads(shared[f"post.{run_id}.inputs"]) == {}
+    assert json.loads(shared[f"post.{run_id}"])["session_name"] == "shared"
+    assert f"attachment.{run_id}.file" not in shared
+
+
+def test_retries_only_go_to_destinations_that_failed(tmp_path: Path) -> None:
+    client = Client(
+        api_urls={"http://flaky:1984": "flaky-key", "http://stable:1984": "key"},
+        session=mock.Mock(),
+        info=ls_schemas.LangSmithInfo(
+            batch_ingest_config=ls_schemas.BatchIngestConfig(
+                use_multipart_endpoint=True,
+                size_limit_bytes=None,
+                size_limit=100,
+                scale_up_nthreads_limit=16,
+                scale_up_qsize_trigger=1000,
+                scale_down_nempty_trigger=4,
+            )
+        ),
+        tracing_spool_dir=str(tmp_path),
+    )
+    sent: List[str] = []
+    flaky_down = True
+
+    def request(method: str, url: str, **kwargs) -> None:
+        if flaky_down and url.startswith("http://flaky"):
+            raise ls_utils.LangSmithConnectionError("down")
+        sent.append(url)
+
+    with mock.patch.object(Client, "request_with_retries", side_effect=request):
+        client.create_run(inputs={}, **_run())
+        assert client.tracing_queue is not None
+        client.tracing_queue.join()
+        spool = client._tracing_spool
+        assert spool is not None and spool.pending == 1
+
+        flaky_down = False
+        spool._next_retry = 0
+        for _ in range(50):
+            if not spool.pending:
+                break
+            time.sleep(0.1)
+    assert spool.pending == 0
+    assert sorted(sent) == [
+        "http://flaky:1984/runs/multipart",
+        "http://stable:1984/runs/multipart",
+    ]
+
+
+def test_slow_destinations_dont_hold_up_the_others() -> None:
+    client = Client(
+        api_urls={"http://slow:1984": "slow-key", "http://fast:1984": "key"},
+        session=mock.Mock(),
+        info=ls_schemas.LangSmithInfo(
+            batch_ingest_config=ls_schemas.BatchIngestConfig(
+                use_multipart_endpoint=True,
+                size_limit_bytes=None,
+                size_limit=100,
+                scale_up_nthreads_limit=16,
+                scale_up_qsize_trigger=1000,
+                scale_down_nempty_trigger=4,
+            )
+        ),
+    )
+    release = threading.Event()
+    sent: Dict[str, List[str]] = {"http://slow": [], "http://fast": []}
+
+    def request(method: str, url: str, request_kwargs: dict, **kwargs) -> None:
+        if url.startswith("http://slow"):
+            assert release.wait(timeout=5)
+        sent[url[: url.index(":1984")]].extend(
+            name for name in _parts(request_kwargs) if name.count(".") == 1
+        )
+
+    runs = [_run(), _run()]
+    with mock.patch.object(Client, "request_with_retries", side_effect=request):
+        for run in runs:
+            client.create_run(inputs={}, **run)
+            # Each batch reaches the fast URL while the slow one is stuck on
+            # the first
+            for _ in range(50):
+                if len(sent["http://fast"]) == runs.index(run) + 1:
+                    break
+                time.sleep(0.1)
+        assert sent == {
+            "http://slow": [],
+            "http://fast": [f"post.{run['id']}" for run in runs],
+        }
+        release.set()
+        assert client.tracing_queue is not None
+        client.tracing_queue.join()
+    assert sorted(sent["http://slow"]) == sorted(f"post.{run['id']}" for run in runs)
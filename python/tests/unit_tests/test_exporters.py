@@ -0,0 +1,178 @@
+"""Test the trace exporters."""
+
+import io
+import json
+import time
+import uuid
+from datetime import datetime, timedelta, timezone
+from pathlib import Path
+from typing import List
+from unittest import mock
+
+import pytest
+
+from langsmith import run_trees
+from langsmith import utils as ls_utils
+from langsmith._internal._delivery import BatchDelivery
+from langsmith.client import Client
+from langsmith.exporters import (
+    ConsoleExporter,
+    InMemoryExporter,
+    JSONLFileExporter,
+    LangSmithExporter,
+    TraceBatch,
+)
+
+_START = datetime(2024, 1, 1, tzinfo=timezone.utc)
+
+
+def _trace(error: bool = False) -> List[dict]:
+    root_id, child_id = uuid.uuid4(), uuid.uuid4()
+    root_order = run_trees._create_current_dotted_order(_START, root_id)
+    child_order = root_order + "." + run_trees._create_current_dotted_order(
+        _START, child_id
+    )
+    return [
+        {
+            "id": root_id,
+            "trace_id": root_id,
+            "dotted_order": root_order,
+            "name": "agent",
+            "run_type": "chain",
+            "start_time": _START,
+        },
+        {
+            "id": child_id,
+            "trace_id": root_id,
+            "parent_run_id": root_id,
+            "dotted_order": child_order,
+            "name": "llm",
+            "run_type": "llm",
+            "start_time": _START,
+            "end_time": _START + timedelta(seconds=0.5),
+            "error": "boom" if error else None,
+        },
+    ]
+
+
+def _trace_client(**kwargs) -> Client:
+    return Client(
+        api_url="http://localhost:1984", api_key="123", session=mock.Mock(), **kwargs
+    )
+
+
+def test_exporters_replace_the_api() -> None:
+    exporter = InMemoryExporter()
+    client = _trace_client(exporters=[exporter])
+    root, child = _trace()
+    client.create_run(inputs={"q": "hi"}, **root)
+    client.create_run(inputs={}, **child)
+    client.update_run(
+        root["id"],
+        outputs={"a": "hello"},
+        end_time=_START + timedelta(seconds=1),
+        trace_id=root["trace_id"],
+        dotted_order=root["dotted_order"],
+    )
+    assert client.tracing_queue is not None
+    client.tracing_queue.join()
+
+    runs = exporter.runs
+    assert [run["name"] for run in runs] == ["agent", "llm"]
+    assert runs[0]["inputs"] == {"q": "hi"}
+    assert runs[0]["outputs"] == {"a": "hello"}
+    # nothing was sent, not even a request for the server info
+    client.session.request.assert_not_called()  # type: ignore[attr-defined]
+
+
+def test_exporters_are_chained(tmp_path: Path) -> None:
+    path = tmp_path / "audit" / "traces.jsonl"
+    memory = InMemoryExporter()
+    client = _trace_client(
+        exporters=[LangSmithExporter(), JSONLFileExporter(path), memory],
+        info={},
+    )
+    root, child = _trace()
+    with mock.patch.object(
+        Client, "_batch_ingest_runs", return_value=BatchDelivery()
+    ) as ingest:
+        client.create_run(inputs={}, **child)
+        client.create_run(inputs={"q": "hi"}, **root)
+        assert client.tracing_queue is not None
+        client.tracing_queue.join()
+
+    sent = {
+        run["id"] for call in ingest.call_args_list for run in call.kwargs["create"]
+    }
+    assert sent == {root["id"], child["id"]}
+    lines = [json.loads(line) for line in path.read_text().splitlines()]
+    assert {line["event"] for line in lines} == {"post"}
+    assert {line["payload"]["id"] for line in lines} == {
+        str(root["id"]),
+        str(child["id"]),
+    }
+    assert len(memory.runs) == 2
+
+
+def test_failing_exporter_does_not_block_others() -> None:
+    broken = mock.Mock()
+    broken.export.side_effect = RuntimeError("disk full")
+    memory = InMemoryExporter()
+    client = _trace_client(exporters=[broken, memory])
+    root, _ = _trace()
+    delivery = client._export_batch([root], [], [])
+    assert delivery.outcomes == {"Mock:0": "retry", "InMemoryExporter:1": "delivered"}
+    assert [run["id"] for run in memory.runs] == [root["id"]]
+
+
+def test_retries_only_go_to_failing_exporters(tmp_path: Path) -> None:
+    flaky = mock.Mock()
+    flaky.export.side_effect = [False, True]
+    memory = InMemoryExporter()
+    client = _trace_client(
+        exporters=[flaky, memory], tracing_spool_dir=str(tmp_path / "spool")
+    )
+    root, _ = _trace()
+    client.create_run(inputs={}, **root)
+    assert client.tracing_queue is not None
+    client.tracing_queue.join()
+    assert client._tracing_spool is not None
+    assert client._tracing_spool.pending == 1
+
+    client._tracing_spool._next_retry = 0
+    for _ in range(50):
+        if not client._tracing_spool.pending:
+            break
+        time.sleep(0.1)
+    assert client._tracing_spool.pending == 0
+    assert flaky.export.call_count == 2
+    # The exporter that accepted the run the first time doesn't get it again
+    assert [run["id"] for run in memory.runs] == [root["id"]]
+
+
+def test_console_exporter_prints_finished_traces() -> None:
+    stream = io.StringIO()
+    exporter = ConsoleExporter(stream)
+    root, child = _trace(error=True)
+    exporter.export(TraceBatch(create=[root, child], update=[], feedback=[]))
+    assert stream.getvalue() == ""
+
+    end = {**root, "end_time": _START + timedelta(seconds=2)}
+    exporter.export(TraceBatch(create=[], update=[end], feedback=[]))
+    assert stream.getvalue() == (
+        "agent (chain) 2.00s\n└── llm (llm) 0.50s [error: boom]\n"
+    )
+
+    exporter.export(TraceBatch(create=_trace()[:1], update=[], feedback=[]))
+    exporter.shutdown()
+    assert stream.getvalue().endswith("agent (chain) (incomplete)\n")
+
+
+def test_exporters_require_auto_batching() -> None:
+    with pytest.raises(ls_utils.LangSmithUserError, match="auto_batch_tracing"):
+        Client(
+            api_url="http://localhost:1984",
+            api_key="123",
+            auto_batch_tracing=False,
+            exporters=[InMemoryExporter()],
+        )
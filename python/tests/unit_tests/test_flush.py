@@ -0,0 +1,77 @@
+"""Test flushing and shutting down the tracing queue."""
+
+import threading
+import uuid
+from datetime import datetime, timezone
+from unittest import mock
+
+from langsmith import run_trees
+from langsmith import schemas as ls_schemas
+from langsmith._internal._delivery import BatchDelivery
+from langsmith._internal._tracing_stats import DeliveryReport
+from langsmith.client import Client
+
+
+def _create_run(client: Client) -> None:
+    id_ = uuid.uuid4()
+    client.create_run(
+        "my_run",
+        inputs={"messages": ["hi"]},
+        run_type="chain",
+        id=id_,
+        trace_id=id_,
+        dotted_order=run_trees._create_current_dotted_order(
+            datetime.now(timezone.utc), id_
+        ),
+    )
+
+
+def _client() -> Client:
+    return Client(
+        api_url="http://localhost:1984",
+        api_key="123",
+        session=mock.Mock(),
+        info=ls_schemas.LangSmithInfo(),
+    )
+
+
+def test_flush_reports_delivered_and_failed_runs() -> None:
+    client = _client()
+    with mock.patch.object(Client, "_batch_ingest_runs", return_value=BatchDelivery()):
+        _create_run(client)
+        _create_run(client)
+        assert client.flush(timeout=5) == DeliveryReport(2, 0, 0)
+    with mock.patch.object(
+        Client, "_batch_ingest_runs", return_value=BatchDelivery("retry")
+    ):
+        _create_run(client)
+        assert client.flush() == DeliveryReport(0, 1, 0)
+
+
+def test_flush_timeout_reports_abandoned_runs() -> None:
+    client = _client()
+    sending = threading.Event()
+    release = threading.Event()
+
+    def ingest(**kwargs) -> BatchDelivery:
+        sending.set()
+        return BatchDelivery("delivered" if release.wait(timeout=5) else "retry")
+
+    with mock.patch.object(Client, "_batch_ingest_runs", side_effect=ingest):
+        _create_run(client)
+        assert sending.wait(timeout=5)
+        assert client.flush(timeout=0.1) == DeliveryReport(0, 0, 1)
+        release.set()
+        assert client.flush(timeout=5) == DeliveryReport(1, 0, 0)
+
+
+def test_shutdown_stops_the_tracing_thread() -> None:
+    client = _client()
+    with mock.patch.object(Client, "_batch_ingest_runs", return_value=BatchDelivery()):
+        for _ in range(3):
+            _create_run(client)
+        report = client.shutdown(timeout=5)
+    assert client._tracing_thread is not None
+    assert not client._tracing_thread.is_alive()
+    assert (report.failed, report.abandoned) == (0, 0)
+    assert client.get_tracing_stats().runs_sent == 3
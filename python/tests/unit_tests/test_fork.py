@@ -0,0 +1,109 @@
+"""Test tracing from forked processes."""
+
+import json
+import os
+import time
+import uuid
+from datetime import datetime, timezone
+from pathlib import Path
+from typing import List
+from unittest import mock
+
+import pytest
+
+from langsmith import run_trees
+from langsmith import schemas as ls_schemas
+from langsmith._internal._delivery import BatchDelivery
+from langsmith.client import Client
+from langsmith.sampling import TailSampler
+
+
+def _create_run(client: Client) -> str:
+    id_ = uuid.uuid4()
+    client.create_run(
+        "my_run",
+        inputs={"messages": ["hi"]},
+        run_type="chain",
+        id=id_,
+        trace_id=id_,
+        dotted_order=run_trees._create_current_dotted_order(
+            datetime.now(timezone.utc), id_
+        ),
+    )
+    return str(id_)
+
+
+def _client(**kwargs) -> Client:
+    return Client(
+        api_url="http://localhost:1984",
+        api_key="123",
+        session=mock.Mock(),
+        info=ls_schemas.LangSmithInfo(),
+        **kwargs,
+    )
+
+
+def test_reset_tracing_after_fork(tmp_path: Path) -> None:
+    sampler = TailSampler(lambda trace: True)
+    client = _client(tracing_spool_dir=str(tmp_path), tail_sampler=sampler)
+    queue, spool = client.tracing_queue, client._tracing_spool
+    sampler._buffer["trace"] = mock.Mock()
+    client._reset_tracing_after_fork()
+    assert client.tracing_queue is not queue
+    assert client._tracing_spool is not spool
+    assert client._tracing_thread is None
+    assert sampler.buffered_traces == 0
+
+
+@pytest.mark.skipif(not hasattr(os, "fork"), reason="requires os.fork")
+def test_forked_child_sends_its_own_runs() -> None:
+    read_fd, write_fd = os.pipe()
+    sent: List[str] = []
+
+    def ingest(create: List[dict], **kwargs) -> BatchDelivery:
+        ids = [str(run["id"]) for run in create]
+        sent.extend(ids)
+        os.write(write_fd, (json.dumps(ids) + "\n").encode())
+        return BatchDelivery()
+
+    client = _client()
+    with mock.patch.object(Client, "_batch_ingest_runs", side_effect=ingest):
+        parent_run = _create_run(client)
+        pid = os.fork()
+        if pid == 0:
+            try:
+                _create_run(client)
+                assert client.tracing_queue is not None
+                client.tracing_queue.join()
+            finally:
+                os._exit(0)
+        os.waitpid(pid, 0)
+        assert client.tracing_queue is not None
+        client.tracing_queue.join()
+    os.close(write_fd)
+    with os.fdopen(read_fd) as f:
+        batches = [json.loads(line) for line in f]
+    sent_ids = [run_id for batch in batches for run_id in batch]
+    # The child sends its own run, and leaves the parent's to the parent
+    assert len(sent_ids) == 2
+    assert sent_ids.count(parent_run) == 1
+    assert sent == [parent_run]
+
+
+@pytest.mark.skipif(not hasattr(os, "fork"), reason="requires os.fork")
+def test_queued_runs_are_sent_before_fork() -> None:
+    sent: List[str] = []
+
+    def ingest(create: List[dict], **kwargs) -> BatchDelivery:
+        time.sleep(0.2)
+        sent.extend(str(run["id"]) for run in create)
+        return BatchDelivery()
+
+    client = _client()
+    with mock.patch.object(Client, "_batch_ingest_runs", side_effect=ingest):
+        parent_run = _create_run(client)
+        pid = os.fork()
+        if pid == 0:
+            os._exit(0)
+        assert sent == [parent_run]
+        os.waitpid(pid, 0)
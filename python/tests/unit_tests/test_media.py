@@ -0,0 +1,109 @@
+"""Test moving inline base64 media into attachments."""
+
+import base64
+import uuid
+from datetime import datetime, timezone
+from unittest import mock
+
+from langsmith import run_trees
+from langsmith import schemas as ls_schemas
+from langsmith._internal._media import extract_inline_media
+from langsmith.client import Client
+
+PNG = b"\x89PNG\r\n\x1a\n"
+PNG_B64 = base64.b64encode(PNG).decode()
+
+
+def _messages() -> dict:
+    return {
+        "messages": [
+            {
+                "role": "user",
+                "content": [
+                    {"type": "text", "text": "What's this?"},
+                    {
+                        "type": "image_url",
+                        "image_url": {"url": f"data:image/png;base64,{PNG_B64}"},
+                    },
+                    {
+                        "type": "input_audio",
+                        "input_audio": {"data": "UklG", "format": "wav"},
+                    },
+                ],
+            },
+            {
+                "role": "user",
+                "content": [
+                    {
+                        "type": "image",
+                        "source": {
+                            "type": "base64",
+                            "media_type": "image/jpeg",
+                            "data": PNG_B64,
+                        },
+                    },
+                    {
+                        "type": "image_url",
+                        "image_url": {"url": "data:image/png;base64,%%"},
+                    },
+                ],
+            },
+        ]
+    }
+
+
+def test_extract_inline_media() -> None:
+    inputs = _messages()
+    extracted, attachments = extract_inline_media("inputs", inputs)
+    openai, anthropic = extracted["messages"]
+    assert openai["content"][0] == {"type": "text", "text": "What's this?"}
+    assert openai["content"][1]["image_url"]["url"] == "attachment://inputs-media-0"
+    assert openai["content"][2]["input_audio"] == {
+        "data": "attachment://inputs-media-1",
+        "format": "wav",
+    }
+    assert anthropic["content"][0]["source"]["data"] == "attachment://inputs-media-2"
+    # Data that isn't valid base64 is left where it is
+    assert anthropic["content"][1] == _messages()["messages"][1]["content"][1]
+    assert attachments == {
+        "inputs-media-0": ("image/png", PNG),
+        "inputs-media-1": ("audio/wav", b"RIF"),
+        "inputs-media-2": ("image/jpeg", PNG),
+    }
+    # The values passed in are left as they were, and unchanged ones reused
+    assert inputs == _messages()
+    assert openai["content"][0] is inputs["messages"][0]["content"][0]
+    assert extract_inline_media("outputs", {"answer": "hi"}) == ({"answer": "hi"}, {})
+
+
+def test_client_sends_inline_media_as_attachments() -> None:
+    client = Client(
+        api_url="http://localhost:1984",
+        api_key="123",
+        session=mock.Mock(),
+        info=ls_schemas.LangSmithInfo(),
+        extract_media=True,
+    )
+    id_ = uuid.uuid4()
+    run = {
+        "id": id_,
+        "trace_id": id_,
+        "dotted_order": run_trees._create_current_dotted_order(
+            datetime.now(timezone.utc), id_
+        ),
+        "name": "llm",
+        "run_type": "llm",
+        "inputs": _messages(),
+    }
+    with mock.patch("langsmith.client._serialize_multipart_parts") as serialize:
+        serialize.return_value = ([], "")
+        with mock.patch.object(Client, "_send_multipart_req"):
+            client.multipart_ingest(create=[run])
+    create, _, _, attachments, _ = serialize.call_args.args
+    assert set(attachments[id_]) == {
+        "inputs-media-0",
+        "inputs-media-1",
+        "inputs-media-2",
+    }
+    content = create[0]["inputs"]["messages"][0]["content"]
+    assert content[1]["image_url"]["url"] == "attachment://inputs-media-0"
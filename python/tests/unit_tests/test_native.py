@@ -0,0 +1,162 @@
+"""Test the compiled core gives the same bytes as the pure-Python path."""
+
+import dataclasses
+import datetime
+import decimal
+import enum
+import gzip
+import os
+import re
+import uuid
+from typing import Any, List, Optional
+from unittest import mock
+
+import pytest
+from requests_toolbelt.multipart import MultipartEncoder
+
+if os.environ.get("LANGSMITH_TEST_NATIVE_CORE") == "true":
+    # CI builds the core for these tests, so fail rather than skip without it
+    import langsmith_native  # noqa: F401
+else:
+    pytest.importorskip("langsmith_native")
+
+from langsmith import schemas as ls_schemas  # noqa: E402
+from langsmith._internal import _native, _serde  # noqa: E402
+from langsmith.client import Client  # noqa: E402
+
+
+class _Color(enum.Enum):
+    RED = "red"
+
+
+@dataclasses.dataclass
+class _Point:
+    x: int
+    y: int
+
+
+class _Opaque:
+    def __repr__(self) -> str:
+        return "<opaque>"
+
+
+class _Str(str):
+    pass
+
+
+def _pure(fn: Any, *args: Any) -> Any:
+    with mock.patch.object(_native, "_core", None):
+        return fn(*args)
+
+
+now = datetime.datetime(2024, 1, 2, 3, 4, 5, 678901)
+OBJECTS: List[Any] = [
+    None,
+    True,
+    "".join(chr(i) for i in range(0x80)) + "é😀 ",
+    [0, -1, 2**63 - 1, -(2**63), 2**64 - 1, 2**64, -(2**63) - 1],
+    [1.0, -0.0, 0.1, 1e-5, 1e-7, 1e16, 1e22, 5e-324, float("nan"), float("inf")],
+    {"nested": {"list": [1, (2, 3)], "empty": {}, "tuple": ()}},
+    uuid.UUID("d4e795fb-9437-47a5-984d-bea42d5a7a59"),
+    now,
+    now.replace(tzinfo=datetime.timezone.utc),
+    now.replace(tzinfo=datetime.timezone(-datetime.timedelta(hours=5, minutes=30))),
+    now.replace(tzinfo=datetime.timezone(datetime.timedelta(seconds=30))),
+    now.date(),
+    now.time(),
+    decimal.Decimal("3.14"),
+    {1, 2},
+    b"bytes",
+    re.compile("pattern"),
+    _Color.RED,
+    _Point(1, 2),
+    _Opaque(),
+    _Str("subclass"),
+    {1: "int key", None: "none key"},
+    "lone \ud800 surrogate",
+    {"a": {"b": {"c": [[[[[[[[[[{"deep": True}]]]]]]]]]]}}},
+    [[]] * 200,
+]
+
+
+@pytest.mark.parametrize("obj", OBJECTS)
+def test_dumps_json_matches_pure_python(obj: Any) -> None:
+    assert _serde.dumps_json(obj) == _pure(_serde.dumps_json, obj)
+
+
+def test_dumps_run_dict_natively() -> None:
+    run = {
+        "id": uuid.uuid4(),
+        "name": "llm",
+        "start_time": now,
+        "inputs": {"messages": [{"role": "user", "content": "hi"}]},
+        "extra": {"metadata": {"tokens": 10, "cost": 0.25}},
+        "tags": ["a", "b"],
+    }
+    assert _native.dumps(run, _serde._serialize_json) == _pure(_serde.dumps_json, run)
+
+
+def test_elide_surrogates() -> None:
+    data = rb'["\ud800", "\uDBFF\udfff", "A", "\\ud800", "\ud8"]'
+    assert _serde._elide_surrogates(data) == _pure(_serde._elide_surrogates, data)
+
+
+def test_encode_multipart_matches_encoder() -> None:
+    parts = [
+        ("post.1", (None, b'{"a":1}', "application/json", {"Content-Length": "7"})),
+        (
+            'attachment.1.we"ird\nname',
+            (None, b"\x00\xff", "image/png", {"Content-Length": "2", "X-Empty": ""}),
+        ),
+    ]
+    expected = MultipartEncoder(parts, boundary="boundary").to_string()
+    assert _native.encode_multipart(parts, "boundary") == expected
+    # Parts that aren't bytes are left to the encoder
+    text_parts = [("x", (None, "text", "text/plain", {}))]
+    assert _native.encode_multipart(text_parts, "b") is None
+
+
+def _sent_bodies(compression: Optional[str] = None) -> List[Any]:
+    session = mock.Mock()
+    client = Client(
+        api_url="http://localhost:1984",
+        api_key="123",
+        session=session,
+        auto_batch_tracing=False,
+        compression=compression,
+        info=ls_schemas.LangSmithInfo(
+            batch_ingest_config=ls_schemas.BatchIngestConfig(
+                use_multipart_endpoint=True,
+                supported_compression_methods=["gzip"],  # type: ignore[typeddict-item]
+            )
+        ),
+    )
+    run_id = "d4e795fb-9437-47a5-984d-bea42d5a7a59"
+    client.multipart_ingest(
+        create=[
+            {
+                "name": "test",
+                "id": run_id,
+                "trace_id": run_id,
+                "dotted_order": f"20240102T030405678901Z{run_id}",
+                "inputs": {"x": "a" * 1_000},
+                "start_time": now,
+                "attachments": {"img": ("image/png", b"\x89PNG")},
+            }
+        ]
+    )
+    return [call[1]["data"] for call in session.request.call_args_list]
+
+
+def test_client_sends_the_natively_encoded_body() -> None:
+    (body,) = _sent_bodies()
+    assert isinstance(body, bytes)
+    (encoder,) = _pure(_sent_bodies)
+    assert isinstance(encoder, MultipartEncoder)
+    assert body == encoder.to_string()
+
+
+def test_client_compresses_the_natively_encoded_body() -> None:
+    (body,) = _sent_bodies("gzip")
+    (expected,) = _pure(_sent_bodies, "gzip")
+    assert gzip.decompress(body) == gzip.decompress(expected)
@@ -0,0 +1,146 @@
+"""Test offline trace capture and upload."""
+
+import json
+import os
+import uuid
+from datetime import datetime, timezone
+from pathlib import Path
+from typing import List
+from unittest import mock
+
+import pytest
+
+from langsmith import run_trees
+from langsmith import schemas as ls_schemas
+from langsmith import utils as ls_utils
+from langsmith._internal._delivery import BatchDelivery
+from langsmith.client import Client
+from langsmith.exporters import TraceBatch
+from langsmith.offline import OfflineExporter, upload_traces
+from langsmith.run_helpers import traceable, tracing_context
+
+_START = datetime(2024, 1, 1, tzinfo=timezone.utc)
+
+
+def _trace() -> List[dict]:
+    root_id, child_id = uuid.uuid4(), uuid.uuid4()
+    root_order = run_trees._create_current_dotted_order(_START, root_id)
+    child_order = root_order + "." + run_trees._create_current_dotted_order(
+        _START, child_id
+    )
+    return [
+        {
+            "id": root_id,
+            "trace_id": root_id,
+            "dotted_order": root_order,
+            "name": "agent",
+            "run_type": "chain",
+            "session_name": "offline",
+            "inputs": {"q": "hi"},
+        },
+        {
+            "id": child_id,
+            "trace_id": root_id,
+            "parent_run_id": root_id,
+            "dotted_order": child_order,
+            "name": "llm",
+            "run_type": "llm",
+            "session_name": "offline",
+            "inputs": {},
+            "attachments": {"image": ("image/png", b"\x89PNG")},
+        },
+    ]
+
+
+def _upload_client() -> Client:
+    return Client(
+        api_url="http://localhost:1984",
+        api_key="123",
+        session=mock.Mock(),
+        auto_batch_tracing=False,
+        info=ls_schemas.LangSmithInfo(
+            batch_ingest_config=ls_schemas.BatchIngestConfig(
+                use_multipart_endpoint=True,
+                size_limit_bytes=None,
+                size_limit=100,
+                scale_up_nthreads_limit=16,
+                scale_up_qsize_trigger=1000,
+                scale_down_nempty_trigger=4,
+            )
+        ),
+    )
+
+
+def test_traceable_writes_to_offline_dir(
+    tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+) -> None:
+    ls_utils.get_env_var.cache_clear()
+    monkeypatch.setenv("LANGSMITH_TRACING_OFFLINE_DIR", str(tmp_path))
+    client = Client(api_url="http://localhost:1984", api_key="123")
+    ls_utils.get_env_var.cache_clear()
+
+    @traceable(client=client)
+    def my_function(x: int) -> int:
+        return x + 1
+
+    with tracing_context(enabled=True):
+        my_function(1)
+    assert client.tracing_queue is not None
+    client.tracing_queue.join()
+
+    (file,) = os.listdir(tmp_path)
+    lines = [json.loads(line) for line in (tmp_path / file).read_text().splitlines()]
+    assert [line["event"] for line in lines] == ["post", "patch"]
+    assert lines[0]["payload"]["inputs"] == {"x": 1}
+    assert lines[1]["payload"]["outputs"] == {"output": 2}
+
+
+def test_upload_merges_and_orders_traces(tmp_path: Path) -> None:
+    exporter = OfflineExporter(tmp_path)
+    first, second = _trace(), _trace()
+    posts = [dict(run) for run in first + second]
+    patches = [
+        {**run, "inputs": None, "outputs": {"a": "hello"}, "end_time": _START}
+        for run in first + second
+    ]
+    # children are written before their parents, and patches in another batch
+    exporter.export(TraceBatch(create=posts[::-1], update=[], feedback=[]))
+    exporter.export(TraceBatch(create=[], update=patches, feedback=[]))
+    exporter.shutdown()
+    with open(exporter.path, "ab") as f:
+        f.write(b'{"event": "post", "payl')
+
+    client = _upload_client()
+    with mock.patch.object(
+        Client, "_multipart_ingest", return_value=BatchDelivery()
+    ) as ingest:
+        result = upload_traces(
+            tmp_path, client=client, project_name="uploaded", batch_size=1, delete=True
+        )
+
+    assert (result.traces, result.runs, result.skipped_lines) == (2, 4, 1)
+    assert ingest.call_count == 2
+    for call in ingest.call_args_list:
+        assert call.kwargs["update"] == []
+        create = call.kwargs["create"]
+        trace = first if create[0]["trace_id"] == str(first[0]["id"]) else second
+        assert [run["id"] for run in create] == [str(run["id"]) for run in trace]
+        assert all(run["session_name"] == "uploaded" for run in create)
+        assert all(run["outputs"] == {"a": "hello"} for run in create)
+        assert create[0]["inputs"] == {"q": "hi"}
+        assert create[1]["attachments"] == {"image": ("image/png", b"\x89PNG")}
+    assert os.listdir(tmp_path) == []
+
+
+def test_upload_keeps_files_on_failure(tmp_path: Path) -> None:
+    exporter = OfflineExporter(tmp_path)
+    exporter.export(TraceBatch(create=_trace(), update=[], feedback=[]))
+    exporter.shutdown()
+
+    client = _upload_client()
+    with mock.patch.object(
+        Client, "_multipart_ingest", return_value=BatchDelivery("retry")
+    ):
+        result = upload_traces(tmp_path, client=client, delete=True)
+    assert (result.traces, result.failed_traces) == (0, 1)
+    assert os.listdir(tmp_path) == [os.path.basename(exporter.path)]
@@ -0,0 +1,208 @@
+"""Test the OpenTelemetry span exporter."""
+
+import json
+from typing import Optional
+from unittest import mock
+
+import pytest
+
+pytest.importorskip("opentelemetry.sdk.trace")
+
+from opentelemetry.sdk.trace import Event, ReadableSpan  # noqa: E402
+from opentelemetry.sdk.trace.export import SpanExportResult  # noqa: E402
+from opentelemetry.trace import SpanContext, Status, StatusCode  # noqa: E402
+
+from langsmith._internal._tracing_stats import DeliveryReport  # noqa: E402
+from langsmith.client import Client  # noqa: E402
+from langsmith.exporters import InMemoryExporter  # noqa: E402
+from langsmith.otel import LangSmithSpanExporter, span_id_to_run_id  # noqa: E402
+
+_TRACE_ID = 0x4BF92F3577B34DA6A3CE929D0E0E4736
+_START = 1_704_067_200_000_000_000  # 2024-01-01
+
+
+def _span(
+    name: str,
+    span_id: int,
+    parent_id: Optional[int] = None,
+    *,
+    offset_ms: int = 0,
+    remote_parent: bool = False,
+    **kwargs,
+) -> ReadableSpan:
+    parent = (
+        SpanContext(_TRACE_ID, parent_id, is_remote=remote_parent)
+        if parent_id is not None
+        else None
+    )
+    start = _START + offset_ms * 1_000_000
+    return ReadableSpan(
+        name,
+        context=SpanContext(_TRACE_ID, span_id, is_remote=False),
+        parent=parent,
+        start_time=start,
+        end_time=start + 1_000_000_000,
+        **kwargs,
+    )
+
+
+def _client(exporter: InMemoryExporter) -> Client:
+    return Client(
+        api_url="http://localhost:1984",
+        api_key="123",
+        session=mock.Mock(),
+        exporters=[exporter],
+    )
+
+
+def test_export_gen_ai_spans() -> None:
+    memory = InMemoryExporter()
+    client = _client(memory)
+    exporter = LangSmithSpanExporter(client, project_name="otel")
+    chat = _span(
+        "chat gpt-4o",
+        2,
+        1,
+        offset_ms=1,
+        attributes={
+            "gen_ai.operation.name": "chat",
+            "gen_ai.system": "openai",
+            "gen_ai.request.model": "gpt-4o",
+            "gen_ai.request.temperature": 0.5,
+            "gen_ai.usage.input_tokens": 10,
+            "gen_ai.usage.output_tokens": 5,
+            "gen_ai.input.messages": json.dumps(
+                [{"role": "user", "parts": [{"type": "text", "content": "hi"}]}]
+            ),
+            "gen_ai.output.messages": json.dumps(
+                [
+                    {
+                        "role": "assistant",
+                        "parts": [{"type": "text", "content": "hello"}],
+                        "finish_reason": "stop",
+                    }
+                ]
+            ),
+            "http.status_code": 200,
+        },
+    )
+    tool = _span(
+        "execute_tool",
+        3,
+        1,
+        offset_ms=2,
+        attributes={
+            "gen_ai.operation.name": "execute_tool",
+            "gen_ai.tool.name": "search",
+            "gen_ai.tool.call.arguments": '{"query": "weather"}',
+            "gen_ai.tool.call.result": "sunny",
+        },
+    )
+    agent = _span(
+        "invoke_agent",
+        1,
+        attributes={
+            "gen_ai.operation.name": "invoke_agent",
+            "langsmith.span.tags": "a, b",
+            "langsmith.metadata.user_id": "123",
+        },
+    )
+    # Children end, and so are exported, before their parent
+    assert exporter.export([tool, chat]) == SpanExportResult.SUCCESS
+    assert exporter.export([agent]) == SpanExportResult.SUCCESS
+    assert exporter.force_flush()
+
+    root, llm, search = memory.runs
+    assert [run["id"] for run in (root, llm, search)] == [
+        span_id_to_run_id(i) for i in (1, 2, 3)
+    ]
+    assert {run["trace_id"] for run in memory.runs} == {root["id"]}
+    assert llm["parent_run_id"] == search["parent_run_id"] == root["id"]
+    assert llm["dotted_order"].startswith(root["dotted_order"] + ".")
+    assert {run["session_name"] for run in memory.runs} == {"otel"}
+
+    assert root["run_type"] == "chain"
+    assert root["tags"] == ["a", "b"]
+    assert root["extra"]["metadata"]["user_id"] == "123"
+    assert root["extra"]["metadata"]["otel_trace_id"] == format(_TRACE_ID, "032x")
+
+    assert (llm["name"], llm["run_type"]) == ("chat gpt-4o", "llm")
+    assert llm["inputs"] == {"messages": [{"role": "user", "content": "hi"}]}
+    assert llm["outputs"] == {
+        "messages": [
+            {"role": "assistant", "content": "hello", "finish_reason": "stop"}
+        ],
+        "usage_metadata": {"input_tokens": 10, "output_tokens": 5, "total_tokens": 15},
+    }
+    metadata = llm["extra"]["metadata"]
+    assert metadata["ls_provider"] == "openai"
+    assert metadata["ls_model_name"] == "gpt-4o"
+    assert metadata["ls_temperature"] == 0.5
+    assert metadata["http.status_code"] == 200
+    assert "gen_ai.input.messages" not in metadata
+
+    assert (search["name"], search["run_type"]) == ("search", "tool")
+    assert search["inputs"] == {"query": "weather"}
+    assert search["outputs"] == {"output": "sunny"}
+
+
+def test_export_errors_and_legacy_attributes() -> None:
+    memory = InMemoryExporter()
+    exporter = LangSmithSpanExporter(_client(memory))
+    llm = _span(
+        "completion",
+        5,
+        4,
+        remote_parent=True,
+        attributes={
+            "gen_ai.system": "anthropic",
+            "gen_ai.prompt.0.role": "user",
+            "gen_ai.prompt.0.content": "hi",
+            "langsmith.trace.session_name": "legacy",
+        },
+        events=[
+            Event("gen_ai.choice", {"message.content": "hello"}, _START),
+            Event(
+                "exception",
+                {"exception.type": "RateLimitError", "exception.message": "slow"},
+                _START,
+            ),
+            Event("retry", {"attempt": 1}, _START),
+        ],
+        status=Status(StatusCode.ERROR, "slow"),
+    )
+    exporter.export([llm])
+    exporter.shutdown()
+
+    (run,) = memory.runs
+    # A remote parent lives in another service, so the span starts a trace
+    assert run["trace_id"] == run["id"]
+    assert "parent_run_id" not in run
+    assert run["session_name"] == "legacy"
+    assert run["run_type"] == "llm"
+    assert run["inputs"] == {"messages": [{"role": "user", "content": "hi"}]}
+    assert run["outputs"] == {"messages": [{"role": "assistant", "content": "hello"}]}
+    assert run["error"] == "RateLimitError: slow"
+    assert [event["name"] for event in run["events"]] == ["retry"]
+
+
+def test_shutdown_drops_orphaned_spans() -> None:
+    memory = InMemoryExporter()
+    exporter = LangSmithSpanExporter(_client(memory))
+    exporter.export([_span("child", 7, 6)])
+    with mock.patch("langsmith.otel.logger") as logger:
+        exporter.shutdown()
+    assert memory.runs == []
+    assert "1 spans were not sent" in logger.warning.call_args[0][0]
+
+
+def test_flushes_are_bounded_by_the_timeout() -> None:
+    client = mock.Mock(spec=Client)
+    client.flush.return_value = DeliveryReport(delivered=1, failed=0, abandoned=2)
+    exporter = LangSmithSpanExporter(client)
+    assert not exporter.force_flush(timeout_millis=100)
+    client.flush.assert_called_once_with(timeout=0.1)
+
+    client.flush.return_value = DeliveryReport(delivered=2, failed=0, abandoned=0)
+    exporter.shutdown()
+    client.flush.assert_called_with(timeout=5)
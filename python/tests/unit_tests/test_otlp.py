@@ -0,0 +1,193 @@
+"""Test exporting runs as OTLP spans."""
+
+import json
+import struct
+import uuid
+from datetime import datetime, timezone
+from typing import Any, Dict, Iterator, List, Tuple
+from unittest import mock
+
+import requests
+
+from langsmith.client import Client
+from langsmith.exporters import TraceBatch
+from langsmith.otel import OTLPExporter, run_id_to_span_id
+from langsmith.run_helpers import traceable, tracing_context
+
+
+class _Collector(requests.Session):
+    """A session that records requests instead of sending them to a collector."""
+
+    def __init__(self, status: int = 200) -> None:
+        super().__init__()
+        self.status = status
+        self.requests: List[Tuple[str, Dict[str, str], bytes]] = []
+
+    def post(self, url: Any, data: Any = None, **kwargs: Any) -> requests.Response:
+        self.requests.append((url, kwargs["headers"], data))
+        response = requests.Response()
+        response.status_code = self.status
+        response.url = url
+        return response
+
+
+def _client(exporter: OTLPExporter) -> Client:
+    return Client(
+        api_url="http://localhost:1984",
+        api_key="123",
+        session=mock.Mock(),
+        exporters=[exporter],
+    )
+
+
+def _attributes(attributes: List[dict]) -> Dict[str, Any]:
+    return {a["key"]: next(iter(a["value"].values())) for a in attributes}
+
+
+def test_export_json_spans() -> None:
+    collector = _Collector()
+    client = _client(
+        OTLPExporter(
+            "http://collector:4318/v1/traces",
+            encoding="json",
+            headers={"x-key": "1"},
+            session=collector,
+        )
+    )
+
+    @traceable(
+        run_type="llm",
+        client=client,
+        metadata={"ls_provider": "openai", "ls_model_name": "gpt-4o"},
+    )
+    def chat(question: str) -> dict:
+        return {"usage_metadata": {"input_tokens": 3, "output_tokens": 4}}
+
+    @traceable(client=client, tags=["agent"], project_name="otlp")
+    def agent(question: str) -> None:
+        chat(question)
+        raise ValueError("boom")
+
+    with tracing_context(enabled=True):
+        try:
+            agent("hi")
+        except ValueError:
+            pass
+    assert client.tracing_queue is not None
+    client.tracing_queue.join()
+
+    spans = []
+    for url, headers, body in collector.requests:
+        assert url == "http://collector:4318/v1/traces"
+        assert headers["Content-Type"] == "application/json"
+        assert headers["x-key"] == "1"
+        (resource_spans,) = json.loads(body)["resourceSpans"]
+        assert _attributes(resource_spans["resource"]["attributes"]) == {
+            "service.name": "langsmith"
+        }
+        spans.extend(resource_spans["scopeSpans"][0]["spans"])
+    root, llm = sorted(spans, key=lambda s: s["startTimeUnixNano"])
+    attributes = _attributes(root["attributes"])
+    run_id = uuid.UUID(attributes["langsmith.run.id"])
+    assert root["traceId"] == run_id.hex
+    assert root["spanId"] == run_id_to_span_id(run_id).hex()
+    assert "parentSpanId" not in root
+    assert root["status"] == {"code": 2, "message": mock.ANY}
+    assert "boom" in root["status"]["message"]
+    assert attributes["langsmith.trace.session_name"] == "otlp"
+    assert attributes["langsmith.span.tags"] == {"values": [{"stringValue": "agent"}]}
+    assert json.loads(attributes["langsmith.inputs"]) == {"question": "hi"}
+
+    assert llm["traceId"] == root["traceId"]
+    assert llm["parentSpanId"] == root["spanId"]
+    assert llm["kind"] == 3
+    assert int(llm["endTimeUnixNano"]) >= int(llm["startTimeUnixNano"])
+    attributes = _attributes(llm["attributes"])
+    assert attributes["gen_ai.system"] == "openai"
+    assert attributes["gen_ai.request.model"] == "gpt-4o"
+    assert attributes["gen_ai.usage.input_tokens"] == "3"
+    assert attributes["gen_ai.usage.output_tokens"] == "4"
+    assert attributes["langsmith.metadata.ls_provider"] == "openai"
+
+
+def _read_varint(data: bytes, pos: int) -> Tuple[int, int]:
+    value = shift = 0
+    while True:
+        byte = data[pos]
+        value |= (byte & 0x7F) << shift
+        pos, shift = pos + 1, shift + 7
+        if not byte & 0x80:
+            return value, pos
+
+
+def _fields(data: bytes) -> Iterator[Tuple[int, Any]]:
+    pos = 0
+    while pos < len(data):
+        key, pos = _read_varint(data, pos)
+        number, wire_type = key >> 3, key & 7
+        if wire_type == 0:
+            value, pos = _read_varint(data, pos)
+        elif wire_type == 1:
+            (value,) = struct.unpack("<Q", data[pos : pos + 8])
+            pos += 8
+        else:
+            length, pos = _read_varint(data, pos)
+            value, pos = data[pos : pos + length], pos + length
+        yield number, value
+
+
+def _field(data: bytes, number: int) -> Any:
+    return next(value for n, value in _fields(data) if n == number)
+
+
+def test_export_protobuf_spans() -> None:
+    collector = _Collector()
+    exporter = OTLPExporter(include_content=False, session=collector)
+    run_id = uuid.uuid4()
+    start = datetime(2024, 1, 1, tzinfo=timezone.utc)
+    run = {
+        "id": run_id,
+        "trace_id": run_id,
+        "name": "agent",
+        "run_type": "chain",
+        "inputs": {"question": "hi"},
+        "start_time": start,
+        "extra": {"metadata": {"attempt": 2}},
+    }
+    # Runs are sent once they end
+    assert exporter.export(TraceBatch(create=[run], update=[], feedback=[]))
+    assert collector.requests == []
+    end = {"id": run_id, "trace_id": run_id, "end_time": start.isoformat()}
+    assert exporter.export(TraceBatch(create=[], update=[end], feedback=[]))
+    exporter.shutdown()
+
+    ((url, headers, body),) = collector.requests
+    assert url == "http://localhost:4318/v1/traces"
+    assert headers["Content-Type"] == "application/x-protobuf"
+    resource_spans = _field(body, 1)
+    span = _field(_field(resource_spans, 2), 2)
+    assert _field(span, 1) == run_id.bytes
+    assert _field(span, 2) == run_id.bytes[8:]
+    assert _field(span, 5) == b"agent"
+    assert _field(span, 7) == _field(span, 8) == 1_704_067_200_000_000_000
+    attributes = {
+        _field(kv, 1).decode(): dict(_fields(_field(kv, 2)))
+        for n, kv in _fields(span)
+        if n == 9
+    }
+    assert attributes["langsmith.span.kind"] == {1: b"chain"}
+    assert attributes["langsmith.metadata.attempt"] == {3: 2}
+    assert "langsmith.inputs" not in attributes
+
+
+def test_export_failure() -> None:
+    exporter = OTLPExporter(session=_Collector(status=500))
+    run_id = uuid.uuid4()
+    run = {
+        "id": run_id,
+        "trace_id": run_id,
+        "name": "agent",
+        "start_time": datetime.now(timezone.utc),
+        "end_time": datetime.now(timezone.utc),
+    }
+    assert not exporter.export(TraceBatch(create=[run], update=[], feedback=[]))
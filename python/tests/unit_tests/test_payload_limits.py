@@ -0,0 +1,70 @@
+"""Test shrinking oversized run inputs and outputs."""
+
+import json
+import uuid
+from datetime import datetime, timezone
+
+import pytest
+
+from langsmith import run_trees
+from langsmith import utils as ls_utils
+from langsmith.client import _serialize_multipart_parts
+from langsmith.payload_limits import PayloadLimit
+
+
+def test_attach_moves_large_values() -> None:
+    limit = PayloadLimit(max_bytes=100)
+    values = {"question": "hi", "document": "x" * 1000}
+    limited, attachments = limit.apply("inputs", values)
+    assert limited["question"] == "hi"
+    assert limited["document"] == "[1002 bytes moved to attachment 'inputs-document']"
+    assert attachments == {
+        "inputs-document": ("application/json", json.dumps("x" * 1000).encode())
+    }
+    # The original values are left as they were
+    assert values["document"] == "x" * 1000
+
+
+def test_truncate_and_summarize() -> None:
+    values = {"document": "é" * 1000, "rows": list(range(1000))}
+    limited, attachments = PayloadLimit(max_bytes=100).apply(
+        "outputs", values, can_attach=False
+    )
+    assert attachments == {}
+    assert limited["document"].endswith("... [truncated 2000 bytes]")
+    assert limited["document"].startswith("é")
+    assert len(limited["document"].encode()) <= 100
+    assert limited["rows"].startswith("[0,1,2,")
+
+    summarize = PayloadLimit(
+        max_bytes=100,
+        action="summarize",
+        summarizer=lambda key, value: f"{len(value)} {key}",
+    )
+    limited, _ = summarize.apply("outputs", values)
+    assert limited == {"document": "1000 document", "rows": "1000 rows"}
+
+    with pytest.raises(ls_utils.LangSmithUserError):
+        PayloadLimit(max_bytes=100, action="summarize")
+
+
+def test_multipart_parts_offload_large_inputs() -> None:
+    id_ = uuid.uuid4()
+    run = {
+        "id": id_,
+        "trace_id": id_,
+        "dotted_order": run_trees._create_current_dotted_order(
+            datetime.now(timezone.utc), id_
+        ),
+        "inputs": {"question": "hi", "document": "x" * 1000},
+        "outputs": {"answer": "short"},
+    }
+    parts, _ = _serialize_multipart_parts(
+        [run], [], [], {id_: {"image": ("image/png", b"png")}}, PayloadLimit(100)
+    )
+    by_name = {name: part for name, part in parts}
+    inputs = json.loads(by_name[f"post.{id_}.inputs"][1])
+    assert inputs["document"] == "[1002 bytes moved to attachment 'inputs-document']"
+    assert json.loads(by_name[f"post.{id_}.outputs"][1]) == {"answer": "short"}
+    assert by_name[f"attachment.{id_}.inputs-document"][2] == "application/json"
+    assert by_name[f"attachment.{id_}.image"][1] == b"png"
@@ -1,6 +1,6 @@
 import json
 from concurrent.futures import ThreadPoolExecutor
-from datetime import datetime
+from datetime import datetime, timezone
 from unittest.mock import MagicMock
 from uuid import UUID
 
@@ -108,6 +108,24 @@ def test_run_tree_events_not_null():
     assert run_tree.events == []
 
 
+def test_post_snapshots_inputs_and_outputs():
+    mock_client = MagicMock(spec=Client)
+    messages = [{"role": "user", "content": "hi"}]
+    run_tree = run_trees.RunTree(
+        name="My Chat Bot",
+        inputs={"messages": messages},
+        outputs={"messages": messages},
+        client=mock_client,
+    )
+    run_tree.post()
+    messages.append({"role": "assistant", "content": "hello"})
+    messages[0]["content"] = "changed"
+    kwargs = mock_client.create_run.call_args.kwargs
+    expected = {"messages": [{"role": "user", "content": "hi"}]}
+    assert kwargs["inputs"] == expected
+    assert kwargs["outputs"] == expected
+
+
 def test_nested_run_trees_from_dotted_order():
     grandparent = run_trees.RunTree(
         name="Grandparent",
@@ -150,3 +168,130 @@ def test_nested_run_trees_from_dotted_order():
     assert grandparent_clone.id == grandparent.id
     assert grandparent_clone.parent_run_id is None
     assert grandparent_clone.dotted_order == grandparent.dotted_order
+
+
+def test_w3c_trace_context_headers():
+    root = run_trees.RunTree(name="Root", client=MagicMock(spec=Client))
+    child = root.create_child(name="Child").create_child(name="Grandchild")
+    headers = child.to_headers()
+
+    assert headers["traceparent"] == f"00-{root.id.hex}-{child.id.hex[16:]}-01"
+    assert headers["tracestate"].startswith("langsmith=")
+
+    # A gateway that drops custom headers, and adds its own tracestate entry
+    forwarded = {
+        "traceparent": headers["traceparent"],
+        "tracestate": "vendor=abc," + headers["tracestate"],
+    }
+    clone = run_trees.RunTree.from_headers(forwarded)
+    assert clone is not None
+    assert clone.id == child.id
+    assert clone.trace_id == root.id
+    assert clone.parent_run_id == child.parent_run_id
+    assert clone.dotted_order == child.dotted_order
+
+    bytes_headers = {k.encode(): v.encode() for k, v in forwarded.items()}
+    clone = run_trees.RunTree.from_headers(bytes_headers)
+    assert clone is not None and clone.dotted_order == child.dotted_order
+
+    # The tracestate entry of another trace is ignored
+    other = run_trees.RunTree(name="Other", client=MagicMock(spec=Client))
+    mismatched = {
+        "traceparent": other.to_headers()["traceparent"],
+        "tracestate": headers["tracestate"],
+    }
+    assert run_trees.RunTree.from_headers(mismatched) is None
+    traceparent_only = {"traceparent": forwarded["traceparent"]}
+    assert run_trees.RunTree.from_headers(traceparent_only) is None
+
+
+def test_w3c_tracestate_omitted_for_deep_traces():
+    run = run_trees.RunTree(name="Root", client=MagicMock(spec=Client))
+    for depth in range(2, 9):
+        run = run.create_child(name=f"Level {depth}")
+        assert ("tracestate" in run.to_headers()) == (depth <= 7)
+    assert "traceparent" in run.to_headers()
+
+
+def test_w3c_trace_context_matches_js():
+    # The JS SDK is tested against the same headers
+    dotted_order = (
+        "20261016T060611006928Zc3868daa-7d8b-45d0-9c67-93261b813aac"
+        ".20261016T060611007137Ze0466f8c-5036-4fb3-9ceb-ea8df98211c2"
+    )
+    headers = {
+        "traceparent": "00-c3868daa7d8b45d09c6793261b813aac-9cebea8df98211c2-01",
+        "tracestate": (
+            "langsmith=AAZd7vX_NdDDho2qfYtF0JxnkyYbgTqs.AAZd7vX_NqHgRm-MUDZPs5zr6o35ghHC"
+        ),
+    }
+    parent = run_trees.RunTree.from_headers(headers)
+    assert parent is not None
+    assert parent.dotted_order == dotted_order
+    assert {k: parent.to_headers()[k] for k in headers} == headers
+
+
+def test_uuid7_run_ids(monkeypatch: pytest.MonkeyPatch) -> None:
+    from langsmith import utils
+    from langsmith._internal import _uuid
+
+    ids = [_uuid.uuid7() for _ in range(1000)]
+    assert ids == sorted(ids, key=lambda id_: id_.int)
+    assert {id_.version for id_ in ids} == {7}
+    assert _uuid.uuid7(1_700_000_000_000).int >> 80 == 1_700_000_000_000
+
+    monkeypatch.setenv("LANGSMITH_TRACING_UUID7", "true")
+    utils.get_env_var.cache_clear()
+    try:
+        run = RunTree(name="parent", client=MagicMock(spec=Client))
+        child = run.create_child("child")
+        assert run.id.version == child.id.version == 7
+        assert run.trace_id == run.id
+    finally:
+        utils.get_env_var.cache_clear()
+
+
+def test_child_dotted_order_tolerates_clock_skew() -> None:
+    parent = RunTree(
+        name="parent",
+        start_time=datetime(2024, 1, 1, 12, 0, 0, 500),
+        client=MagicMock(spec=Client),
+    )
+    # A child whose clock is behind its parent's
+    child = parent.create_child("child", start_time=datetime(2024, 1, 1, 11, 59))
+    assert child.dotted_order == (
+        f"{parent.dotted_order}.20240101T120000000500Z{child.id}"
+    )
+    # The start time is moved up with the dotted order, so the two agree
+    assert child.start_time == datetime(2024, 1, 1, 12, 0, 0, 500)
+    assert run_trees.validate_dotted_order(child.dotted_order) == []
+    # Children that start after their parent keep their own start time
+    later = datetime(2024, 1, 1, 12, 1, tzinfo=timezone.utc)
+    sibling = parent.create_child("sibling", start_time=later)
+    assert sibling.start_time == later
+    assert sibling.dotted_order == (
+        f"{parent.dotted_order}.20240101T120100000000Z{sibling.id}"
+    )
+
+
+def test_validate_and_repair_dotted_order() -> None:
+    parent = "20240101T120000000000Z152ce25c-064e-4742-bf36-8bb0389f8805"
+    child = "20240101T115900000000Zfe8b541f-e75a-4ee6-b92d-732710897194"
+    grandchild = "20240101T115930000000Z625b30ed-2fbb-4387-81b1-cb5d6221e5b4"
+    dotted_order = f"{parent}.{child}.{grandchild}"
+    problems = run_trees.validate_dotted_order(dotted_order)
+    assert len(problems) == 2
+    assert problems[0].startswith("Segment 1 starts at 2024-01-01T11:59:00")
+    assert run_trees.validate_dotted_order(f"{parent}.not-a-segment") == [
+        "Segment 1 is malformed: 'not-a-segment'"
+    ]
+
+    repaired = run_trees.repair_dotted_order(dotted_order)
+    assert repaired == (
+        f"{parent}.20240101T120000000000Zfe8b541f-e75a-4ee6-b92d-732710897194"
+        ".20240101T120000000000Z625b30ed-2fbb-4387-81b1-cb5d6221e5b4"
+    )
+    assert run_trees.validate_dotted_order(repaired) == []
+    # Repairing keeps a dotted order the prefix of its children's
+    assert repaired.startswith(run_trees.repair_dotted_order(f"{parent}.{child}"))
+    assert run_trees.repair_dotted_order(repaired) == repaired
@@ -0,0 +1,236 @@
+"""Test trace sampling policies."""
+
+import uuid
+from datetime import datetime, timedelta, timezone
+from typing import List, Optional
+from unittest import mock
+
+import pytest
+
+from langsmith import run_trees
+from langsmith import schemas as ls_schemas
+from langsmith import utils as ls_utils
+from langsmith._internal._background_thread import TracingQueueItem
+from langsmith._internal._delivery import BatchDelivery
+from langsmith.client import Client
+from langsmith.sampling import (
+    CompletedTrace,
+    SamplingRule,
+    TailSampler,
+    any_of,
+    get_sample_rate,
+    keep_errors,
+    keep_slower_than,
+    keep_tagged,
+    sample_rate,
+)
+
+_START = datetime(2024, 1, 1, tzinfo=timezone.utc)
+
+
+def _trace(
+    duration: float = 1.0,
+    child_error: Optional[str] = None,
+    tags: Optional[List[str]] = None,
+) -> List[TracingQueueItem]:
+    root_id, child_id = uuid.uuid4(), uuid.uuid4()
+    root_order = run_trees._create_current_dotted_order(_START, root_id)
+    child_order = root_order + "." + run_trees._create_current_dotted_order(
+        _START, child_id
+    )
+    common = {"trace_id": root_id, "tags": tags}
+    return [
+        TracingQueueItem(
+            root_order,
+            "create",
+            {"id": root_id, "dotted_order": root_order, "start_time": _START, **common},
+        ),
+        TracingQueueItem(
+            child_order,
+            "create",
+            {
+                "id": child_id,
+                "trace_id": root_id,
+                "dotted_order": child_order,
+                "parent_run_id": root_id,
+            },
+        ),
+        TracingQueueItem(
+            child_order,
+            "update",
+            {
+                "id": child_id,
+                "trace_id": root_id,
+                "dotted_order": child_order,
+                "error": child_error,
+            },
+        ),
+        TracingQueueItem(
+            root_order,
+            "update",
+            {
+                "id": root_id,
+                "trace_id": root_id,
+                "dotted_order": root_order,
+                "end_time": _START + timedelta(seconds=duration),
+            },
+        ),
+    ]
+
+
+def _feed(sampler: TailSampler, items: List[TracingQueueItem]) -> List:
+    released = []
+    for item in items:
+        released.extend(sampler.add(item))
+    return released
+
+
+def test_tail_sampler_buffers_until_root_ends() -> None:
+    seen: List[CompletedTrace] = []
+
+    def policy(trace: CompletedTrace) -> bool:
+        seen.append(trace)
+        return True
+
+    sampler = TailSampler(policy)
+    items = _trace(child_error="boom")
+    for item in items[:-1]:
+        assert sampler.add(item) == []
+    assert sampler.buffered_traces == 1
+    assert sampler.add(items[-1]) == items
+    assert sampler.buffered_traces == 0
+
+    (trace,) = seen
+    assert trace.complete
+    assert trace.has_error
+    assert trace.latency == 1.0
+    assert [run["id"] for run in trace.runs] == [
+        items[0].item["id"],
+        items[1].item["id"],
+    ]
+
+
+@pytest.mark.parametrize(
+    "kwargs, kept",
+    [
+        ({}, False),
+        ({"child_error": "boom"}, True),
+        ({"duration": 30}, True),
+        ({"tags": ["agent"]}, True),
+    ],
+)
+def test_tail_sampler_policies(kwargs: dict, kept: bool) -> None:
+    sampler = TailSampler(
+        any_of(
+            keep_errors(), keep_slower_than(10), keep_tagged("agent"), sample_rate(0)
+        )
+    )
+    items = _trace(**kwargs)
+    assert _feed(sampler, items) == (items if kept else [])
+
+
+def test_tail_sampler_remembers_decisions() -> None:
+    sampler = TailSampler(lambda trace: False)
+    items = _trace()
+    _feed(sampler, items[:1] + items[3:])
+    # a late child follows the decision made for its trace
+    assert _feed(sampler, items[1:3]) == []
+    assert sampler.buffered_traces == 0
+    assert sampler.dropped_items == len(items)
+
+
+def test_tail_sampler_evicts_incomplete_traces() -> None:
+    seen: List[CompletedTrace] = []
+    sampler = TailSampler(lambda trace: seen.append(trace) or True, max_traces=1)
+    first, second = _trace(), _trace()
+    _feed(sampler, first[:1])
+    assert _feed(sampler, second[:1]) == first[:1]
+    assert not seen[0].complete
+    assert sampler.release_all() == second[:1]
+
+
+def test_client_drops_unsampled_traces() -> None:
+    client = Client(
+        api_url="http://localhost:1984",
+        api_key="123",
+        session=mock.Mock(),
+        info=ls_schemas.LangSmithInfo(),
+        tail_sampler=TailSampler(keep_errors()),
+    )
+    kept, dropped = _trace(child_error="boom"), _trace()
+    with mock.patch.object(
+        Client, "_batch_ingest_runs", return_value=BatchDelivery()
+    ) as ingest:
+        for item in dropped + kept:
+            if item.action == "create":
+                client.create_run(name="run", inputs={}, run_type="chain", **item.item)
+            else:
+                client.update_run(item.item.pop("id"), **item.item)
+        assert client.tracing_queue is not None
+        client.tracing_queue.join()
+    sent_ids = {
+        run["id"] for call in ingest.call_args_list for run in call.kwargs["create"]
+    }
+    assert sent_ids == {kept[0].item["id"], kept[1].item["id"]}
+
+
+def test_tail_sampler_requires_auto_batching() -> None:
+    with pytest.raises(ls_utils.LangSmithUserError, match="auto_batch_tracing"):
+        Client(
+            api_url="http://localhost:1984",
+            api_key="123",
+            auto_batch_tracing=False,
+            tail_sampler=TailSampler(keep_errors()),
+        )
+
+
+def test_sampling_rule_matches() -> None:
+    run = {
+        "session_name": "prod-agents",
+        "name": "retriever_docs",
+        "run_type": "retriever",
+        "tags": ["rag"],
+        "extra": {"metadata": {"user_id": "1"}},
+    }
+    assert SamplingRule(sample_rate=0, name="retriever*").matches(run)
+    assert SamplingRule(sample_rate=0, session_name="prod-*", tags=["rag"]).matches(
+        run
+    )
+    assert SamplingRule(sample_rate=0, metadata_keys=["user_id"]).matches(run)
+    assert not SamplingRule(sample_rate=0, run_type="llm").matches(run)
+    assert not SamplingRule(sample_rate=0, tags=["agent"]).matches(run)
+    assert not SamplingRule(sample_rate=0, metadata_keys=["org_id"]).matches(run)
+
+
+def test_get_sample_rate_uses_first_matching_rule() -> None:
+    rules = [
+        SamplingRule(sample_rate=1.0, name="agent"),
+        SamplingRule(sample_rate=0.1, run_type="retriever"),
+        SamplingRule(sample_rate=0.5),
+    ]
+    assert get_sample_rate(rules, {"name": "agent", "run_type": "retriever"}) == 1.0
+    assert get_sample_rate(rules, {"name": "docs", "run_type": "retriever"}) == 0.1
+    assert get_sample_rate(rules, {"name": "docs"}) == 0.5
+    assert get_sample_rate(rules[:1], {"name": "docs"}, 0.2) == 0.2
+    assert get_sample_rate([], {"name": "docs"}) == 1.0
+
+
+def test_sampling_rules_from_env(monkeypatch: pytest.MonkeyPatch) -> None:
+    ls_utils.get_env_var.cache_clear()
+    monkeypatch.setenv(
+        "LANGSMITH_TRACING_SAMPLING_RULES",
+        '[{"name": "retriever*", "sample_rate": 0}]',
+    )
+    client = Client(
+        api_url="http://localhost:1984", api_key="123", auto_batch_tracing=False
+    )
+    ls_utils.get_env_var.cache_clear()
+    root_id, child_id = uuid.uuid4(), uuid.uuid4()
+    runs = [
+        {"id": root_id, "trace_id": root_id, "name": "retriever_docs"},
+        {"id": child_id, "trace_id": root_id, "name": "child"},
+        {"id": uuid.uuid4(), "name": "agent"},
+    ]
+    sampled = client._filter_for_sampling(runs)
+    assert [run["name"] for run in sampled] == ["agent"]
+    assert client._filter_for_sampling([{"id": root_id}], patch=True) == []
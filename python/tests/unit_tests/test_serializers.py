@@ -0,0 +1,104 @@
+import dataclasses
+import datetime
+import uuid
+from typing import Iterator
+
+import orjson
+import pytest
+
+from langsmith import utils as ls_utils
+from langsmith._internal._serde import dumps_json
+from langsmith.serializers import register_serializer, unregister_serializer
+
+
+class Celsius:
+    def __init__(self, degrees: float) -> None:
+        self.degrees = degrees
+
+
+class Boiling(Celsius):
+    def __init__(self) -> None:
+        super().__init__(100)
+
+
+@dataclasses.dataclass
+class Point:
+    x: int
+    y: int
+
+
+@dataclasses.dataclass
+class Other:
+    z: int
+
+
+class Tags(dict):
+    pass
+
+
+@pytest.fixture(autouse=True)
+def _unregister() -> Iterator[None]:
+    yield
+    for cls in (Celsius, Boiling, Point, Tags, datetime.date):
+        unregister_serializer(cls)
+
+
+def test_registered_serializer_replaces_repr() -> None:
+    assert orjson.loads(dumps_json(Celsius(21.5))).startswith("<")
+    register_serializer(Celsius, lambda c: {"celsius": c.degrees})
+    assert orjson.loads(dumps_json({"t": Celsius(21.5), "b": Boiling()})) == {
+        "t": {"celsius": 21.5},
+        "b": {"celsius": 100},
+    }
+    # The most specific registered type wins
+    register_serializer(Boiling, lambda _: "boiling")
+    assert orjson.loads(dumps_json([Celsius(1), Boiling()])) == [
+        {"celsius": 1},
+        "boiling",
+    ]
+
+
+def test_serializers_override_types_orjson_handles() -> None:
+    day = datetime.date(2024, 1, 2)
+    register_serializer(Point, lambda p: [p.x, p.y])
+    register_serializer(Tags, lambda t: sorted(t))
+    register_serializer(datetime.date, lambda d: d.toordinal())
+    value = {
+        "point": Point(1, 2),
+        "other": Other(3),
+        "tags": Tags(b=1, a=2),
+        "day": day,
+        "now": datetime.datetime(2024, 1, 2, 3, 4),
+        "id": uuid.UUID(int=1),
+    }
+    assert orjson.loads(dumps_json(value)) == {
+        "point": [1, 2],
+        "other": {"z": 3},
+        "tags": ["a", "b"],
+        "day": day.toordinal(),
+        "now": datetime.datetime(2024, 1, 2, 3, 4).toordinal(),
+        "id": str(uuid.UUID(int=1)),
+    }
+    for cls in (Point, Tags, datetime.date):
+        unregister_serializer(cls)
+    assert orjson.loads(dumps_json(value))["point"] == {"x": 1, "y": 2}
+
+
+def test_registered_serializer_size_cap() -> None:
+    register_serializer(Celsius, lambda c: "x" * int(c.degrees), max_bytes=50)
+    assert orjson.loads(dumps_json(Celsius(10))) == "x" * 10
+    capped = orjson.loads(dumps_json(Celsius(100)))
+    assert capped.startswith('"xxx')
+    assert capped.endswith("... [truncated 102 bytes]")
+    assert len(capped.encode("utf-8")) <= 50
+
+
+def test_failing_serializer_falls_back() -> None:
+    register_serializer(Point, lambda p: 1 / 0)
+    assert orjson.loads(dumps_json(Point(1, 2))) == {"x": 1, "y": 2}
+
+
+@pytest.mark.parametrize("cls", [dict, str, uuid.UUID])
+def test_register_rejects_json_types(cls: type) -> None:
+    with pytest.raises(ls_utils.LangSmithUserError):
+        register_serializer(cls, str)
@@ -0,0 +1,243 @@
+"""Test the on-disk tracing spool."""
+
+import os
+import time
+import uuid
+from datetime import datetime, timezone
+from pathlib import Path
+from unittest import mock
+
+import requests
+
+from langsmith import run_trees
+from langsmith import schemas as ls_schemas
+from langsmith._internal import _spool
+from langsmith._internal._background_thread import (
+    TracingQueueItem,
+    _tracing_thread_requeue_spooled,
+)
+from langsmith._internal._spool import TracingSpool
+from langsmith._internal._tracing_queue import BoundedTracingQueue
+from langsmith.client import Client
+
+
+def _item(action: str = "create", **kwargs) -> TracingQueueItem:
+    id_ = uuid.uuid4()
+    run = {
+        "id": id_,
+        "trace_id": id_,
+        "dotted_order": run_trees._create_current_dotted_order(
+            datetime.now(timezone.utc), id_
+        ),
+        "name": "my_run",
+        "inputs": {"messages": ["hi"]},
+        **kwargs,
+    }
+    return TracingQueueItem(run["dotted_order"], action, run)
+
+
+def _simulate_exit(spool: TracingSpool) -> None:
+    # The process died without closing the spool.
+    _spool._LIVE_NONCES.discard(spool._nonce)
+
+
+def test_recover_replays_unacknowledged_items(tmp_path: Path) -> None:
+    previous = TracingSpool(str(tmp_path))
+    items = [_item(), _item(), _item("update")]
+    for item in items:
+        assert previous.append(item)
+    previous.ack(items[:1])
+    _simulate_exit(previous)
+
+    spool = TracingSpool(str(tmp_path))
+    assert spool.recover() == 2
+    recovered = spool.due_retries()
+    assert [it.item["id"] for it in recovered] == [
+        str(it.item["id"]) for it in items[1:]
+    ]
+    assert [it.action for it in recovered] == ["create", "update"]
+    assert spool.pending == 2
+    # A third process has nothing left to claim
+    assert TracingSpool(str(tmp_path)).recover() == 0
+
+    spool.ack(recovered)
+    assert spool.pending == 0
+    assert os.listdir(tmp_path) == []
+
+
+def test_recover_skips_live_spools(tmp_path: Path) -> None:
+    live = TracingSpool(str(tmp_path))
+    live.append(_item())
+    assert TracingSpool(str(tmp_path)).recover() == 0
+    assert live.pending == 1
+
+
+def test_recover_restores_attachments(tmp_path: Path) -> None:
+    previous = TracingSpool(str(tmp_path))
+    previous.append(_item(attachments={"img": ("image/png", b"\x89PNG")}))
+    _simulate_exit(previous)
+
+    spool = TracingSpool(str(tmp_path))
+    spool.recover()
+    (recovered,) = spool.due_retries()
+    assert recovered.item["attachments"] == {"img": ("image/png", b"\x89PNG")}
+
+
+def test_failed_items_are_retried_from_disk(tmp_path: Path) -> None:
+    spool = TracingSpool(str(tmp_path), retry_interval=0)
+    item = _item()
+    spool.append(item)
+    # ingestion pops fields off the payload
+    item.item.pop("inputs")
+    spool.nack([item])
+
+    (retry,) = spool.due_retries()
+    assert retry.spool_id == item.spool_id
+    assert retry.item["inputs"] == {"messages": ["hi"]}
+    assert spool.due_retries() == []
+    spool.ack([retry])
+    assert spool.pending == 0
+
+
+def test_items_remember_the_targets_they_were_delivered_to(tmp_path: Path) -> None:
+    previous = TracingSpool(str(tmp_path), retry_interval=0)
+    item = _item()
+    previous.append(item)
+    previous.ack([item], ["http://a"])
+    previous.nack([item])
+    (retry,) = previous.due_retries()
+    assert retry.sent_to == {"http://a"}
+    _simulate_exit(previous)
+
+    spool = TracingSpool(str(tmp_path))
+    spool.recover()
+    (recovered,) = spool.due_retries()
+    assert recovered.sent_to == {"http://a"}
+    spool.ack([recovered], ["http://b"])
+    assert spool.pending == 1
+    spool.ack([recovered])
+    assert spool.pending == 0
+
+
+def test_items_expire_after_max_age(tmp_path: Path) -> None:
+    previous = TracingSpool(str(tmp_path), retry_interval=0, max_age=60)
+    items = [_item(), _item()]
+    for item in items:
+        previous.append(item)
+    previous.nack(items[:1])
+    later = time.time() + 120
+    with mock.patch("time.time", return_value=later):
+        assert previous.due_retries() == []
+    assert previous.pending == 1
+    _simulate_exit(previous)
+
+    spool = TracingSpool(str(tmp_path), max_age=60)
+    with mock.patch("time.time", return_value=later):
+        assert spool.recover() == 0
+    assert spool.pending == 0
+    assert os.listdir(tmp_path) == []
+
+
+def test_recovered_items_are_read_back_in_chunks(tmp_path: Path) -> None:
+    previous = TracingSpool(str(tmp_path))
+    items = [_item() for _ in range(5)]
+    for item in items:
+        previous.append(item)
+    _simulate_exit(previous)
+
+    spool = TracingSpool(str(tmp_path))
+    assert spool.recover() == 5
+    chunks = [spool.due_retries(limit=2) for _ in range(3)]
+    assert [len(chunk) for chunk in chunks] == [2, 2, 1]
+    assert spool.due_retries() == []
+    assert spool.pending == 5
+
+    # The background thread only requeues them as the queue drains
+    spool.nack([item for chunk in chunks for item in chunk])
+    spool._next_retry = 0
+    queue = BoundedTracingQueue()
+    client = mock.Mock(_tracing_spool=spool)
+    _tracing_thread_requeue_spooled(client, queue, 2)
+    _tracing_thread_requeue_spooled(client, queue, 2)
+    assert queue.qsize() == 2
+
+
+def test_segments_rotate_and_are_removed(tmp_path: Path) -> None:
+    spool = TracingSpool(str(tmp_path), segment_bytes=1)
+    items = [_item() for _ in range(3)]
+    for item in items:
+        spool.append(item)
+    assert len(os.listdir(tmp_path)) == 3
+    spool.ack(items[:2])
+    assert len(os.listdir(tmp_path)) == 1
+    spool.ack(items[2:])
+    spool.close()
+    assert os.listdir(tmp_path) == []
+
+
+def test_spool_is_bounded(tmp_path: Path) -> None:
+    spool = TracingSpool(str(tmp_path), max_bytes=1)
+    item = _item()
+    assert not spool.append(item)
+    assert item.spool_id is None
+    assert spool.pending == 0
+
+
+def _response(status: int) -> requests.Response:
+    response = requests.Response()
+    response.status_code = status
+    response._content = b"{}"
+    return response
+
+
+def _client(session: mock.Mock, tmp_path: Path) -> Client:
+    return Client(
+        api_url="http://localhost:1984",
+        api_key="123",
+        session=session,
+        info=ls_schemas.LangSmithInfo(
+            batch_ingest_config=ls_schemas.BatchIngestConfig(
+                use_multipart_endpoint=True,
+                size_limit_bytes=None,
+                size_limit=100,
+                scale_up_nthreads_limit=16,
+                scale_up_qsize_trigger=1000,
+                scale_down_nempty_trigger=4,
+            )
+        ),
+        tracing_spool_dir=str(tmp_path),
+    )
+
+
+def test_client_keeps_runs_until_delivered(tmp_path: Path) -> None:
+    session = mock.Mock()
+    session.request.return_value = _response(500)
+    client = _client(session, tmp_path)
+    item = _item()
+    client.create_run(run_type="llm", **item.item)
+    assert client.tracing_queue is not None
+    client.tracing_queue.join()
+    assert client._tracing_spool is not None
+    assert client._tracing_spool.pending == 1
+
+    # once the API is reachable again the control thread retries the run
+    session.request.return_value = _response(200)
+    client._tracing_spool._next_retry = 0
+    for _ in range(50):
+        if not client._tracing_spool.pending:
+            break
+        time.sleep(0.1)
+    assert client._tracing_spool.pending == 0
+
+
+def test_client_drops_runs_the_api_rejects(tmp_path: Path) -> None:
+    session = mock.Mock()
+    session.request.return_value = _response(422)
+    client = _client(session, tmp_path)
+    item = _item()
+    client.create_run(run_type="llm", **item.item)
+    assert client.tracing_queue is not None
+    client.tracing_queue.join()
+    assert client._tracing_spool is not None
+    # sending an invalid run again won't help
+    assert client._tracing_spool.pending == 0
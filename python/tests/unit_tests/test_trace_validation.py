@@ -0,0 +1,117 @@
+import uuid
+from datetime import datetime, timedelta, timezone
+from typing import Any, Dict, Optional
+from unittest import mock
+
+from langsmith import client as ls_client
+from langsmith import run_trees
+from langsmith._internal._trace_validation import TraceValidator
+
+start = datetime(2024, 1, 1, tzinfo=timezone.utc)
+
+
+def _run(
+    name: str, parent: Optional[Dict[str, Any]] = None, **kwargs: Any
+) -> Dict[str, Any]:
+    run_id = uuid.uuid4()
+    dotted_order = run_trees._create_dotted_order(
+        start, run_id, parent["dotted_order"] if parent else None
+    )
+    return {
+        "id": run_id,
+        "name": name,
+        "trace_id": parent["trace_id"] if parent else run_id,
+        "parent_run_id": parent["id"] if parent else None,
+        "dotted_order": dotted_order,
+        "start_time": start,
+        **kwargs,
+    }
+
+
+def test_valid_trace() -> None:
+    validator = TraceValidator()
+    root = _run("root")
+    child = _run("child", root)
+    grandchild = _run("grandchild", child, end_time=start + timedelta(seconds=1))
+    assert validator.check(root) == []
+    assert validator.check(child) == []
+    assert validator.check(grandchild) == []
+    assert validator.check({**child, "end_time": start.isoformat()}, update=True) == []
+
+
+def test_reports_mistakes_with_run_path() -> None:
+    validator = TraceValidator()
+    root = _run("root")
+    child = _run("child", root)
+    assert validator.check(root) == []
+    assert validator.check(child) == []
+
+    other_root = _run("other")
+    grandchild = _run("grandchild", child)
+    # A parent_run_id that doesn't match the dotted_order, and a trace_id
+    # from another trace
+    wrong = {
+        **grandchild,
+        "parent_run_id": root["id"],
+        "trace_id": other_root["id"],
+    }
+    path = f"root > child > grandchild ({grandchild['id']})"
+    assert validator.check(wrong) == [
+        f"{path}: trace_id {other_root['id']} isn't the root {root['id']} of the"
+        " dotted_order",
+        f"{path}: parent_run_id {root['id']} isn't the parent {child['id']} in"
+        " the dotted_order",
+        f"{path}: trace_id {other_root['id']} isn't the trace_id {root['id']} of"
+        " its parent",
+    ]
+    assert validator.check(wrong)[-1] == f"{path}: run was already created"
+
+    orphan = _run("orphan", _run("missing", root))
+    assert validator.check(orphan)[0].endswith("was never created")
+
+    late = _run("late", root, end_time=start - timedelta(seconds=1))
+    assert validator.check(late) == [
+        f"root > late ({late['id']}): end_time 2023-12-31T23:59:59+00:00 is"
+        " before start_time 2024-01-01T00:00:00+00:00"
+    ]
+
+    patch = _run("patch", root)
+    assert validator.check(patch, update=True) == [
+        f"root > patch ({patch['id']}): run was updated before it was created"
+    ]
+    bare = {"id": uuid.uuid4(), "name": "bare"}
+    assert validator.check(bare) == [
+        f"bare ({bare['id']}): trace_id and dotted_order must be set"
+    ]
+
+
+def test_distributed_traces_skip_parent_check() -> None:
+    validator = TraceValidator()
+    # The parent was created by another service
+    remote_parent = _run("remote")
+    child = _run("child", remote_parent)
+    assert validator.check(child) == []
+
+
+def test_client_logs_invalid_runs() -> None:
+    session = mock.MagicMock()
+    client = ls_client.Client(
+        api_url="http://localhost:1984",
+        api_key="123",
+        session=session,
+        auto_batch_tracing=False,
+        validate_traces=True,
+    )
+    root = _run("root")
+    with mock.patch.object(ls_client.logger, "warning") as warning:
+        client.create_run(**root, run_type="chain", inputs={})
+        client.update_run(
+            uuid.uuid4(),
+            name="stray",
+            trace_id=root["id"],
+            dotted_order=root["dotted_order"],
+        )
+    assert warning.call_count == 1
+    message = warning.call_args.args[0]
+    assert message.startswith("Invalid run stray (")
+    assert message.endswith("run was updated before it was created")
@@ -0,0 +1,134 @@
+"""Test the memory-bounded tracing queue."""
+
+import threading
+import time
+import uuid
+from pathlib import Path
+from typing import List
+from unittest import mock
+
+import orjson
+import pytest
+
+from langsmith import utils as ls_utils
+from langsmith._internal import _tracing_queue
+from langsmith._internal._background_thread import TracingQueueItem
+from langsmith._internal._spool import TracingSpool
+from langsmith._internal._tracing_queue import BoundedTracingQueue
+from langsmith.client import Client
+
+
+def _item(priority: str, size: int = 10) -> TracingQueueItem:
+    run = {"id": str(uuid.uuid4()), "inputs": {"text": "a" * size}}
+    return TracingQueueItem(priority, "create", run)
+
+
+def _drain(queue: BoundedTracingQueue) -> List[str]:
+    priorities = []
+    while queue.qsize():
+        priorities.append(queue.get().priority)
+        queue.task_done()
+    return priorities
+
+
+def test_drop_newest() -> None:
+    queue = BoundedTracingQueue(max_items=2)
+    for priority in "abc":
+        queue.put(_item(priority))
+    assert (queue.dropped_items, queue.qsize()) == (1, 2)
+    assert _drain(queue) == ["a", "b"]
+    queue.join()
+
+
+def test_drop_oldest_by_bytes() -> None:
+    queue = BoundedTracingQueue(max_bytes=400, overflow_policy="drop_oldest")
+    for priority in "abc":
+        queue.put(_item(priority, size=100))
+    assert queue.dropped_items == 1
+    assert queue.dropped_bytes == queue.size_bytes // 2 > 100
+    assert _drain(queue) == ["b", "c"]
+    # Dropped items don't keep join() waiting
+    queue.join()
+    # An item over the byte budget is only accepted into an empty queue
+    queue.put(_item("d", size=1_000))
+    assert queue.qsize() == 1
+
+
+def test_dropped_items_are_removed_from_the_spool(tmp_path: Path) -> None:
+    queue = BoundedTracingQueue(max_items=1, overflow_policy="drop_oldest")
+    queue.spool = TracingSpool(str(tmp_path))
+    items = [_item("b"), _item("a")]
+    for item in items:
+        queue.spool.append(item)
+        queue.put(item)
+    assert queue.dropped_items == 1
+    # Only the item still queued is replayed if the process exits
+    assert queue.spool.pending == 1
+    assert _drain(queue) == ["a"]
+
+
+def test_items_are_sized_without_serializing_them() -> None:
+    queue = BoundedTracingQueue(max_bytes=1_000)
+    item = _item("a", size=100)
+    with mock.patch.object(_tracing_queue, "dumps_json") as dumps_json:
+        queue.put(item)
+    assert not dumps_json.called
+    serialized = len(orjson.dumps(item.item))
+    assert serialized // 2 < item.size_bytes <= serialized
+
+
+def test_block_until_there_is_room() -> None:
+    queue = BoundedTracingQueue(max_items=1, overflow_policy="block")
+    queue.put(_item("a"))
+    producer = threading.Thread(target=queue.put, args=(_item("b"),))
+    producer.start()
+    time.sleep(0.1)
+    assert producer.is_alive()
+    assert queue.get().priority == "a"
+    producer.join(timeout=1)
+    assert not producer.is_alive()
+    assert queue.get().priority == "b"
+    # Callers that can't wait drop the item
+    queue.put(_item("c"))
+    queue.put(_item("d"), timeout=0.01)
+    assert queue.dropped_items == 1
+
+
+def test_spill_to_disk(tmp_path: Path) -> None:
+    queue = BoundedTracingQueue(
+        max_items=2, overflow_policy="spill", spill_dir=str(tmp_path)
+    )
+    for priority in "edcba":
+        queue.put(_item(priority))
+    assert (queue.spilled_items, queue.dropped_items) == (3, 0)
+    assert queue.qsize() == 5
+    assert len(queue.queue) == 2
+    # Items in memory go first, then spilled ones are read back as they fit
+    assert [queue.get().priority for _ in range(2)] == ["d", "e"]
+    spilled = queue.get()
+    assert spilled.priority == "b"
+    assert spilled.item["inputs"] == {"text": "a" * 10}
+    assert _drain(queue) == ["c", "a"]
+    for _ in range(3):
+        queue.task_done()
+    queue.join()
+
+
+def test_client_queue_from_env(monkeypatch: pytest.MonkeyPatch) -> None:
+    ls_utils.get_env_var.cache_clear()
+    monkeypatch.setenv("LANGSMITH_TRACING_QUEUE_MAX_BYTES", "1000")
+    monkeypatch.setenv("LANGSMITH_TRACING_QUEUE_OVERFLOW_POLICY", "drop_oldest")
+    try:
+        client = Client(api_url="http://localhost:1984", api_key="123", info={})
+        queue = client.tracing_queue
+        assert queue is not None
+        assert (queue.max_items, queue.max_bytes) == (0, 1000)
+        assert queue.overflow_policy == "drop_oldest"
+        with pytest.raises(ls_utils.LangSmithUserError, match="drop_all"):
+            Client(
+                api_url="http://localhost:1984",
+                api_key="123",
+                tracing_queue_overflow_policy="drop_all",  # type: ignore[arg-type]
+            )
+    finally:
+        ls_utils.get_env_var.cache_clear()
@@ -0,0 +1,85 @@
+"""Test the tracing pipeline stats."""
+
+import uuid
+from datetime import datetime, timezone
+from unittest import mock
+
+import requests
+
+from langsmith import run_trees
+from langsmith import schemas as ls_schemas
+from langsmith._internal._tracing_stats import TracingStatsRecorder
+from langsmith.client import Client
+
+
+def _create_run(client: Client) -> None:
+    id_ = uuid.uuid4()
+    client.create_run(
+        "my_run",
+        inputs={"messages": ["hi"]},
+        run_type="llm",
+        id=id_,
+        trace_id=id_,
+        dotted_order=run_trees._create_current_dotted_order(
+            datetime.now(timezone.utc), id_
+        ),
+    )
+
+
+def test_to_prometheus() -> None:
+    recorder = TracingStatsRecorder()
+    recorder.increment("runs_sent", 3)
+    for size in (1, 3, 2_000):
+        recorder.observe("batch_size", size)
+    stats = recorder.snapshot(queue_depth=7, runs_dropped=2)
+    assert (stats.runs_sent, stats.queue_depth, stats.runs_dropped) == (3, 7, 2)
+    assert stats.batch_size.buckets[:2] == ((1, 1), (5, 2))
+    assert (stats.batch_size.count, stats.batch_size.sum) == (3, 2_004)
+
+    lines = stats.to_prometheus().splitlines()
+    assert "# TYPE langsmith_tracing_runs_sent_total counter" in lines
+    assert "langsmith_tracing_runs_sent_total 3" in lines
+    assert "langsmith_tracing_queue_depth 7" in lines
+    assert 'langsmith_tracing_batch_size_bucket{le="5"} 2' in lines
+    assert 'langsmith_tracing_batch_size_bucket{le="1000"} 2' in lines
+    assert 'langsmith_tracing_batch_size_bucket{le="+Inf"} 3' in lines
+    assert "langsmith_tracing_batch_size_sum 2004" in lines
+    assert "langsmith_tracing_batch_size_count 3" in lines
+
+
+def test_client_counts_failed_requests() -> None:
+    session = mock.Mock()
+    session.request.return_value.status_code = 500
+    session.request.return_value.raise_for_status.side_effect = requests.HTTPError(
+        "boom"
+    )
+    client = Client(
+        api_url="http://localhost:1984",
+        api_key="123",
+        session=session,
+        info=ls_schemas.LangSmithInfo(
+            batch_ingest_config=ls_schemas.BatchIngestConfig(
+                use_multipart_endpoint=True,
+                size_limit_bytes=None,
+                size_limit=100,
+                scale_up_nthreads_limit=16,
+                scale_up_qsize_trigger=1000,
+                scale_down_nempty_trigger=4,
+            )
+        ),
+    )
+    _create_run(client)
+    assert client.tracing_queue is not None
+    client.tracing_queue.join()
+    # Roots that aren't sampled never reach the queue
+    client.tracing_sample_rate = 0
+    _create_run(client)
+
+    stats = client.get_tracing_stats()
+    assert stats.runs_queued == 1
+    assert stats.runs_filtered == 1
+    assert (stats.batches, stats.runs_sent, stats.runs_failed) == (1, 0, 1)
+    assert (stats.requests, stats.request_failures) == (1, 1)
+    assert stats.request_retries == 2
+    assert stats.request_latency_seconds.count == 1
+    assert stats.queue_depth == 0
@@ -8,7 +8,7 @@ import unittest
 import uuid
 from datetime import datetime
 from enum import Enum
-from typing import Any, NamedTuple, Optional
+from typing import Any, Dict, NamedTuple, Optional
 from unittest.mock import MagicMock, patch
 
 import attr
@@ -273,6 +273,36 @@ def test_deepish_copy():
     assert ls_utils.deepish_copy(my_dict) == my_dict
 
 
+def test_snapshot() -> None:
+    class Counter:
+        def __init__(self) -> None:
+            self.count = 0
+
+    gen = (i for i in range(3))
+    inputs: Dict[str, Any] = {
+        "messages": [{"role": "user", "content": ["hi"]}],
+        "stop": ("a", ["b"]),
+        "ids": {1, 2},
+        "counter": Counter(),
+        "gen": gen,
+        "at": datetime.now(),
+    }
+    inputs["self"] = inputs
+    copied = ls_utils.snapshot(inputs)
+    inputs["messages"][0]["content"].append("there")
+    inputs["stop"][1].append("c")
+    inputs["ids"].add(3)
+    inputs["counter"].count += 1
+    assert copied["messages"] == [{"role": "user", "content": ["hi"]}]
+    assert copied["stop"] == ("a", ["b"])
+    assert copied["ids"] == {1, 2}
+    assert copied["counter"].count == 0
+    # Uncopyable and immutable values are shared
+    assert copied["gen"] is gen
+    assert copied["at"] is inputs["at"]
+    assert copied["self"] is copied
+
+
 def test_is_version_greater_or_equal():
     # Test versions equal to 0.5.23
     assert ls_utils.is_version_greater_or_equal("0.5.23", "0.5.23")